@@ -176,6 +176,14 @@ impl MiddleWare for EndpointMiddleware {
             );
         }
 
+        // Vault only accepts JSON merge patches for PATCH requests
+        if req.method() == http::Method::PATCH {
+            req.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("application/merge-patch+json"),
+            );
+        }
+
         // Optionally wrap response
         if let Some(namespace) = &self.namespace {
             info!("Middleware: adding namespace header {}", namespace);
@@ -258,6 +266,24 @@ where
         .map_err(ClientError::from)
 }
 
+/// Executes an [Endpoint] which is expected to return a raw, non-JSON
+/// response and returns its body.
+///
+/// Any errors which occur in execution are wrapped in a
+/// [ClientError::RestClientError] and propagated.
+pub async fn exec_with_raw<E>(client: &impl Client, endpoint: E) -> Result<Vec<u8>, ClientError>
+where
+    E: Endpoint,
+{
+    info!("Executing {} and expecting a raw response", endpoint.path());
+    endpoint
+        .with_middleware(client.middle())
+        .exec(client.http())
+        .await
+        .map_err(parse_err)
+        .map(|r| r.raw())
+}
+
 /// Executes an [Endpoint] and returns the result.
 ///
 /// The result from the executed endpoint has a few operations performed on it:
@@ -1,2 +1,380 @@
 pub mod requests;
 pub mod responses;
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::ClientError;
+
+/// The type of the private keys of certificates issued by a role
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    /// RSA keys of 2048, 3072, 4096 or 8192 bits (default)
+    #[default]
+    Rsa,
+    /// ECDSA keys using the P-224, P-256, P-384 or P-521 curve
+    Ec,
+    /// Ed25519 keys
+    Ed25519,
+    /// Any key type, only usable when signing CSRs
+    Any,
+}
+
+impl KeyType {
+    /// Returns the key sizes accepted for this type, where 0 selects the
+    /// default size
+    pub fn allowed_bits(&self) -> &'static [u64] {
+        match self {
+            Self::Rsa => &[0, 2048, 3072, 4096, 8192],
+            Self::Ec => &[0, 224, 256, 384, 521],
+            Self::Ed25519 => &[0],
+            Self::Any => &[0],
+        }
+    }
+}
+
+/// Implements case insensitive parsing, formatting and serde support for an
+/// enum of the names accepted by Vault
+macro_rules! named_enum {
+    ($(#[$doc:meta])* $name:ident { $($(#[$vdoc:meta])* $variant:ident => $value:literal,)* }) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vdoc])* $variant,)*
+        }
+
+        impl $name {
+            /// Returns the name used by Vault
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value,)*
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ClientError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(if s.eq_ignore_ascii_case($value) {
+                    return Ok(Self::$variant);
+                })*
+                Err(ClientError::ParseVaultValueError {
+                    kind: stringify!($name).to_string(),
+                    reason: format!("unknown value {}", s),
+                })
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+named_enum!(
+    /// A purpose of the key of certificates issued by a role
+    KeyUsage {
+        DigitalSignature => "DigitalSignature",
+        ContentCommitment => "ContentCommitment",
+        KeyEncipherment => "KeyEncipherment",
+        DataEncipherment => "DataEncipherment",
+        KeyAgreement => "KeyAgreement",
+        CertSign => "CertSign",
+        CrlSign => "CRLSign",
+        EncipherOnly => "EncipherOnly",
+        DecipherOnly => "DecipherOnly",
+    }
+);
+
+named_enum!(
+    /// An extended purpose of the key of certificates issued by a role
+    ExtKeyUsage {
+        Any => "Any",
+        ServerAuth => "ServerAuth",
+        ClientAuth => "ClientAuth",
+        CodeSigning => "CodeSigning",
+        EmailProtection => "EmailProtection",
+        IpsecEndSystem => "IPSECEndSystem",
+        IpsecTunnel => "IPSECTunnel",
+        IpsecUser => "IPSECUser",
+        TimeStamping => "TimeStamping",
+        OcspSigning => "OCSPSigning",
+        MicrosoftServerGatedCrypto => "MicrosoftServerGatedCrypto",
+        NetscapeServerGatedCrypto => "NetscapeServerGatedCrypto",
+        MicrosoftCommercialCodeSigning => "MicrosoftCommercialCodeSigning",
+        MicrosoftKernelCodeSigning => "MicrosoftKernelCodeSigning",
+    }
+);
+
+/// A validation applied to the common name of certificates issued by a role
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CnValidation {
+    /// The common name may be an email address
+    Email,
+    /// The common name may be a hostname
+    Hostname,
+    /// The common name isn't validated, which can't be combined with the
+    /// other validations
+    Disabled,
+}
+
+/// A certificate policy added to certificates issued by a role
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyIdentifier {
+    pub oid: String,
+    /// The URI of the certification practice statement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cps: Option<String>,
+    /// A user notice shown to relying parties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notice: Option<String>,
+}
+
+impl PolicyIdentifier {
+    pub fn new(oid: &str) -> Self {
+        PolicyIdentifier {
+            oid: oid.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The complete definition of a PKI role
+///
+/// Unlike [SetRoleRequest][crate::api::pki::requests::SetRoleRequest], a
+/// role read with [crate::pki::role::read_typed] can be modified and written
+/// back with [crate::pki::role::set_typed]. Durations are in seconds.
+#[derive(Builder, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[builder(setter(into, strip_option), default)]
+pub struct PkiRole {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ttl: Option<u64>,
+    /// A fixed expiry in the `YYYY-MM-ddTHH:MM:SSZ` format, which can't be
+    /// combined with a TTL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_localhost: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_domains: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_domains_template: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_bare_domains: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_subdomains: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_glob_domains: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_wildcard_certificates: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_any_name: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforce_hostnames: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_ip_sans: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_uri_sans: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_uri_sans_template: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_other_sans: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_serial_numbers: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_user_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cn_validations: Option<Vec<CnValidation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_flag: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_flag: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_signing_flag: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_protection_flag: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_type: Option<KeyType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_bits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_bits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_pss: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_usage: Option<Vec<KeyUsage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext_key_usage: Option<Vec<ExtKeyUsage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext_key_usage_oids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_csr_common_name: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_csr_sans: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ou: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locality: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub province: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub street_address: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generate_lease: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_cn: Option<bool>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_policy_identifiers",
+        deserialize_with = "deserialize_policy_identifiers"
+    )]
+    pub policy_identifiers: Option<Vec<PolicyIdentifier>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub basic_constraints_valid_for_non_ca: Option<bool>,
+}
+
+impl PkiRole {
+    /// Checks for settings which Vault rejects or which can't be combined
+    pub fn validate(&self) -> Result<(), ClientError> {
+        let invalid = |reason: String| Err(ClientError::InvalidRoleError { reason });
+        let key_type = self.key_type.unwrap_or_default();
+
+        if let Some(bits) = self.key_bits {
+            if !key_type.allowed_bits().contains(&bits) {
+                return invalid(format!(
+                    "key_bits {} is not supported for key type {:?}",
+                    bits, key_type
+                ));
+            }
+        }
+        if let Some(bits) = self.signature_bits {
+            if ![0, 256, 384, 512].contains(&bits) {
+                return invalid(format!("signature_bits {} is not supported", bits));
+            }
+        }
+        if self.use_pss == Some(true) && !matches!(key_type, KeyType::Rsa | KeyType::Any) {
+            return invalid(format!("use_pss requires an RSA key, not {:?}", key_type));
+        }
+        if let (Some(ttl), Some(max_ttl)) = (self.ttl, self.max_ttl) {
+            if max_ttl > 0 && ttl > max_ttl {
+                return invalid(format!("ttl {} is larger than max_ttl {}", ttl, max_ttl));
+            }
+        }
+        let not_after = self.not_after.as_deref().unwrap_or_default();
+        if !not_after.is_empty() && self.ttl.unwrap_or_default() > 0 {
+            return invalid("ttl and not_after can't both be set".to_string());
+        }
+        if let Some(validations) = &self.cn_validations {
+            if validations.len() > 1 && validations.contains(&CnValidation::Disabled) {
+                return invalid("cn_validations can't combine disabled with others".to_string());
+            }
+        }
+        if let Some(ids) = &self.policy_identifiers {
+            if ids.iter().any(|id| !is_oid(&id.oid)) {
+                return invalid("policy_identifiers must be dotted OIDs".to_string());
+            }
+        }
+        if let Some(oids) = &self.ext_key_usage_oids {
+            if oids.iter().any(|oid| !is_oid(oid)) {
+                return invalid("ext_key_usage_oids must be dotted OIDs".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_oid(value: &str) -> bool {
+    let mut arcs = value.split('.');
+    value.contains('.')
+        && arcs.all(|arc| !arc.is_empty() && arc.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Policies with only an OID are sent as a list of OIDs, others as the JSON
+/// encoded list of objects which Vault expects for extended policies
+fn serialize_policy_identifiers<S: Serializer>(
+    ids: &Option<Vec<PolicyIdentifier>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let ids = ids.as_deref().unwrap_or_default();
+    if ids.iter().all(|id| id.cps.is_none() && id.notice.is_none()) {
+        serializer.collect_seq(ids.iter().map(|id| &id.oid))
+    } else {
+        let json = serde_json::to_string(ids).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+}
+
+/// Vault returns policies as OIDs, objects or JSON encoded objects depending
+/// on how they were written
+fn deserialize_policy_identifiers<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<PolicyIdentifier>>, D::Error> {
+    fn parse<E: serde::de::Error>(value: Value, ids: &mut Vec<PolicyIdentifier>) -> Result<(), E> {
+        match value {
+            Value::Null => Ok(()),
+            Value::Array(values) => values.into_iter().try_for_each(|v| parse(v, ids)),
+            Value::Object(_) => {
+                ids.push(serde_json::from_value(value).map_err(E::custom)?);
+                Ok(())
+            }
+            Value::String(s) if s.trim_start().starts_with(['[', '{']) => {
+                parse(serde_json::from_str(&s).map_err(E::custom)?, ids)
+            }
+            Value::String(s) => {
+                ids.extend(
+                    s.split(',')
+                        .map(str::trim)
+                        .filter(|oid| !oid.is_empty())
+                        .map(PolicyIdentifier::new),
+                );
+                Ok(())
+            }
+            other => Err(E::custom(format!("invalid policy identifier {}", other))),
+        }
+    }
+
+    let value = Option::<Value>::deserialize(deserializer)?;
+    match value {
+        None => Ok(None),
+        Some(value) => {
+            let mut ids = Vec::new();
+            parse(value, &mut ids)?;
+            Ok(Some(ids))
+        }
+    }
+}
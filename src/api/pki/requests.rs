@@ -1,10 +1,19 @@
+use std::collections::HashMap;
+
 use super::responses::{
-    GenerateCertificateResponse, GenerateIntermediateResponse, GenerateRootResponse,
-    ListCertificatesResponse, ListRolesResponse, ReadCRLConfigResponse, ReadCertificateResponse,
-    ReadRoleResponse, ReadURLsResponse, RevokeCertificateResponse, RotateCRLsResponse,
-    SignCertificateResponse, SignIntermediateResponse, SignSelfIssuedResponse,
+    GenerateCertificateResponse, GenerateEabResponse, GenerateIntermediateResponse,
+    GenerateKeyResponse, GenerateRootResponse, ImportIssuersResponse, ListAcmeAccountsResponse,
+    ListCertificatesResponse, ListEabResponse, ListIssuersResponse, ListKeysResponse,
+    ListRolesResponse, ReadAcmeAccountResponse, ReadAcmeConfigResponse, ReadAutoTidyConfigResponse,
+    ReadCRLConfigResponse, ReadCertificateMetadataResponse, ReadCertificateResponse,
+    ReadClusterConfigResponse, ReadEstConfigResponse, ReadIssuerResponse,
+    ReadIssuersConfigResponse, ReadKeyResponse, ReadRoleResponse, ReadURLsResponse,
+    RevokeCertificateResponse, RotateCRLsResponse, SignCertificateResponse,
+    SignIntermediateResponse, SignSelfIssuedResponse, TidyStatusResponse,
 };
+use super::PkiRole;
 use rustify_derive::Endpoint;
+use serde::Serialize;
 
 /// ## Submit CA Information
 /// This endpoint allows submitting the CA information for the backend via a PEM
@@ -57,6 +66,9 @@ pub struct GenerateRootRequest {
     pub key_bits: Option<u64>,
     pub key_type: Option<String>,
     pub ip_sans: Option<String>,
+    pub issuer_name: Option<String>,
+    pub key_name: Option<String>,
+    pub key_ref: Option<String>,
     pub max_path_length: Option<i32>,
     pub organization: Option<Vec<String>>,
     pub other_sans: Option<Vec<String>>,
@@ -186,6 +198,37 @@ pub struct SignSelfIssuedRequest {
     pub certificate: String,
 }
 
+/// ## Sign Verbatim
+/// This endpoint signs a new certificate based upon the provided CSR. Values
+/// are taken verbatim from the CSR; the only restriction is that this
+/// endpoint will refuse to issue an intermediate CA certificate.
+///
+/// * Path: {self.mount}/sign-verbatim
+/// * Method: POST
+/// * Response: [SignCertificateResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#sign-verbatim
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/sign-verbatim",
+    method = "POST",
+    response = "SignCertificateResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SignVerbatimRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub csr: String,
+    pub ext_key_usage: Option<Vec<String>>,
+    pub ext_key_usage_oids: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub key_usage: Option<Vec<String>>,
+    pub not_after: Option<String>,
+    pub signature_bits: Option<u64>,
+    pub ttl: Option<String>,
+    pub use_pss: Option<bool>,
+}
+
 /// ## List Certificates
 /// This endpoint returns a list of the current certificates by serial number
 /// only.
@@ -231,6 +274,29 @@ pub struct ReadCertificateRequest {
     pub serial: String,
 }
 
+/// ## Read Certificate Metadata
+/// This endpoint retrieves the metadata stored along with a certificate,
+/// including the role which issued it. Metadata is only stored by Vault
+/// Enterprise for roles with `no_store_metadata` disabled.
+///
+/// * Path: {self.mount}/cert-metadata/{self.serial}
+/// * Method: GET
+/// * Response: [ReadCertificateMetadataResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-certificate-metadata
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/cert-metadata/{self.serial}",
+    response = "ReadCertificateMetadataResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadCertificateMetadataRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub serial: String,
+}
+
 /// ## Generate Certificate
 /// This endpoint generates a new set of credentials (private key and
 /// certificate) based on the role named in the endpoint. The issuing CA
@@ -325,6 +391,15 @@ pub struct SetCRLConfigRequest {
     pub mount: String,
     pub expiry: Option<String>,
     pub disable: Option<bool>,
+    pub ocsp_disable: Option<bool>,
+    pub ocsp_expiry: Option<String>,
+    pub auto_rebuild: Option<bool>,
+    pub auto_rebuild_grace_period: Option<String>,
+    pub enable_delta: Option<bool>,
+    pub delta_rebuild_interval: Option<String>,
+    pub cross_cluster_revocation: Option<bool>,
+    pub unified_crl: Option<bool>,
+    pub unified_crl_on_existing_paths: Option<bool>,
 }
 
 /// ## Rotate CRLs
@@ -349,6 +424,91 @@ pub struct RotateCRLsRequest {
     pub mount: String,
 }
 
+/// ## Read CRL
+/// This endpoint retrieves the current CRL of the default issuer in DER
+/// format. The response is returned as is and isn't wrapped in the usual
+/// Vault response.
+///
+/// * Path: {self.mount}/crl
+/// * Method: GET
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuer-crl
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/crl", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadCRLRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read CRL (PEM)
+/// This endpoint retrieves the current CRL of the default issuer in PEM
+/// format.
+///
+/// * Path: {self.mount}/crl/pem
+/// * Method: GET
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuer-crl
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/crl/pem", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadCRLPemRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read Delta CRL
+/// This endpoint retrieves the current delta CRL of the default issuer in
+/// DER format. Delta CRLs are only built when `enable_delta` is set in the
+/// CRL configuration.
+///
+/// * Path: {self.mount}/crl/delta
+/// * Method: GET
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuer-crl
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/crl/delta", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadDeltaCRLRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read Delta CRL (PEM)
+/// This endpoint retrieves the current delta CRL of the default issuer in
+/// PEM format.
+///
+/// * Path: {self.mount}/crl/delta/pem
+/// * Method: GET
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuer-crl
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/crl/delta/pem", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadDeltaCRLPemRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## OCSP Request
+/// This endpoint queries the OCSP responder of the mount. The request is the
+/// URL encoded base64 of a DER encoded OCSP request and the DER encoded OCSP
+/// response is returned as is.
+///
+/// * Path: {self.mount}/ocsp/{self.request}
+/// * Method: GET
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#ocsp-request
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/ocsp/{self.request}", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct OCSPRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub request: String,
+}
+
 /// ## Read URLs
 /// This endpoint fetches the URLs to be encoded in generated certificates.
 ///
@@ -388,6 +548,48 @@ pub struct SetURLsRequest {
     pub ocsp_servers: Option<Vec<String>>,
 }
 
+/// ## Read Cluster Configuration
+/// This endpoint fetches the cluster-local configuration used to template
+/// the AIA URLs of issuers.
+///
+/// * Path: {self.mount}/config/cluster
+/// * Method: GET
+/// * Response: [ReadClusterConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-cluster-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/cluster",
+    response = "ReadClusterConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadClusterConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Set Cluster Configuration
+/// This endpoint sets the cluster-local path to this mount and the path to
+/// its AIA distribution points, used when templating the AIA URLs of issuers.
+///
+/// * Path: {self.mount}/config/cluster
+/// * Method: POST
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#set-cluster-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/cluster",
+    method = "POST",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SetClusterConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub path: Option<String>,
+    pub aia_path: Option<String>,
+}
+
 /// ## Generate Intermediate
 /// This endpoint generates a new private key and a CSR for signing. If using
 /// Vault as a root, and for many other CAs, the various parameters on the final
@@ -419,6 +621,9 @@ pub struct GenerateIntermediateRequest {
     pub locality: Option<Vec<String>>,
     pub key_bits: Option<u64>,
     pub key_format: Option<String>,
+    pub key_name: Option<String>,
+    pub key_ref: Option<String>,
+    pub key_type: Option<String>,
     pub ip_sans: Option<String>,
     pub organization: Option<Vec<String>>,
     pub other_sans: Option<Vec<String>>,
@@ -555,6 +760,52 @@ pub struct SetRoleRequest {
     pub use_csr_sans: Option<bool>,
 }
 
+/// ## Read Role
+/// This endpoint queries the role definition as a [PkiRole], which can be
+/// written back with [SetPkiRoleRequest].
+///
+/// * Path: {self.mount}/roles/{self.name}
+/// * Method: GET
+/// * Response: [PkiRole]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-role
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/roles/{self.name}",
+    response = "PkiRole",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadPkiRoleRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub name: String,
+}
+
+/// ## Create/Update Role
+/// This endpoint creates or updates the role definition from a [PkiRole].
+/// Settings which aren't set in the role are reset to their defaults.
+///
+/// * Path: {self.mount}/roles/{self.name}
+/// * Method: POST
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#create-update-role
+#[derive(Builder, Debug, Default, Endpoint, Serialize)]
+#[endpoint(
+    path = "{self.mount}/roles/{self.name}",
+    method = "POST",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SetPkiRoleRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub name: String,
+    #[serde(flatten)]
+    pub role: PkiRole,
+}
+
 /// ## Delete Role
 /// This endpoint deletes the role definition. Deleting a role does not revoke
 /// certificates previously issued under this role.
@@ -599,5 +850,839 @@ pub struct TidyRequest {
     pub mount: String,
     pub tidy_cert_store: Option<bool>,
     pub tidy_revoked_certs: Option<bool>,
+    pub tidy_revoked_cert_issuer_associations: Option<bool>,
+    pub tidy_expired_issuers: Option<bool>,
+    pub tidy_move_legacy_ca_bundle: Option<bool>,
+    pub tidy_revocation_queue: Option<bool>,
+    pub tidy_cross_cluster_revoked_certs: Option<bool>,
+    pub tidy_acme: Option<bool>,
+    pub safety_buffer: Option<String>,
+    pub issuer_safety_buffer: Option<String>,
+    pub revocation_queue_safety_buffer: Option<String>,
+    pub acme_account_safety_buffer: Option<String>,
+    pub pause_duration: Option<String>,
+}
+
+/// ## Tidy Status
+/// This endpoint allows monitoring the status of the running or last run
+/// tidy operation.
+///
+/// * Path: {self.mount}/tidy-status
+/// * Method: GET
+/// * Response: [TidyStatusResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#tidy-status
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/tidy-status",
+    response = "TidyStatusResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct TidyStatusRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Cancel Tidy
+/// This endpoint allows cancelling a running tidy operation. The tidy stops
+/// after the certificate it's currently processing.
+///
+/// * Path: {self.mount}/tidy-cancel
+/// * Method: POST
+/// * Response: [TidyStatusResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#cancel-tidy
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/tidy-cancel",
+    method = "POST",
+    response = "TidyStatusResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct CancelTidyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read Automatic Tidy Configuration
+/// This endpoint fetches the configuration of the periodic tidy operation.
+///
+/// * Path: {self.mount}/config/auto-tidy
+/// * Method: GET
+/// * Response: [ReadAutoTidyConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-automatic-tidy-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/auto-tidy",
+    response = "ReadAutoTidyConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadAutoTidyConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Configure Automatic Tidy
+/// This endpoint configures a tidy operation which runs periodically with
+/// the given parameters.
+///
+/// * Path: {self.mount}/config/auto-tidy
+/// * Method: POST
+/// * Response: [ReadAutoTidyConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#configure-automatic-tidy
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/auto-tidy",
+    method = "POST",
+    response = "ReadAutoTidyConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SetAutoTidyConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub enabled: Option<bool>,
+    pub interval_duration: Option<String>,
+    pub tidy_cert_store: Option<bool>,
+    pub tidy_revoked_certs: Option<bool>,
+    pub tidy_revoked_cert_issuer_associations: Option<bool>,
+    pub tidy_expired_issuers: Option<bool>,
+    pub tidy_move_legacy_ca_bundle: Option<bool>,
+    pub tidy_revocation_queue: Option<bool>,
+    pub tidy_cross_cluster_revoked_certs: Option<bool>,
+    pub tidy_acme: Option<bool>,
     pub safety_buffer: Option<String>,
+    pub issuer_safety_buffer: Option<String>,
+    pub revocation_queue_safety_buffer: Option<String>,
+    pub acme_account_safety_buffer: Option<String>,
+    pub pause_duration: Option<String>,
+}
+
+/// ## List Issuers
+/// This endpoint returns a list of issuers currently provisioned in this mount.
+///
+/// * Path: {self.mount}/issuers
+/// * Method: LIST
+/// * Response: [ListIssuersResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#list-issuers
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuers",
+    method = "LIST",
+    response = "ListIssuersResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ListIssuersRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read Issuer
+/// This endpoint returns the configuration and certificate of an issuer. The
+/// reference may be the issuer's ID, its name or `default`.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}
+/// * Method: GET
+/// * Response: [ReadIssuerResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuer
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}",
+    response = "ReadIssuerResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadIssuerRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+}
+
+/// ## Update Issuer
+/// This endpoint updates the configuration of an issuer. Fields which aren't
+/// set are reset to their default values.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}
+/// * Method: POST
+/// * Response: [ReadIssuerResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#update-issuer
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}",
+    method = "POST",
+    response = "ReadIssuerResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct UpdateIssuerRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    pub crl_distribution_points: Option<Vec<String>>,
+    pub enable_aia_url_templating: Option<bool>,
+    pub issuer_name: Option<String>,
+    pub issuing_certificates: Option<Vec<String>>,
+    pub leaf_not_after_behavior: Option<String>,
+    pub manual_chain: Option<Vec<String>>,
+    pub ocsp_servers: Option<Vec<String>>,
+    pub revocation_signature_algorithm: Option<String>,
+    pub usage: Option<String>,
+}
+
+/// ## Patch Issuer
+/// This endpoint updates the given fields of an issuer's configuration and
+/// keeps the others unchanged.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}
+/// * Method: PATCH
+/// * Response: [ReadIssuerResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#patch-issuer
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}",
+    method = "PATCH",
+    response = "ReadIssuerResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct PatchIssuerRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    pub crl_distribution_points: Option<Vec<String>>,
+    pub enable_aia_url_templating: Option<bool>,
+    pub issuer_name: Option<String>,
+    pub issuing_certificates: Option<Vec<String>>,
+    pub leaf_not_after_behavior: Option<String>,
+    pub manual_chain: Option<Vec<String>>,
+    pub ocsp_servers: Option<Vec<String>>,
+    pub revocation_signature_algorithm: Option<String>,
+    pub usage: Option<String>,
+}
+
+/// ## Delete Issuer
+/// This endpoint deletes the specified issuer. The key used by the issuer is
+/// kept.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}
+/// * Method: DELETE
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#delete-issuer
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}",
+    method = "DELETE",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct DeleteIssuerRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+}
+
+/// ## Import CA Certificates and Keys
+/// This endpoint imports the CA certificates and private keys of a PEM bundle
+/// as issuers and keys. Certificates and keys which already exist are not
+/// imported again.
+///
+/// * Path: {self.mount}/issuers/import/bundle
+/// * Method: POST
+/// * Response: [ImportIssuersResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#import-ca-certificates-and-keys
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuers/import/bundle",
+    method = "POST",
+    response = "ImportIssuersResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ImportIssuersRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub pem_bundle: String,
+}
+
+/// ## Read Issuers Configuration
+/// This endpoint returns the default issuer of the mount.
+///
+/// * Path: {self.mount}/config/issuers
+/// * Method: GET
+/// * Response: [ReadIssuersConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-issuers-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/issuers",
+    response = "ReadIssuersConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadIssuersConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Set Issuers Configuration
+/// This endpoint sets the default issuer of the mount, which is used by the
+/// endpoints that don't reference an issuer.
+///
+/// * Path: {self.mount}/config/issuers
+/// * Method: POST
+/// * Response: [ReadIssuersConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#set-issuers-configuration
+#[derive(Builder, Debug, Default, Endpoint, Serialize)]
+#[endpoint(
+    path = "{self.mount}/config/issuers",
+    method = "POST",
+    response = "ReadIssuersConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SetIssuersConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[serde(rename = "default")]
+    pub default_issuer: String,
+    pub default_follows_latest_issuer: Option<bool>,
+}
+
+/// ## Sign Certificate With Issuer
+/// This endpoint signs a new certificate based upon the provided CSR and the
+/// role named in the endpoint, using the referenced issuer instead of the
+/// default one.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}/sign/{self.role}
+/// * Method: POST
+/// * Response: [SignCertificateResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#sign-certificate
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}/sign/{self.role}",
+    method = "POST",
+    response = "SignCertificateResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct IssuerSignCertificateRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    #[endpoint(skip)]
+    pub role: String,
+    pub alt_names: Option<String>,
+    pub common_name: Option<String>,
+    pub csr: Option<String>,
+    pub exclude_cn_from_sans: Option<bool>,
+    pub format: Option<String>,
+    pub ip_sans: Option<String>,
+    pub other_sans: Option<Vec<String>>,
+    pub serial_number: Option<String>,
+    pub ttl: Option<String>,
+    pub uri_sans: Option<String>,
+}
+
+/// ## Generate Certificate With Issuer
+/// This endpoint generates a new set of credentials (private key and
+/// certificate) based on the role named in the endpoint, using the referenced
+/// issuer instead of the default one.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}/issue/{self.role}
+/// * Method: POST
+/// * Response: [GenerateCertificateResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#generate-certificate-and-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}/issue/{self.role}",
+    method = "POST",
+    response = "GenerateCertificateResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct IssuerGenerateCertificateRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    #[endpoint(skip)]
+    pub role: String,
+    pub alt_names: Option<String>,
+    pub common_name: Option<String>,
+    pub exclude_cn_from_sans: Option<bool>,
+    pub format: Option<String>,
+    pub ip_sans: Option<String>,
+    pub other_sans: Option<Vec<String>>,
+    pub private_key_format: Option<String>,
+    pub ttl: Option<String>,
+    pub uri_sans: Option<String>,
+}
+
+/// ## Sign Intermediate With Issuer
+/// This endpoint uses the referenced issuer to issue a certificate with
+/// appropriate values for acting as an intermediate CA.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}/sign-intermediate
+/// * Method: POST
+/// * Response: [SignIntermediateResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#sign-intermediate
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}/sign-intermediate",
+    method = "POST",
+    response = "SignIntermediateResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct IssuerSignIntermediateRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    pub alt_names: Option<String>,
+    pub common_name: Option<String>,
+    pub country: Option<Vec<String>>,
+    pub csr: Option<String>,
+    pub exclude_cn_from_sans: Option<bool>,
+    pub format: Option<String>,
+    pub locality: Option<Vec<String>>,
+    pub ip_sans: Option<String>,
+    pub max_path_length: Option<i32>,
+    pub organization: Option<Vec<String>>,
+    pub other_sans: Option<Vec<String>>,
+    pub ou: Option<Vec<String>>,
+    pub permitted_dns_domains: Option<Vec<String>>,
+    pub postal_code: Option<Vec<String>>,
+    pub province: Option<Vec<String>>,
+    pub serial_number: Option<String>,
+    pub street_address: Option<Vec<String>>,
+    pub ttl: Option<String>,
+    pub uri_sans: Option<String>,
+    pub use_csr_values: Option<bool>,
+}
+
+/// ## Sign Self-Issued With Issuer
+/// This endpoint uses the referenced issuer to sign a self-issued certificate.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}/sign-self-issued
+/// * Method: POST
+/// * Response: [SignSelfIssuedResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#sign-self-issued
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}/sign-self-issued",
+    method = "POST",
+    response = "SignSelfIssuedResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct IssuerSignSelfIssuedRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    pub certificate: String,
+    pub require_matching_certificate_algorithms: Option<bool>,
+}
+
+/// ## Sign Verbatim With Issuer
+/// This endpoint uses the referenced issuer to sign a CSR without applying
+/// the restrictions of a role. The values of the CSR are kept as they are.
+///
+/// * Path: {self.mount}/issuer/{self.issuer_ref}/sign-verbatim
+/// * Method: POST
+/// * Response: [SignCertificateResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#sign-verbatim
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/issuer/{self.issuer_ref}/sign-verbatim",
+    method = "POST",
+    response = "SignCertificateResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct IssuerSignVerbatimRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub issuer_ref: String,
+    pub csr: String,
+    pub ext_key_usage: Option<Vec<String>>,
+    pub ext_key_usage_oids: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub key_usage: Option<Vec<String>>,
+    pub not_after: Option<String>,
+    pub signature_bits: Option<u64>,
+    pub ttl: Option<String>,
+    pub use_pss: Option<bool>,
+}
+
+/// ## List Keys
+/// This endpoint returns a list of keys currently provisioned in this mount.
+///
+/// * Path: {self.mount}/keys
+/// * Method: LIST
+/// * Response: [ListKeysResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#list-keys
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys",
+    method = "LIST",
+    response = "ListKeysResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ListKeysRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read Key
+/// This endpoint returns the details of a key. The reference may be the key's
+/// ID, its name or `default`.
+///
+/// * Path: {self.mount}/key/{self.key_ref}
+/// * Method: GET
+/// * Response: [ReadKeyResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#read-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/key/{self.key_ref}",
+    response = "ReadKeyResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_ref: String,
+}
+
+/// ## Update Key
+/// This endpoint updates the name of a key.
+///
+/// * Path: {self.mount}/key/{self.key_ref}
+/// * Method: POST
+/// * Response: [ReadKeyResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#update-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/key/{self.key_ref}",
+    method = "POST",
+    response = "ReadKeyResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct UpdateKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_ref: String,
+    pub key_name: String,
+}
+
+/// ## Delete Key
+/// This endpoint deletes a key. Keys which are still used by an issuer can't
+/// be deleted.
+///
+/// * Path: {self.mount}/key/{self.key_ref}
+/// * Method: DELETE
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#delete-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/key/{self.key_ref}",
+    method = "DELETE",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct DeleteKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_ref: String,
+}
+
+/// ## Import Key
+/// This endpoint imports a PEM encoded private key.
+///
+/// * Path: {self.mount}/keys/import
+/// * Method: POST
+/// * Response: [ReadKeyResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#import-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys/import",
+    method = "POST",
+    response = "ReadKeyResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ImportKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub pem_bundle: String,
+    pub key_name: Option<String>,
+}
+
+/// ## Generate Key
+/// This endpoint generates a new private key. If the path ends with
+/// `exported`, the private key will be returned in the response; if it is
+/// `internal` the private key will not be returned and cannot be retrieved
+/// later. With `kms`, the key is backed by the given managed key.
+///
+/// * Path: {self.mount}/keys/generate/{self.key_kind}
+/// * Method: POST
+/// * Response: [GenerateKeyResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#generate-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys/generate/{self.key_kind}",
+    method = "POST",
+    response = "GenerateKeyResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct GenerateKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_kind: String,
+    pub key_bits: Option<u64>,
+    pub key_name: Option<String>,
+    pub key_type: Option<String>,
+    pub managed_key_id: Option<String>,
+    pub managed_key_name: Option<String>,
+}
+
+/// ## Read ACME Configuration
+/// This endpoint fetches the configuration of the ACME server of the mount.
+///
+/// * Path: {self.mount}/config/acme
+/// * Method: GET
+/// * Response: [ReadAcmeConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#get-acme-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/acme",
+    response = "ReadAcmeConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadAcmeConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Set ACME Configuration
+/// This endpoint enables and configures the ACME server of the mount,
+/// restricting which issuers and roles ACME clients can use and whether
+/// they must be bound to an external account.
+///
+/// * Path: {self.mount}/config/acme
+/// * Method: POST
+/// * Response: [ReadAcmeConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#set-acme-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/acme",
+    method = "POST",
+    response = "ReadAcmeConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SetAcmeConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub enabled: Option<bool>,
+    pub allowed_issuers: Option<Vec<String>>,
+    pub allowed_roles: Option<Vec<String>>,
+    pub allow_role_ext_key_usage: Option<bool>,
+    pub default_directory_policy: Option<String>,
+    pub dns_resolver: Option<String>,
+    pub eab_policy: Option<String>,
+    pub max_ttl: Option<String>,
+}
+
+/// ## Generate ACME EAB Key
+/// This endpoint generates an External Account Binding key for an ACME
+/// directory, which an ACME client uses to bind its new account.
+///
+/// The directory is `acme` for the default directory of the mount, or a
+/// scoped one such as `roles/{role}/acme` or `issuer/{issuer}/acme`.
+///
+/// * Path: {self.mount}/{self.directory}/new-eab
+/// * Method: POST
+/// * Response: [GenerateEabResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#get-acme-eab-binding-token
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/{self.directory}/new-eab",
+    method = "POST",
+    response = "GenerateEabResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct GenerateEabRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub directory: String,
+}
+
+/// ## List Unused ACME EAB Keys
+/// This endpoint returns the External Account Binding keys which weren't
+/// used to create an account yet.
+///
+/// * Path: {self.mount}/eab
+/// * Method: LIST
+/// * Response: [ListEabResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#list-unused-acme-eab-binding-keys
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/eab",
+    method = "LIST",
+    response = "ListEabResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ListEabRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Delete ACME EAB Key
+/// This endpoint revokes an unused External Account Binding key.
+///
+/// * Path: {self.mount}/eab/{self.key_id}
+/// * Method: DELETE
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#delete-unused-acme-eab-binding-keys
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/eab/{self.key_id}",
+    method = "DELETE",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct DeleteEabRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_id: String,
+}
+
+/// ## List ACME Accounts
+/// This endpoint returns the key IDs of the ACME accounts of the mount.
+///
+/// * Path: {self.mount}/acme/mgmt/account/keyid
+/// * Method: LIST
+/// * Response: [ListAcmeAccountsResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#list-acme-account-keys
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/acme/mgmt/account/keyid",
+    method = "LIST",
+    response = "ListAcmeAccountsResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ListAcmeAccountsRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Read ACME Account
+/// This endpoint fetches an ACME account along with its orders.
+///
+/// * Path: {self.mount}/acme/mgmt/account/keyid/{self.key_id}
+/// * Method: GET
+/// * Response: [ReadAcmeAccountResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#get-acme-account-info
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/acme/mgmt/account/keyid/{self.key_id}",
+    response = "ReadAcmeAccountResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadAcmeAccountRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_id: String,
+}
+
+/// ## Update ACME Account
+/// This endpoint updates the status of an ACME account, for example to
+/// revoke it.
+///
+/// * Path: {self.mount}/acme/mgmt/account/keyid/{self.key_id}
+/// * Method: POST
+/// * Response: [ReadAcmeAccountResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki#update-acme-account-info
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/acme/mgmt/account/keyid/{self.key_id}",
+    method = "POST",
+    response = "ReadAcmeAccountResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct UpdateAcmeAccountRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub key_id: String,
+    pub status: Option<String>,
+}
+
+/// ## Read EST Configuration
+/// This endpoint fetches the configuration of the EST server of the mount.
+/// EST is only available in Vault Enterprise.
+///
+/// * Path: {self.mount}/config/est
+/// * Method: GET
+/// * Response: [ReadEstConfigResponse]
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki/issuance#read-est-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/config/est",
+    response = "ReadEstConfigResponse",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ReadEstConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Set EST Configuration
+/// This endpoint enables and configures the EST server of the mount. EST is
+/// only available in Vault Enterprise.
+///
+/// * Path: {self.mount}/config/est
+/// * Method: POST
+/// * Response: N/A
+/// * Reference: https://developer.hashicorp.com/vault/api-docs/secret/pki/issuance#set-est-configuration
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(path = "{self.mount}/config/est", method = "POST", builder = "true")]
+#[builder(setter(into, strip_option), default)]
+pub struct SetEstConfigRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    pub enabled: Option<bool>,
+    pub default_mount: Option<bool>,
+    pub default_path_policy: Option<String>,
+    pub label_to_path_policy: Option<HashMap<String, String>>,
+    pub authenticators: Option<HashMap<String, HashMap<String, String>>>,
+    pub enable_sentinel_parsing: Option<bool>,
+    pub audit_fields: Option<Vec<String>>,
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Response from executing
@@ -17,6 +19,7 @@ pub struct GenerateCertificateResponse {
 #[derive(Deserialize, Debug, Serialize)]
 pub struct GenerateIntermediateResponse {
     pub csr: String,
+    pub key_id: Option<String>,
     pub private_key: Option<String>,
     pub private_key_type: Option<String>,
 }
@@ -26,7 +29,11 @@ pub struct GenerateIntermediateResponse {
 #[derive(Deserialize, Debug, Serialize)]
 pub struct GenerateRootResponse {
     pub certificate: String,
+    pub issuer_id: Option<String>,
+    pub issuer_name: Option<String>,
     pub issuing_ca: String,
+    pub key_id: Option<String>,
+    pub key_name: Option<String>,
     pub serial_number: String,
 }
 
@@ -52,12 +59,32 @@ pub struct ReadCertificateResponse {
     pub revocation_time: i64,
 }
 
+/// Response from executing
+/// [ReadCertificateMetadataRequest][crate::api::pki::requests::ReadCertificateMetadataRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadCertificateMetadataResponse {
+    pub cert_metadata: Option<String>,
+    pub expiration: Option<String>,
+    pub issuer_id: Option<String>,
+    pub role: Option<String>,
+    pub serial_number: String,
+}
+
 /// Response from executing
 /// [ReadCRLConfigRequest][crate::api::pki::requests::ReadCRLConfigRequest]
 #[derive(Deserialize, Debug, Serialize)]
 pub struct ReadCRLConfigResponse {
     pub disable: bool,
     pub expiry: String,
+    pub ocsp_disable: Option<bool>,
+    pub ocsp_expiry: Option<String>,
+    pub auto_rebuild: Option<bool>,
+    pub auto_rebuild_grace_period: Option<String>,
+    pub enable_delta: Option<bool>,
+    pub delta_rebuild_interval: Option<String>,
+    pub cross_cluster_revocation: Option<bool>,
+    pub unified_crl: Option<bool>,
+    pub unified_crl_on_existing_paths: Option<bool>,
 }
 
 /// Response from executing
@@ -83,6 +110,71 @@ pub struct ReadURLsResponse {
     pub ocsp_servers: Vec<String>,
 }
 
+/// Response from executing
+/// [ReadClusterConfigRequest][crate::api::pki::requests::ReadClusterConfigRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadClusterConfigResponse {
+    pub path: String,
+    pub aia_path: Option<String>,
+}
+
+/// Response from executing
+/// [TidyStatusRequest][crate::api::pki::requests::TidyStatusRequest]
+///
+/// Durations are in seconds. Most fields are only set once a tidy operation
+/// was started.
+#[derive(Deserialize, Debug, Serialize)]
+pub struct TidyStatusResponse {
+    /// One of `Inactive`, `Running`, `Finished`, `Error`, `Cancelling` or
+    /// `Cancelled`
+    pub state: String,
+    pub error: Option<String>,
+    pub message: Option<String>,
+    pub time_started: Option<String>,
+    pub time_finished: Option<String>,
+    pub safety_buffer: Option<u64>,
+    pub issuer_safety_buffer: Option<u64>,
+    pub pause_duration: Option<String>,
+    pub tidy_cert_store: Option<bool>,
+    pub tidy_revoked_certs: Option<bool>,
+    pub tidy_revoked_cert_issuer_associations: Option<bool>,
+    pub tidy_expired_issuers: Option<bool>,
+    pub tidy_move_legacy_ca_bundle: Option<bool>,
+    pub tidy_revocation_queue: Option<bool>,
+    pub tidy_cross_cluster_revoked_certs: Option<bool>,
+    pub cert_store_deleted_count: Option<u64>,
+    pub revoked_cert_deleted_count: Option<u64>,
+    pub missing_issuer_cert_count: Option<u64>,
+    pub current_cert_store_count: Option<u64>,
+    pub current_revoked_cert_count: Option<u64>,
+    pub revocation_queue_deleted_count: Option<u64>,
+    pub cross_revoked_cert_deleted_count: Option<u64>,
+    pub last_auto_tidy_finished: Option<String>,
+}
+
+/// Response from executing
+/// [ReadAutoTidyConfigRequest][crate::api::pki::requests::ReadAutoTidyConfigRequest]
+///
+/// Durations are in seconds.
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadAutoTidyConfigResponse {
+    pub enabled: bool,
+    pub interval_duration: u64,
+    pub tidy_cert_store: Option<bool>,
+    pub tidy_revoked_certs: Option<bool>,
+    pub tidy_revoked_cert_issuer_associations: Option<bool>,
+    pub tidy_expired_issuers: Option<bool>,
+    pub tidy_move_legacy_ca_bundle: Option<bool>,
+    pub tidy_revocation_queue: Option<bool>,
+    pub tidy_cross_cluster_revoked_certs: Option<bool>,
+    pub tidy_acme: Option<bool>,
+    pub safety_buffer: Option<u64>,
+    pub issuer_safety_buffer: Option<u64>,
+    pub revocation_queue_safety_buffer: Option<u64>,
+    pub acme_account_safety_buffer: Option<u64>,
+    pub pause_duration: Option<String>,
+}
+
 /// Response from executing
 /// [ReadRoleRequest][crate::api::pki::requests::ReadRoleRequest]
 #[derive(Deserialize, Debug, Serialize)]
@@ -155,3 +247,185 @@ pub struct SignSelfIssuedResponse {
     pub certificate: String,
     pub issuing_ca: String,
 }
+
+/// Response from executing
+/// [ListIssuersRequest][crate::api::pki::requests::ListIssuersRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ListIssuersResponse {
+    pub keys: Vec<String>,
+    pub key_info: Option<HashMap<String, IssuerInfo>>,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct IssuerInfo {
+    pub issuer_name: String,
+    pub is_default: Option<bool>,
+}
+
+/// Response from executing
+/// [ReadIssuerRequest][crate::api::pki::requests::ReadIssuerRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadIssuerResponse {
+    pub ca_chain: Vec<String>,
+    pub certificate: String,
+    pub crl_distribution_points: Option<Vec<String>>,
+    pub enable_aia_url_templating: Option<bool>,
+    pub issuer_id: String,
+    pub issuer_name: String,
+    pub issuing_certificates: Option<Vec<String>>,
+    pub key_id: String,
+    pub leaf_not_after_behavior: String,
+    pub manual_chain: Option<Vec<String>>,
+    pub ocsp_servers: Option<Vec<String>>,
+    pub revocation_signature_algorithm: Option<String>,
+    pub revocation_time: Option<i64>,
+    pub revoked: Option<bool>,
+    pub usage: String,
+}
+
+/// Response from executing
+/// [ImportIssuersRequest][crate::api::pki::requests::ImportIssuersRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ImportIssuersResponse {
+    pub existing_issuers: Option<Vec<String>>,
+    pub existing_keys: Option<Vec<String>>,
+    pub imported_issuers: Option<Vec<String>>,
+    pub imported_keys: Option<Vec<String>>,
+    pub mapping: Option<HashMap<String, String>>,
+}
+
+/// Response from executing
+/// [ReadIssuersConfigRequest][crate::api::pki::requests::ReadIssuersConfigRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadIssuersConfigResponse {
+    pub default: String,
+    pub default_follows_latest_issuer: Option<bool>,
+}
+
+/// Response from executing
+/// [ListKeysRequest][crate::api::pki::requests::ListKeysRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ListKeysResponse {
+    pub keys: Vec<String>,
+    pub key_info: Option<HashMap<String, KeyInfo>>,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct KeyInfo {
+    pub key_name: String,
+}
+
+/// Response from executing
+/// [ReadKeyRequest][crate::api::pki::requests::ReadKeyRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadKeyResponse {
+    pub key_id: String,
+    pub key_name: String,
+    pub key_type: String,
+    pub managed_key_id: Option<String>,
+    pub managed_key_name: Option<String>,
+    pub subject_key_id: Option<String>,
+}
+
+/// Response from executing
+/// [GenerateKeyRequest][crate::api::pki::requests::GenerateKeyRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct GenerateKeyResponse {
+    pub key_id: String,
+    pub key_name: String,
+    pub key_type: String,
+    pub private_key: Option<String>,
+}
+
+/// Response from executing
+/// [ReadAcmeConfigRequest][crate::api::pki::requests::ReadAcmeConfigRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadAcmeConfigResponse {
+    pub enabled: bool,
+    pub allowed_issuers: Vec<String>,
+    pub allowed_roles: Vec<String>,
+    pub allow_role_ext_key_usage: Option<bool>,
+    pub default_directory_policy: String,
+    pub dns_resolver: Option<String>,
+    pub eab_policy: String,
+    pub max_ttl: Option<u64>,
+}
+
+/// Response from executing
+/// [GenerateEabRequest][crate::api::pki::requests::GenerateEabRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct GenerateEabResponse {
+    pub id: String,
+    pub key_type: String,
+    /// The base64url encoded HMAC key
+    pub key: String,
+    pub acme_directory: String,
+    pub created_on: String,
+}
+
+/// Response from executing
+/// [ListEabRequest][crate::api::pki::requests::ListEabRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ListEabResponse {
+    pub keys: Vec<String>,
+    pub key_info: Option<HashMap<String, EabInfo>>,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct EabInfo {
+    pub key_type: String,
+    pub acme_directory: String,
+    pub created_on: String,
+}
+
+/// Response from executing
+/// [ListAcmeAccountsRequest][crate::api::pki::requests::ListAcmeAccountsRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ListAcmeAccountsResponse {
+    pub keys: Vec<String>,
+}
+
+/// Response from executing
+/// [ReadAcmeAccountRequest][crate::api::pki::requests::ReadAcmeAccountRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadAcmeAccountResponse {
+    pub key_id: String,
+    pub status: String,
+    pub directory: String,
+    pub created_date: String,
+    pub revoked_date: Option<String>,
+    pub contacts: Option<Vec<String>>,
+    pub eab: Option<AcmeAccountEab>,
+    pub orders: Option<Vec<AcmeOrder>>,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct AcmeAccountEab {
+    pub eab_id: String,
+    pub directory: String,
+    pub key_type: String,
+    pub created_time: String,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct AcmeOrder {
+    pub order_id: String,
+    pub status: String,
+    pub cert_serial_number: Option<String>,
+    pub cert_expiry: Option<String>,
+    pub order_expiry: Option<String>,
+}
+
+/// Response from executing
+/// [ReadEstConfigRequest][crate::api::pki::requests::ReadEstConfigRequest]
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReadEstConfigResponse {
+    pub enabled: bool,
+    pub default_mount: bool,
+    pub default_path_policy: String,
+    pub label_to_path_policy: Option<HashMap<String, String>>,
+    pub authenticators: Option<HashMap<String, HashMap<String, String>>>,
+    pub enable_sentinel_parsing: Option<bool>,
+    pub audit_fields: Option<Vec<String>>,
+    pub last_updated: Option<String>,
+}
@@ -1,9 +1,13 @@
 pub mod requests;
 pub mod responses;
 
-use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, ops::Deref, str::FromStr};
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::ClientError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum KeyType {
     /// AES-128 wrapped with GCM using a 96-bit nonce size AEAD (symmetric,
@@ -31,6 +35,31 @@ pub enum KeyType {
     Rsa3072,
     /// RSA with bit size of 4096 (asymmetric)
     Rsa4096,
+    /// HMAC (HMAC generation and verification only, the key size is set with
+    /// `key_size`)
+    Hmac,
+    /// A managed key configured in Vault, referenced by `managed_key_name` or
+    /// `managed_key_id` (enterprise only)
+    #[serde(rename = "managed_key")]
+    ManagedKey,
+}
+
+impl KeyType {
+    /// Returns whether keys of this type support key derivation
+    pub fn supports_derivation(&self) -> bool {
+        matches!(
+            self,
+            Self::Aes128Gcm96 | Self::Aes256Gcm96 | Self::Chacha20Poly1305 | Self::Ed25519
+        )
+    }
+
+    /// Returns whether keys of this type support convergent encryption
+    pub fn supports_convergent_encryption(&self) -> bool {
+        matches!(
+            self,
+            Self::Aes128Gcm96 | Self::Aes256Gcm96 | Self::Chacha20Poly1305
+        )
+    }
 }
 
 impl Default for KeyType {
@@ -84,3 +113,134 @@ pub enum MarshalingAlgorithm {
     /// standard Base64-encoding.
     Jws,
 }
+
+/// Parses a `vault:v<N>:<payload>` value, returning its key version
+fn parse_versioned(kind: &str, value: &str) -> Result<u64, ClientError> {
+    let invalid = |reason: &str| ClientError::ParseVaultValueError {
+        kind: kind.to_string(),
+        reason: reason.to_string(),
+    };
+    let mut parts = value.splitn(3, ':');
+    if parts.next() != Some("vault") {
+        return Err(invalid("expected a vault: prefix"));
+    }
+    let version = parts
+        .next()
+        .and_then(|v| v.strip_prefix('v'))
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .ok_or_else(|| invalid("expected a key version such as v1"))?;
+    match parts.next() {
+        Some(payload) if !payload.is_empty() && !payload.contains(char::is_whitespace) => {
+            Ok(version)
+        }
+        _ => Err(invalid("expected a non-empty payload")),
+    }
+}
+
+/// Implements parsing, formatting and serde support for a
+/// `vault:v<N>:<payload>` value type
+macro_rules! versioned_value {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $name {
+            value: String,
+            version: u64,
+        }
+
+        impl $name {
+            /// Parses and validates the value
+            pub fn parse(value: impl Into<String>) -> Result<Self, ClientError> {
+                let value = value.into();
+                let version = parse_versioned(stringify!($name), &value)?;
+                Ok($name { value, version })
+            }
+
+            /// Returns the version of the key which produced this value
+            pub fn version(&self) -> u64 {
+                self.version
+            }
+
+            /// Returns the part after the `vault:v<N>:` prefix
+            pub fn payload(&self) -> &str {
+                self.value.splitn(3, ':').nth(2).unwrap_or_default()
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.value
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ClientError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::parse(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = ClientError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::parse(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.value
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.value
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.value
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.value)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.value)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                $name::parse(value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+versioned_value!(
+    /// A ciphertext returned by the encrypt, rewrap and data key endpoints,
+    /// e.g. `vault:v1:...`
+    Ciphertext
+);
+
+versioned_value!(
+    /// A signature returned by the sign endpoint, e.g. `vault:v1:...`
+    Signature
+);
+
+versioned_value!(
+    /// An HMAC returned by the HMAC endpoint, e.g. `vault:v1:...`
+    Hmac
+);
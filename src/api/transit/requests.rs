@@ -1,8 +1,8 @@
 use super::responses::{
-    BackupKeyResponse, DecryptDataResponse, EncryptDataResponse, ExportKeyResponse,
+    BackupKeyResponse, BatchResponse, DecryptDataResponse, EncryptDataResponse, ExportKeyResponse,
     GenerateDataKeyResponse, GenerateHmacResponse, GenerateRandomBytesResponse, HashDataResponse,
-    ListKeysResponse, ReadKeyResponse, ReadTransitCacheConfigurationResponse, RewrapDataResponse,
-    SignDataResponse, VerifySignedDataResponse,
+    ListKeysResponse, ReadKeyResponse, ReadTransitCacheConfigurationResponse,
+    ReadWrappingKeyResponse, RewrapDataResponse, SignDataResponse, VerifySignedDataResponse,
 };
 use super::{HashAlgorithm, KeyType, MarshalingAlgorithm, OutputFormat, SignatureAlgorithm};
 use rustify_derive::Endpoint;
@@ -52,6 +52,12 @@ pub struct CreateKeyRequest {
     /// this to "0" (the default) will disable automatic key rotation. This
     /// value cannot be shorter than one hour.
     pub auto_rotate_period: Option<String>,
+    /// The key size in bytes for HMAC keys. Must be between 32 and 512.
+    pub key_size: Option<u64>,
+    /// The name of the managed key to use for keys of type `managed_key`.
+    pub managed_key_name: Option<String>,
+    /// The UUID of the managed key to use for keys of type `managed_key`.
+    pub managed_key_id: Option<String>,
 }
 
 /// ## Read Key
@@ -274,6 +280,107 @@ impl std::fmt::Display for ExportVersion {
     }
 }
 
+/// ## Get Wrapping Key
+/// This endpoint retrieves the wrapping key to use for importing keys. The
+/// returned key will be a 4096-bit RSA public key.
+///
+/// * Path: {self.mount}/wrapping_key
+/// * Method: GET
+/// * Response: ReadWrappingKeyResponse
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#get-wrapping-key
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/wrapping_key",
+    response = "ReadWrappingKeyResponse",
+    builder = "true"
+)]
+#[builder(setter(into), default)]
+pub struct ReadWrappingKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+}
+
+/// ## Import Key
+/// This endpoint imports existing key material into a new transit-managed
+/// encryption key. To import key material into an existing key, see the
+/// import_version endpoint.
+///
+/// * Path: {self.mount}/keys/{self.name}/import
+/// * Method: POST
+/// * Response: N/A
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#import-key
+#[derive(Builder, Debug, Default, Endpoint, Serialize)]
+#[endpoint(
+    path = "{self.mount}/keys/{self.name}/import",
+    method = "POST",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ImportKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    /// Specifies the name of the encryption key to create.
+    #[endpoint(skip)]
+    pub name: String,
+    /// A base64-encoded string that contains two values: an ephemeral 256-bit
+    /// AES key wrapped using the wrapping key returned by Vault and the
+    /// encryption of the import key material under the provided AES key.
+    pub ciphertext: String,
+    /// The hash function used for the RSA-OAEP step of creating the
+    /// ciphertext. Defaults to SHA256.
+    pub hash_function: Option<String>,
+    /// Specifies the type of key to create.
+    #[serde(rename = "type")]
+    pub key_type: Option<KeyType>,
+    /// If set, the imported key can be rotated within Vault by using the
+    /// rotate endpoint.
+    pub allow_rotation: Option<bool>,
+    /// Specifies if key derivation is to be used.
+    pub derived: Option<bool>,
+    /// A base64-encoded string providing a context for key derivation.
+    /// Required if derived is set to true.
+    pub context: Option<String>,
+    /// Enables keys to be exportable. Once set, this cannot be disabled.
+    pub exportable: Option<bool>,
+    /// If set, enables taking backup of named key in the plaintext format.
+    /// Once set, this cannot be disabled.
+    pub allow_plaintext_backup: Option<bool>,
+    /// The period at which this key should be rotated automatically. Requires
+    /// allow_rotation to be set to true.
+    pub auto_rotate_period: Option<String>,
+}
+
+/// ## Import Key Version
+/// This endpoint imports new key material into an existing imported key.
+///
+/// * Path: {self.mount}/keys/{self.name}/import_version
+/// * Method: POST
+/// * Response: N/A
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#import-key-version
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys/{self.name}/import_version",
+    method = "POST",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct ImportKeyVersionRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    /// Specifies the name of the key to import the new version into.
+    #[endpoint(skip)]
+    pub name: String,
+    /// A base64-encoded string that contains two values: an ephemeral 256-bit
+    /// AES key wrapped using the wrapping key returned by Vault and the
+    /// encryption of the import key material under the provided AES key.
+    pub ciphertext: String,
+    /// The hash function used for the RSA-OAEP step of creating the
+    /// ciphertext. Defaults to SHA256.
+    pub hash_function: Option<String>,
+    /// The version of the key to update. If not set, a new version is created.
+    pub version: Option<u64>,
+}
+
 /// ## Encrypt Data
 /// This endpoint encrypts the provided plaintext using the named key. This path
 /// supports the create and update policy capabilities as follows: if the user
@@ -407,6 +514,133 @@ pub struct RewrapDataRequest {
     pub nonce: Option<String>,
 }
 
+/// ## Encrypt Data (batch)
+/// This endpoint encrypts a list of plaintexts using the named key in a single
+/// request. Each item of the batch is processed independently and returns its
+/// own result or error.
+///
+/// * Path: {self.mount}/encrypt/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<EncryptDataResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/encrypt/{self.name}",
+    method = "POST",
+    response = "BatchResponse<EncryptDataResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct EncryptDataBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    /// Specifies the name of the encryption key to encrypt against.
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to encrypt.
+    pub batch_input: Vec<EncryptDataBatchInput>,
+    /// Specifies the version of the key to use for encryption. If not set, uses
+    /// the latest version. Must be greater than or equal to the key's
+    /// min_encryption_version, if set.
+    pub key_version: Option<u64>,
+    /// This parameter is required when encryption key is expected to be
+    /// created. When performing an upsert operation, the type of key to create.
+    pub key_type: Option<KeyType>,
+    /// This parameter will only be used when a key is expected to be created.
+    /// Whether to support convergent encryption.
+    pub convergent_encryption: Option<String>,
+}
+
+/// A single item of an [EncryptDataBatchRequest]
+#[derive(Builder, Clone, Debug, Default, Serialize)]
+#[builder(setter(into, strip_option), default)]
+pub struct EncryptDataBatchInput {
+    /// Specifies base64 encoded plaintext to be encoded.
+    pub plaintext: String,
+    /// Specifies the base64 encoded context for key derivation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Specifies the base64 encoded nonce value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// A string which is returned unchanged with the result of this item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// ## Decrypt Data (batch)
+/// This endpoint decrypts a list of ciphertexts using the named key in a
+/// single request.
+///
+/// * Path: {self.mount}/decrypt/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<DecryptDataResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input-1
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/decrypt/{self.name}",
+    method = "POST",
+    response = "BatchResponse<DecryptDataResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct DecryptDataBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    /// Specifies the name of the encryption key to decrypt against.
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to decrypt.
+    pub batch_input: Vec<DecryptDataBatchInput>,
+}
+
+/// A single item of a [DecryptDataBatchRequest] or a [RewrapDataBatchRequest]
+#[derive(Builder, Clone, Debug, Default, Serialize)]
+#[builder(setter(into, strip_option), default)]
+pub struct DecryptDataBatchInput {
+    /// Specifies the ciphertext to decrypt.
+    pub ciphertext: String,
+    /// Specifies the base64 encoded context for key derivation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Specifies a base64 encoded nonce value used during encryption.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// A string which is returned unchanged with the result of this item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// ## Rewrap Data (batch)
+/// This endpoint rewraps a list of ciphertexts using the latest version of the
+/// named key in a single request.
+///
+/// * Path: {self.mount}/rewrap/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<RewrapDataResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input-2
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/rewrap/{self.name}",
+    method = "POST",
+    response = "BatchResponse<RewrapDataResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct RewrapDataBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    /// Specifies the name of the encryption key to re-encrypt against.
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to re-encrypt.
+    pub batch_input: Vec<DecryptDataBatchInput>,
+    /// Specifies the version of the key to use for the operation. If not set,
+    /// uses the latest version. Must be greater than or equal to the key's
+    /// min_encryption_version, if set.
+    pub key_version: Option<u64>,
+}
+
 /// ## Generate Data Key
 /// This endpoint generates a new high-entropy key and the value encrypted with
 /// the named key. Optionally return the plaintext of the key as well. Whether
@@ -580,6 +814,37 @@ pub struct GenerateHmacRequest {
     pub input: String,
 }
 
+/// ## Generate HMAC (batch)
+/// This endpoint returns the digests of a list of inputs using the named key
+/// in a single request.
+///
+/// * Path: {self.mount}/hmac/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<GenerateHmacResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input-5
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/hmac/{self.name}",
+    method = "POST",
+    response = "BatchResponse<GenerateHmacResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct GenerateHmacBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to generate HMACs for.
+    pub batch_input: Vec<SignDataBatchInput>,
+    /// Specifies the version of the key to use for the operation. If not set,
+    /// uses the latest version. Must be greater than or equal to the key's
+    /// min_encryption_version, if set.
+    pub key_version: Option<u64>,
+    /// Specifies the hash algorithm to use.
+    pub algorithm: Option<HashAlgorithm>,
+}
+
 /// ## Sign Data
 /// This endpoint returns the cryptographic signature of the given data using
 /// the named key and the specified hash algorithm. The key must be of a type
@@ -626,6 +891,10 @@ pub struct SignDataRequest {
     /// Specifies the way in which the signature should be marshaled. This
     /// currently only applies to ECDSA keys.
     pub marshaling_algorithm: Option<MarshalingAlgorithm>,
+    /// The salt length used to sign when using the PSS signature algorithm.
+    /// Either "auto" (the default, the maximum length), "hash" (the length of
+    /// the hash) or an integer.
+    pub salt_length: Option<String>,
 }
 
 /// ## Verify Signed Data
@@ -674,6 +943,117 @@ pub struct VerifySignedDataRequest {
     pub marshaling_algorithm: Option<MarshalingAlgorithm>,
 }
 
+/// ## Sign Data (batch)
+/// This endpoint returns the cryptographic signatures of a list of inputs
+/// using the named key in a single request.
+///
+/// * Path: {self.mount}/sign/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<SignDataResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input-3
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/sign/{self.name}",
+    method = "POST",
+    response = "BatchResponse<SignDataResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct SignDataBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to sign.
+    pub batch_input: Vec<SignDataBatchInput>,
+    /// Specifies the version of the key to use for the operation. If not set,
+    /// uses the latest version. Must be greater than or equal to the key's
+    /// min_encryption_version, if set.
+    pub key_version: Option<u64>,
+    /// Specifies the hash algorithm to use.
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Set to true when the inputs are already hashed.
+    pub prehashed: Option<bool>,
+    /// When using a RSA key, specifies the RSA signature algorithm to use for
+    /// signing.
+    pub signature_algorithm: Option<SignatureAlgorithm>,
+    /// Specifies the way in which the signatures should be marshaled. This
+    /// currently only applies to ECDSA keys.
+    pub marshaling_algorithm: Option<MarshalingAlgorithm>,
+}
+
+/// A single item of a [SignDataBatchRequest] or a [GenerateHmacBatchRequest]
+#[derive(Builder, Clone, Debug, Default, Serialize)]
+#[builder(setter(into, strip_option), default)]
+pub struct SignDataBatchInput {
+    /// Specifies the base64 encoded input data.
+    pub input: String,
+    /// Base64 encoded context for key derivation. Ignored when generating
+    /// HMACs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// A string which is returned unchanged with the result of this item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// ## Verify Signed Data (batch)
+/// This endpoint returns whether the provided signatures or HMACs are valid
+/// for a list of inputs in a single request.
+///
+/// * Path: {self.mount}/verify/{self.name}
+/// * Method: POST
+/// * Response: BatchResponse<VerifySignedDataResponse>
+/// * Reference: https://www.vaultproject.io/api-docs/secret/transit#batch_input-4
+#[derive(Builder, Debug, Default, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/verify/{self.name}",
+    method = "POST",
+    response = "BatchResponse<VerifySignedDataResponse>",
+    builder = "true"
+)]
+#[builder(setter(into, strip_option), default)]
+pub struct VerifySignedDataBatchRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub name: String,
+    /// Specifies the items to verify.
+    pub batch_input: Vec<VerifySignedDataBatchInput>,
+    /// Specifies the hash algorithm to use.
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Set to true when the inputs are already hashed.
+    pub prehashed: Option<bool>,
+    /// When using a RSA key, specifies the RSA signature algorithm to use for
+    /// signature verification.
+    pub signature_algorithm: Option<SignatureAlgorithm>,
+    /// Specifies the way in which the signatures were originally marshaled.
+    /// This currently only applies to ECDSA keys.
+    pub marshaling_algorithm: Option<MarshalingAlgorithm>,
+}
+
+/// A single item of a [VerifySignedDataBatchRequest]
+#[derive(Builder, Clone, Debug, Default, Serialize)]
+#[builder(setter(into, strip_option), default)]
+pub struct VerifySignedDataBatchInput {
+    /// Specifies the base64 encoded input data.
+    pub input: String,
+    /// Specifies the signature output from the /transit/sign function. Either
+    /// this must be supplied or hmac must be supplied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Specifies the signature output from the /transit/hmac function. Either
+    /// this must be supplied or signature must be supplied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hmac: Option<String>,
+    /// Base64 encoded context for key derivation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// A string which is returned unchanged with the result of this item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
 /// ## Backup Key
 /// This endpoint returns a plaintext backup of a named key. The backup contains
 /// all the configuration data and keys of all the versions along with the HMAC
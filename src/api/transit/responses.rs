@@ -1,4 +1,4 @@
-use super::KeyType;
+use super::{Ciphertext, Hmac, KeyType, Signature};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -10,9 +10,11 @@ pub struct ReadKeyResponse {
     pub key_type: KeyType,
     pub deletion_allowed: bool,
     pub derived: bool,
+    pub convergent_encryption: Option<bool>,
     pub exportable: bool,
     pub allow_plaintext_backup: bool,
-    pub keys: HashMap<String, u64>,
+    pub keys: ReadKeyData,
+    pub latest_version: Option<u64>,
     pub min_decryption_version: u64,
     pub min_encryption_version: u64,
     pub name: String,
@@ -21,6 +23,31 @@ pub struct ReadKeyResponse {
     pub supports_derivation: bool,
     pub supports_signing: bool,
     pub imported: Option<bool>,
+    /// The automatic rotation period of the key in seconds, 0 if disabled
+    pub auto_rotate_period: Option<u64>,
+    /// The key size in bytes of HMAC keys
+    pub key_size: Option<u64>,
+}
+
+/// The versions of a key returned by
+/// [ReadKeyRequest][crate::api::transit::requests::ReadKeyRequest]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReadKeyData {
+    /// The creation time (Unix timestamp) of each version of a symmetric key
+    Symmetric(HashMap<String, u64>),
+    /// The public key of each version of an asymmetric key
+    Asymmetric(HashMap<String, ReadPublicKeyEntry>),
+}
+
+/// A single version of an asymmetric key in [ReadKeyData]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadPublicKeyEntry {
+    pub creation_time: String,
+    pub name: String,
+    /// The public key, PEM encoded for ECDSA and RSA keys and base64 encoded
+    /// for Ed25519 keys
+    pub public_key: String,
 }
 
 /// Response from executing
@@ -38,11 +65,18 @@ pub struct ExportKeyResponse {
     pub keys: HashMap<String, String>,
 }
 
+/// Response from executing
+/// [ReadWrappingKeyRequest][crate::api::transit::requests::ReadWrappingKeyRequest]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadWrappingKeyResponse {
+    pub public_key: String,
+}
+
 /// Response from executing
 /// [EncryptDataRequest][crate::api::transit::requests::EncryptDataRequest]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EncryptDataResponse {
-    pub ciphertext: String,
+    pub ciphertext: Ciphertext,
 }
 
 /// Response from executing
@@ -56,7 +90,7 @@ pub struct DecryptDataResponse {
 /// [RewrapDataRequest][crate::api::transit::requests::RewrapDataRequest]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RewrapDataResponse {
-    pub ciphertext: String,
+    pub ciphertext: Ciphertext,
 }
 
 /// Response from executing
@@ -64,7 +98,7 @@ pub struct RewrapDataResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateDataKeyResponse {
     pub plaintext: Option<String>,
-    pub ciphertext: String,
+    pub ciphertext: Ciphertext,
 }
 
 /// Response from executing
@@ -85,14 +119,14 @@ pub struct HashDataResponse {
 /// [GenerateHmacRequest][crate::api::transit::requests::GenerateHmacRequest]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateHmacResponse {
-    pub hmac: String,
+    pub hmac: Hmac,
 }
 
 /// Response from executing
 /// [SignDataRequest][crate::api::transit::requests::SignDataRequest]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SignDataResponse {
-    pub signature: String,
+    pub signature: Signature,
 }
 
 /// Response from executing
@@ -102,6 +136,28 @@ pub struct VerifySignedDataResponse {
     pub valid: bool,
 }
 
+/// Response from executing a batch request like
+/// [EncryptDataBatchRequest][crate::api::transit::requests::EncryptDataBatchRequest]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchResponse<T> {
+    pub batch_results: Vec<BatchItemResult<T>>,
+}
+
+/// The result of a single item of a [BatchResponse], in the same order as the
+/// items of the request
+///
+/// Vault omits the result fields of items which failed, in which case `data`
+/// is `None` and `error` contains the reason.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchItemResult<T> {
+    #[serde(flatten)]
+    pub data: Option<T>,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
 /// Response from executing
 /// [BackupKeyRequest][crate::api::transit::requests::BackupKeyRequest]
 #[derive(Debug, Serialize, Deserialize)]
@@ -5,6 +5,18 @@ use thiserror::Error;
 pub enum ClientError {
     #[error("The Vault server returned an error (status code {code})")]
     APIError { code: u16, errors: Vec<String> },
+    #[error("Item {index} of the batch failed: {message}")]
+    BatchItemError {
+        index: usize,
+        reference: Option<String>,
+        message: String,
+    },
+    #[error("Error processing certificate: {reason}")]
+    CertificateError { reason: String },
+    #[error("Error processing encrypted field: {reason}")]
+    EncryptedFieldError { reason: String },
+    #[error("Error processing envelope: {reason}")]
+    EnvelopeError { reason: String },
     #[error("Failed to find file: {path}")]
     FileNotFoundError { path: String },
     #[error("Error reading file: {path}")]
@@ -17,15 +29,29 @@ pub enum ClientError {
         source: std::io::Error,
         path: String,
     },
+    #[error("The key {key} is not a valid environment variable name")]
+    InvalidEnvironmentKeyError { key: String },
     #[error("Invalid login method")]
     InvalidLoginMethodError,
+    #[error("Invalid PKI role: {reason}")]
+    InvalidRoleError { reason: String },
     #[error("Error parsing value into JSON")]
     JsonParseError { source: serde_json::error::Error },
+    #[error("Error creating JWS: {reason}")]
+    JwsError { reason: String },
+    #[error("Error importing key: {reason}")]
+    KeyImportError { reason: String },
+    #[error("Invalid key policy: {reason}")]
+    KeyPolicyError { reason: String },
+    #[error("Error verifying signature offline: {reason}")]
+    OfflineVerificationError { reason: String },
     #[error("Error parsing CA certificate as PEM encoded certificate: {path}")]
     ParseCertificateError {
         source: reqwest::Error,
         path: String,
     },
+    #[error("Error parsing {kind}: {reason}")]
+    ParseVaultValueError { kind: String, reason: String },
     #[error("The request returned an empty response")]
     ResponseEmptyError,
     #[error("The result contained an empty data field")]
@@ -39,6 +65,29 @@ pub enum ClientError {
         #[from]
         source: rustify::errors::ClientError,
     },
+    #[error("Error rewrapping ciphertext: {reason}")]
+    RewrapError { reason: String },
+    #[error("Error rotating issuer: {reason}")]
+    RotationError { reason: String },
+    #[error("The key {key} was not found in the secret at {mount}/{path}")]
+    SecretKeyMissingError {
+        mount: String,
+        path: String,
+        key: String,
+    },
+    #[error("Error parsing the key {key} of the secret at {mount}/{path}")]
+    SecretKeyParseError {
+        source: serde_json::error::Error,
+        mount: String,
+        path: String,
+        key: String,
+    },
+    #[error("Error reading or writing stream")]
+    StreamError { source: std::io::Error },
+    #[error("Error processing encrypted stream: {reason}")]
+    StreamFormatError { reason: String },
+    #[error("Error parsing template on line {line}: {reason}")]
+    TemplateError { line: usize, reason: String },
     #[error("The wrapped response doesn't exist or is not longer valid")]
     WrapInvalidError,
 }
@@ -1,3 +1,11 @@
+pub mod maintenance;
+pub mod migrate;
+#[cfg(feature = "streams")]
+mod watcher;
+
+#[cfg(feature = "streams")]
+pub use watcher::{watch, watch_with_options, SecretEvent, WatchOptions};
+
 use crate::{
     api::{
         self,
@@ -16,6 +24,7 @@ use crate::{
     error::ClientError,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use std::future::Future;
 
 /// Soft-delete the latest version of a secret
 ///
@@ -247,6 +256,39 @@ pub async fn undelete_versions(
     api::exec_with_empty(client, endpoint).await
 }
 
+/// Lists the paths of all secrets below the given path, calling `list` with
+/// each folder to list its keys
+///
+/// Folders which don't exist are skipped. Shared by the KV v1 and KV v2
+/// sweeps in [migrate] and [maintenance].
+pub(crate) async fn list_recursive<F, Fut>(path: &str, list: F) -> Result<Vec<String>, ClientError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<String>, ClientError>>,
+{
+    let mut secrets = Vec::new();
+    let mut folders = vec![path.trim_matches('/').to_string()];
+    while let Some(folder) = folders.pop() {
+        let keys = match list(folder.clone()).await {
+            Ok(keys) => keys,
+            Err(ClientError::APIError { code: 404, .. }) => continue,
+            Err(e) => return Err(e),
+        };
+        for key in keys {
+            let full = match folder.is_empty() {
+                true => key,
+                false => format!("{}/{}", folder, key),
+            };
+            match full.strip_suffix('/') {
+                Some(sub) => folders.push(sub.to_string()),
+                None => secrets.push(full),
+            }
+        }
+    }
+    secrets.sort();
+    Ok(secrets)
+}
+
 pub mod config {
     use crate::{
         api::{
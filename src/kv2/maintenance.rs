@@ -0,0 +1,201 @@
+//! Applies retention policies to every secret below a path of a KV v2 engine.
+//!
+//! The metadata of each secret is read with [read_metadata] and the versions
+//! selected by a [RetentionPolicy] are deleted or destroyed:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use vaultrs::kv2::maintenance::{self, RetentionPolicy};
+//!
+//! // Report which versions would be destroyed because they were created more
+//! // than 30 days ago
+//! let policy = RetentionPolicy {
+//!     max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+//!     dry_run: true,
+//!     ..Default::default()
+//! };
+//! let report = maintenance::apply(&client, "secret", "app", &policy).await?;
+//!
+//! // Destroy every version but the 3 most recent ones of each secret
+//! let policy = RetentionPolicy {
+//!     keep_last: Some(3),
+//!     ..Default::default()
+//! };
+//! let report = maintenance::apply(&client, "secret", "app", &policy).await?;
+//! ```
+use std::time::Duration;
+
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    api::kv2::responses::ReadSecretMetadataResponse,
+    client::Client,
+    error::ClientError,
+    kv2::{delete_versions, destroy_versions, list, list_recursive, read_metadata},
+};
+
+/// What to do with the versions selected by a [RetentionPolicy]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RetentionAction {
+    /// Soft-delete the versions so they can still be undeleted
+    Delete,
+    /// Permanently destroy the versions
+    #[default]
+    Destroy,
+}
+
+/// Selects the versions of a secret which should be removed
+///
+/// A version is selected when it's not among the `keep_last` most recent
+/// versions or when it was created more than `max_age` ago. The `keep_last`
+/// most recent versions and the current version of a secret are never
+/// selected, whatever their age. As either rule selects a version, setting
+/// both selects the same versions as setting `keep_last` alone.
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct RetentionPolicy {
+    /// The number of most recent versions to always keep
+    pub keep_last: Option<u64>,
+    /// The age after which versions are removed
+    pub max_age: Option<Duration>,
+    /// Whether to delete or destroy the selected versions
+    pub action: RetentionAction,
+    /// Only report the selected versions without removing them
+    pub dry_run: bool,
+}
+
+impl RetentionPolicy {
+    /// Returns the versions of the secret with the given metadata which this
+    /// policy selects for removal, relative to `now`
+    pub fn select(&self, meta: &ReadSecretMetadataResponse, now: OffsetDateTime) -> Vec<u64> {
+        let mut selected: Vec<u64> = meta
+            .versions
+            .iter()
+            .filter_map(|(version, info)| Some((version.parse::<u64>().ok()?, info)))
+            .filter(|(version, _)| *version != meta.current_version)
+            .filter(|(_, info)| match self.action {
+                RetentionAction::Delete => info.deletion_time.is_empty() && !info.destroyed,
+                RetentionAction::Destroy => !info.destroyed,
+            })
+            .filter(|(version, info)| {
+                let protected = self
+                    .keep_last
+                    .map(|n| version + n > meta.current_version)
+                    .unwrap_or(false);
+                let too_many = self.keep_last.is_some() && !protected;
+                let too_old = match (self.max_age, parse_time(&info.created_time)) {
+                    (Some(age), Some(created)) => now - created > age,
+                    _ => false,
+                };
+                !protected && (too_many || too_old)
+            })
+            .map(|(version, _)| version)
+            .collect();
+        selected.sort_unstable();
+        selected
+    }
+}
+
+/// The versions of a single secret selected by a [RetentionPolicy]
+#[derive(Debug)]
+pub struct SecretRetention {
+    pub path: String,
+    /// The versions which were (or would have been) removed
+    pub versions: Vec<u64>,
+    /// The error which occurred while reading or updating the secret, if any
+    pub error: Option<ClientError>,
+}
+
+/// The report returned by [apply]
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    /// Every secret which had versions selected or which failed
+    pub secrets: Vec<SecretRetention>,
+}
+
+impl RetentionReport {
+    /// Returns the total number of versions which were (or would have been)
+    /// removed
+    pub fn total_versions(&self) -> usize {
+        self.secrets
+            .iter()
+            .filter(|s| s.error.is_none())
+            .map(|s| s.versions.len())
+            .sum()
+    }
+
+    /// Returns the secrets for which an error occurred
+    pub fn failures(&self) -> impl Iterator<Item = &SecretRetention> {
+        self.secrets.iter().filter(|s| s.error.is_some())
+    }
+}
+
+/// Applies the policy to every secret below `path` in the KV v2 engine
+/// mounted at `mount`
+///
+/// Failures for individual secrets are recorded in the returned
+/// [RetentionReport] and don't stop the sweep. Only failing to list the
+/// secrets is returned as an `Err`.
+#[instrument(skip(client), err)]
+pub async fn apply(
+    client: &impl Client,
+    mount: &str,
+    path: &str,
+    policy: &RetentionPolicy,
+) -> Result<RetentionReport, ClientError> {
+    let now = OffsetDateTime::now_utc();
+    let mut report = RetentionReport {
+        dry_run: policy.dry_run,
+        secrets: Vec::new(),
+    };
+    let paths = list_recursive(
+        path,
+        |folder| async move { list(client, mount, &folder).await },
+    )
+    .await?;
+    for path in paths {
+        let (versions, error) = match apply_secret(client, mount, &path, policy, now).await {
+            Ok(versions) => (versions, None),
+            Err((versions, error)) => (versions, Some(error)),
+        };
+        if !versions.is_empty() || error.is_some() {
+            report.secrets.push(SecretRetention {
+                path,
+                versions,
+                error,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Applies the policy to a single secret, returning the selected versions
+async fn apply_secret(
+    client: &impl Client,
+    mount: &str,
+    path: &str,
+    policy: &RetentionPolicy,
+    now: OffsetDateTime,
+) -> Result<Vec<u64>, (Vec<u64>, ClientError)> {
+    let meta = read_metadata(client, mount, path)
+        .await
+        .map_err(|e| (vec![], e))?;
+    let versions = policy.select(&meta, now);
+    if policy.dry_run || versions.is_empty() {
+        return Ok(versions);
+    }
+
+    let res = match policy.action {
+        RetentionAction::Delete => delete_versions(client, mount, path, versions.clone()).await,
+        RetentionAction::Destroy => destroy_versions(client, mount, path, versions.clone()).await,
+    };
+    match res {
+        Ok(_) => Ok(versions),
+        Err(e) => Err((versions, e)),
+    }
+}
+
+fn parse_time(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339).ok()
+}
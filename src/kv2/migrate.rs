@@ -0,0 +1,204 @@
+//! Copies secrets from a KV v1 engine into a KV v2 engine.
+//!
+//! Every secret found below the given path of the source engine, or the
+//! secret at that path if it isn't a folder, is written to the same path of
+//! the destination engine:
+//!
+//! ```ignore
+//! use vaultrs::kv2::migrate::{self, MigrateOptions};
+//!
+//! // Report what would be copied without writing anything
+//! let report = migrate::migrate(&client, "kv", "secret", "app", MigrateOptions {
+//!     dry_run: true,
+//!     ..Default::default()
+//! }).await?;
+//! ```
+use serde_json::{Map, Value};
+
+use crate::{
+    api::kv2::requests::{SetConfigurationRequest, SetConfigurationRequestBuilder},
+    client::Client,
+    error::ClientError,
+    kv1,
+    kv2::{self, list_recursive},
+};
+
+/// Options for [migrate]
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct MigrateOptions {
+    /// Only list the secrets which would be copied without writing anything
+    pub dry_run: bool,
+    /// Re-read every copied secret from the destination and compare it to
+    /// the source
+    pub verify: bool,
+    /// The maximum number of versions to keep per secret in the destination
+    pub max_versions: Option<u64>,
+    /// Whether the destination should require the `cas` option on writes
+    pub cas_required: Option<bool>,
+    /// The duration after which versions in the destination are deleted
+    pub delete_version_after: Option<String>,
+}
+
+impl MigrateOptions {
+    /// Returns the configuration to apply to the destination, if any
+    fn config(&self) -> Option<SetConfigurationRequestBuilder> {
+        if self.max_versions.is_none()
+            && self.cas_required.is_none()
+            && self.delete_version_after.is_none()
+        {
+            return None;
+        }
+        let mut config = SetConfigurationRequest::builder();
+        if let Some(max_versions) = self.max_versions {
+            config.max_versions(max_versions);
+        }
+        if let Some(cas_required) = self.cas_required {
+            config.cas_required(cas_required);
+        }
+        if let Some(delete_version_after) = &self.delete_version_after {
+            config.delete_version_after(delete_version_after);
+        }
+        Some(config)
+    }
+}
+
+/// The result of migrating a single secret
+#[derive(Debug)]
+pub enum MigrationStatus {
+    /// The secret would have been copied if this wasn't a dry run
+    Planned,
+    /// The secret was written as the given version in the destination
+    Copied { version: u64 },
+    /// The secret was written as the given version in the destination and
+    /// reading it back returned the same data as the source
+    Verified { version: u64 },
+    /// The secret was written but reading it back returned different data
+    Mismatched { version: u64 },
+    /// Reading, writing or verifying the secret failed
+    Failed { error: ClientError },
+}
+
+/// A secret found in the source engine along with the result of migrating it
+#[derive(Debug)]
+pub struct MigratedSecret {
+    pub path: String,
+    pub status: MigrationStatus,
+}
+
+/// The report returned by [migrate]
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    /// The configuration which was (or would have been) written to the
+    /// destination engine, if any
+    pub config: Option<SetConfigurationRequest>,
+    pub secrets: Vec<MigratedSecret>,
+}
+
+impl MigrationReport {
+    /// Returns the secrets which could not be migrated or failed verification
+    pub fn failures(&self) -> impl Iterator<Item = &MigratedSecret> {
+        self.secrets.iter().filter(|s| {
+            matches!(
+                s.status,
+                MigrationStatus::Failed { .. } | MigrationStatus::Mismatched { .. }
+            )
+        })
+    }
+
+    /// Returns whether every secret was migrated (or planned) successfully
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Copies every secret below `path` in the KV v1 engine mounted at `from`
+/// into the KV v2 engine mounted at `to`, preserving their paths
+///
+/// Failures for individual secrets are recorded in the returned
+/// [MigrationReport] and don't stop the migration. Only errors which prevent
+/// the migration from starting, like failing to list the source or to
+/// configure the destination, are returned as an `Err`.
+#[instrument(skip(client, opts), err)]
+pub async fn migrate(
+    client: &impl Client,
+    from: &str,
+    to: &str,
+    path: &str,
+    opts: MigrateOptions,
+) -> Result<MigrationReport, ClientError> {
+    let paths = list_paths(client, from, path).await?;
+    let mut config = opts.config();
+    let mut report = MigrationReport {
+        dry_run: opts.dry_run,
+        config: config.as_mut().map(|c| c.mount(to).build().unwrap()),
+        secrets: Vec::with_capacity(paths.len()),
+    };
+    if opts.dry_run {
+        report.secrets = paths
+            .into_iter()
+            .map(|path| MigratedSecret {
+                path,
+                status: MigrationStatus::Planned,
+            })
+            .collect();
+        return Ok(report);
+    }
+
+    if let Some(config) = config.as_mut() {
+        kv2::config::set(client, to, Some(config)).await?;
+    }
+
+    for path in paths {
+        let status = match copy(client, from, to, &path, opts.verify).await {
+            Ok(status) => status,
+            Err(error) => MigrationStatus::Failed { error },
+        };
+        report.secrets.push(MigratedSecret { path, status });
+    }
+    Ok(report)
+}
+
+/// Lists the secrets below the given path of the source, falling back to the
+/// secret at that path when it isn't a folder
+async fn list_paths(
+    client: &impl Client,
+    mount: &str,
+    path: &str,
+) -> Result<Vec<String>, ClientError> {
+    let paths = list_recursive(path, |folder| async move {
+        Ok(kv1::list(client, mount, &folder).await?.data.keys)
+    })
+    .await?;
+    let path = path.trim_matches('/');
+    if !paths.is_empty() || path.is_empty() {
+        return Ok(paths);
+    }
+    match kv1::get::<Map<String, Value>>(client, mount, path).await {
+        Ok(_) => Ok(vec![path.to_string()]),
+        Err(ClientError::APIError { code: 404, .. }) => Ok(vec![]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Copies a single secret, optionally verifying it afterwards
+async fn copy(
+    client: &impl Client,
+    from: &str,
+    to: &str,
+    path: &str,
+    verify: bool,
+) -> Result<MigrationStatus, ClientError> {
+    let data: Map<String, Value> = kv1::get(client, from, path).await?;
+    let version = kv2::set(client, to, path, &data).await?.version;
+    if !verify {
+        return Ok(MigrationStatus::Copied { version });
+    }
+
+    let copied: Map<String, Value> = kv2::read_version(client, to, path, version).await?;
+    match copied == data {
+        true => Ok(MigrationStatus::Verified { version }),
+        false => Ok(MigrationStatus::Mismatched { version }),
+    }
+}
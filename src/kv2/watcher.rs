@@ -0,0 +1,163 @@
+//! Polling of KV v2 secrets for changes, see [watch].
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use futures::stream::{self, Stream};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    kv2::{read_metadata, read_version},
+};
+
+/// A change to a watched secret as reported by [watch]
+#[derive(Debug)]
+pub enum SecretEvent<D> {
+    /// A new version of the secret became current. This is also emitted for
+    /// every existing secret on the first poll.
+    Changed { path: String, version: u64, data: D },
+    /// The secret was deleted or its current version was deleted or destroyed
+    Removed { path: String },
+}
+
+/// Options for watching secrets with [watch_with_options]
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into))]
+pub struct WatchOptions {
+    /// The paths of the secrets to watch
+    pub paths: Vec<String>,
+    /// The time to wait between polls
+    pub interval: Duration,
+    /// The fraction of `interval` by which each wait is randomly shortened or
+    /// lengthened, e.g. `0.1` for +/- 10%, between `0.0` and `1.0`
+    #[builder(default = "0.0")]
+    pub jitter: f64,
+}
+
+/// Watches the secret at the given path for changes
+///
+/// The returned stream polls the metadata of the secret every `interval` and
+/// only reads the secret when its `current_version` changes. Errors are
+/// yielded as they occur and polling continues afterwards.
+///
+/// See [watch_with_options]
+pub fn watch<'a, D>(
+    client: &'a impl Client,
+    mount: &'a str,
+    path: &str,
+    interval: Duration,
+) -> impl Stream<Item = Result<SecretEvent<D>, ClientError>> + Send + 'a
+where
+    D: DeserializeOwned + Send + 'a,
+{
+    let options = WatchOptions {
+        paths: vec![path.to_string()],
+        interval,
+        jitter: 0.0,
+    };
+    watch_with_options(client, mount, options)
+}
+
+/// Watches the secrets at the given paths for changes
+/// including an argument for [WatchOptions]
+///
+/// See [watch]
+pub fn watch_with_options<'a, D>(
+    client: &'a impl Client,
+    mount: &'a str,
+    options: WatchOptions,
+) -> impl Stream<Item = Result<SecretEvent<D>, ClientError>> + Send + 'a
+where
+    D: DeserializeOwned + Send + 'a,
+{
+    let state = WatchState {
+        options,
+        seen: HashMap::new(),
+        pending: VecDeque::new(),
+        polled: false,
+    };
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.polled {
+                tokio::time::sleep(jittered(state.options.interval, state.options.jitter)).await;
+            }
+            state.polled = true;
+
+            for path in state.options.paths.iter() {
+                let last = state.seen.get(path).copied();
+                match poll_secret(client, mount, path, last).await {
+                    Ok((current, event)) => {
+                        state.seen.insert(path.clone(), current);
+                        if let Some(event) = event {
+                            state.pending.push_back(Ok(event));
+                        }
+                    }
+                    Err(e) => state.pending.push_back(Err(e)),
+                }
+            }
+        }
+    })
+}
+
+struct WatchState<D> {
+    options: WatchOptions,
+    /// The last observed live version of each path, `None` if it had none
+    seen: HashMap<String, Option<u64>>,
+    pending: VecDeque<Result<SecretEvent<D>, ClientError>>,
+    polled: bool,
+}
+
+/// Determines the live version of a secret and reads it if it differs from
+/// the last observed version
+async fn poll_secret<D: DeserializeOwned>(
+    client: &impl Client,
+    mount: &str,
+    path: &str,
+    last: Option<Option<u64>>,
+) -> Result<(Option<u64>, Option<SecretEvent<D>>), ClientError> {
+    let current = match read_metadata(client, mount, path).await {
+        Ok(meta) => meta
+            .versions
+            .get(&meta.current_version.to_string())
+            .filter(|v| v.deletion_time.is_empty() && !v.destroyed)
+            .map(|_| meta.current_version),
+        Err(ClientError::APIError { code: 404, .. }) => None,
+        Err(e) => return Err(e),
+    };
+    if last == Some(current) {
+        return Ok((current, None));
+    }
+
+    let event = match current {
+        Some(version) => Some(SecretEvent::Changed {
+            path: path.to_string(),
+            version,
+            data: read_version(client, mount, path, version).await?,
+        }),
+        None if matches!(last, Some(Some(_))) => Some(SecretEvent::Removed {
+            path: path.to_string(),
+        }),
+        None => None,
+    };
+    Ok((current, event))
+}
+
+/// Randomly shortens or lengthens the interval by up to the given fraction
+///
+/// The fraction is clamped to `0.0..=1.0` and the interval is returned as is
+/// when the jittered one can't be represented.
+fn jittered(interval: Duration, jitter: f64) -> Duration {
+    let jitter = if jitter.is_finite() {
+        jitter.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let factor = 1.0 + jitter * (rand::random::<f64>() * 2.0 - 1.0);
+    Duration::try_from_secs_f64(interval.as_secs_f64() * factor).unwrap_or(interval)
+}
@@ -33,6 +33,18 @@
 //! vaultrs = "0.7.0"
 //! ```
 //!
+//! The following features are opt-in:
+//!
+//! * `cert-manager`: `pki::manager` for TLS certificates served through rustls
+//! * `derive`: the `VaultSecret` derive macro
+//! * `fs`: writing rendered templates and exported secrets to files
+//! * `streams`: `kv2::watch` and `transit::rewrap`
+//! * `transit-crypto`: local cryptography with transit keys, such as envelope
+//!   encryption, key import, JWTs, offline signature verification and, along
+//!   with `fs`, file encryption
+//! * `x509`: parsing and validating PKI certificates, CRLs and local keys,
+//!   enables `fs`
+//!
 //! ## Usage
 //!
 //! ### Basic
@@ -98,6 +110,10 @@
 //! # })
 //! ```
 //!
+//! Fields from several secrets can also be loaded into a single struct using
+//! the `VaultSecret` derive macro provided by the `derive` feature. See the
+//! [secret] module for details.
+//!
 //! ### PKI
 //!
 //! The library currently supports all operations available for the PKI secrets
@@ -128,6 +144,10 @@
 //! # })
 //! ```
 //!
+//! With the `x509` feature, responses containing certificates can be decoded
+//! and their chain validated with `parsed()`. See the `pki::x509` module for
+//! details.
+//!
 //! ### Wrapping
 //!
 //! All requests implement the ability to be
@@ -204,7 +224,9 @@ pub mod error;
 pub mod kv1;
 pub mod kv2;
 pub mod pki;
+pub mod secret;
 pub mod ssh;
 pub mod sys;
+pub mod template;
 pub mod token;
 pub mod transit;
@@ -1,14 +1,32 @@
+#[cfg(feature = "x509")]
+pub mod csr;
+#[cfg(feature = "x509")]
+pub mod inventory;
+#[cfg(feature = "cert-manager")]
+pub mod manager;
+#[cfg(any(feature = "cert-manager", feature = "x509"))]
+mod parse;
+#[cfg(feature = "x509")]
+pub mod revocation;
+pub mod rotation;
+#[cfg(feature = "x509")]
+pub mod x509;
+
 pub mod cert {
     use crate::api;
     use crate::api::pki::requests::{
-        GenerateCertificateRequest, GenerateCertificateRequestBuilder, ListCertificatesRequest,
-        ReadCertificateRequest, RevokeCertificateRequest, TidyRequest,
+        CancelTidyRequest, GenerateCertificateRequest, GenerateCertificateRequestBuilder,
+        ListCertificatesRequest, OCSPRequest, ReadCertificateMetadataRequest,
+        ReadCertificateRequest, RevokeCertificateRequest, TidyRequest, TidyRequestBuilder,
+        TidyStatusRequest,
     };
     use crate::api::pki::responses::{
-        GenerateCertificateResponse, ReadCertificateResponse, RevokeCertificateResponse,
+        GenerateCertificateResponse, ReadCertificateMetadataResponse, ReadCertificateResponse,
+        RevokeCertificateResponse, TidyStatusResponse,
     };
     use crate::client::Client;
     use crate::error::ClientError;
+    use base64::{engine::general_purpose, Engine as _};
 
     /// Generates a certificate using the given role and options
     ///
@@ -59,6 +77,43 @@ pub mod cert {
         api::exec_with_result(client, endpoint).await
     }
 
+    /// Reads the metadata stored along with a certificate using its serial
+    ///
+    /// See [ReadCertificateMetadataRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_metadata(
+        client: &impl Client,
+        mount: &str,
+        serial: &str,
+    ) -> Result<ReadCertificateMetadataResponse, ClientError> {
+        let endpoint = ReadCertificateMetadataRequest::builder()
+            .mount(mount)
+            .serial(serial)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Queries the OCSP responder with a DER encoded OCSP request
+    ///
+    /// Returns the DER encoded OCSP response.
+    ///
+    /// See [OCSPRequest]
+    #[instrument(skip(client, request), err)]
+    pub async fn ocsp(
+        client: &impl Client,
+        mount: &str,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ClientError> {
+        let encoded = general_purpose::STANDARD.encode(request);
+        let endpoint = OCSPRequest::builder()
+            .mount(mount)
+            .request(url::form_urlencoded::byte_serialize(encoded.as_bytes()).collect::<String>())
+            .build()
+            .unwrap();
+        api::exec_with_raw(client, endpoint).await
+    }
+
     /// Revokes a certificate using its serial
     ///
     /// See [RevokeCertificateRequest]
@@ -78,13 +133,86 @@ pub mod cert {
 
     /// Tidy's up the certificate backend
     ///
+    /// The tidy runs in the background, see [tidy_status] to follow it.
+    ///
     /// See [TidyRequest]
-    #[instrument(skip(client), err)]
-    pub async fn tidy(client: &impl Client, mount: &str) -> Result<(), ClientError> {
-        let endpoint = TidyRequest::builder().mount(mount).build().unwrap();
+    #[instrument(skip(client, opts), err)]
+    pub async fn tidy(
+        client: &impl Client,
+        mount: &str,
+        opts: Option<&mut TidyRequestBuilder>,
+    ) -> Result<(), ClientError> {
+        let mut t = TidyRequest::builder();
+        let endpoint = opts.unwrap_or(&mut t).mount(mount).build().unwrap();
         api::exec_with_empty_result(client, endpoint).await
     }
 
+    /// Reads the status of the running or last run tidy
+    ///
+    /// See [TidyStatusRequest]
+    #[instrument(skip(client), err)]
+    pub async fn tidy_status(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<TidyStatusResponse, ClientError> {
+        let endpoint = TidyStatusRequest::builder().mount(mount).build().unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Cancels the running tidy
+    ///
+    /// See [CancelTidyRequest]
+    #[instrument(skip(client), err)]
+    pub async fn tidy_cancel(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<TidyStatusResponse, ClientError> {
+        let endpoint = CancelTidyRequest::builder().mount(mount).build().unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    pub mod auto_tidy {
+        use crate::api;
+        use crate::api::pki::{
+            requests::{
+                ReadAutoTidyConfigRequest, SetAutoTidyConfigRequest,
+                SetAutoTidyConfigRequestBuilder,
+            },
+            responses::ReadAutoTidyConfigResponse,
+        };
+        use crate::client::Client;
+        use crate::error::ClientError;
+
+        /// Reads the automatic tidy configuration
+        ///
+        /// See [ReadAutoTidyConfigRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read(
+            client: &impl Client,
+            mount: &str,
+        ) -> Result<ReadAutoTidyConfigResponse, ClientError> {
+            let endpoint = ReadAutoTidyConfigRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
+        /// Sets the automatic tidy configuration
+        ///
+        /// See [SetAutoTidyConfigRequest]
+        #[instrument(skip(client, opts), err)]
+        pub async fn set(
+            client: &impl Client,
+            mount: &str,
+            opts: Option<&mut SetAutoTidyConfigRequestBuilder>,
+        ) -> Result<ReadAutoTidyConfigResponse, ClientError> {
+            let mut t = SetAutoTidyConfigRequest::builder();
+            let endpoint = opts.unwrap_or(&mut t).mount(mount).build().unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+    }
+
     pub mod ca {
         use crate::api;
         use crate::api::pki::responses::SignSelfIssuedResponse;
@@ -93,7 +221,8 @@ pub mod cert {
                 requests::{
                     DeleteRootRequest, GenerateRootRequest, GenerateRootRequestBuilder,
                     SignCertificateRequest, SignCertificateRequestBuilder, SignIntermediateRequest,
-                    SignIntermediateRequestBuilder, SignSelfIssuedRequest, SubmitCARequest,
+                    SignIntermediateRequestBuilder, SignSelfIssuedRequest, SignVerbatimRequest,
+                    SignVerbatimRequestBuilder, SubmitCARequest,
                 },
                 responses::{
                     GenerateRootResponse, SignCertificateResponse, SignIntermediateResponse,
@@ -195,6 +324,26 @@ pub mod cert {
             api::exec_with_result(client, endpoint).await
         }
 
+        /// Signs a CSR using the root CA without the restrictions of a role
+        ///
+        /// See [SignVerbatimRequest]
+        #[instrument(skip(client, opts), err)]
+        pub async fn sign_verbatim(
+            client: &impl Client,
+            mount: &str,
+            csr: &str,
+            opts: Option<&mut SignVerbatimRequestBuilder>,
+        ) -> Result<SignCertificateResponse, ClientError> {
+            let mut t = SignVerbatimRequest::builder();
+            let endpoint = opts
+                .unwrap_or(&mut t)
+                .mount(mount)
+                .csr(csr)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
         /// Configures the root CA
         ///
         /// See [SubmitCARequest]
@@ -270,7 +419,8 @@ pub mod cert {
     pub mod crl {
         use crate::api::pki::{
             requests::{
-                ReadCRLConfigRequest, RotateCRLsRequest, SetCRLConfigRequest,
+                ReadCRLConfigRequest, ReadCRLPemRequest, ReadCRLRequest, ReadDeltaCRLPemRequest,
+                ReadDeltaCRLRequest, RotateCRLsRequest, SetCRLConfigRequest,
                 SetCRLConfigRequestBuilder,
             },
             responses::{ReadCRLConfigResponse, RotateCRLsResponse},
@@ -279,6 +429,52 @@ pub mod cert {
         use crate::client::Client;
         use crate::error::ClientError;
 
+        /// Reads the DER encoded CRL
+        ///
+        /// See [ReadCRLRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read(client: &impl Client, mount: &str) -> Result<Vec<u8>, ClientError> {
+            let endpoint = ReadCRLRequest::builder().mount(mount).build().unwrap();
+            api::exec_with_raw(client, endpoint).await
+        }
+
+        /// Reads the PEM encoded CRL
+        ///
+        /// See [ReadCRLPemRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read_pem(client: &impl Client, mount: &str) -> Result<String, ClientError> {
+            let endpoint = ReadCRLPemRequest::builder().mount(mount).build().unwrap();
+            api::exec_with_raw(client, endpoint)
+                .await
+                .map(|pem| String::from_utf8_lossy(&pem).into_owned())
+        }
+
+        /// Reads the DER encoded delta CRL
+        ///
+        /// See [ReadDeltaCRLRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read_delta(client: &impl Client, mount: &str) -> Result<Vec<u8>, ClientError> {
+            let endpoint = ReadDeltaCRLRequest::builder().mount(mount).build().unwrap();
+            api::exec_with_raw(client, endpoint).await
+        }
+
+        /// Reads the PEM encoded delta CRL
+        ///
+        /// See [ReadDeltaCRLPemRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read_delta_pem(
+            client: &impl Client,
+            mount: &str,
+        ) -> Result<String, ClientError> {
+            let endpoint = ReadDeltaCRLPemRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            api::exec_with_raw(client, endpoint)
+                .await
+                .map(|pem| String::from_utf8_lossy(&pem).into_owned())
+        }
+
         /// Rotates the CRL
         ///
         /// See [RotateCRLsRequest]
@@ -356,16 +552,58 @@ pub mod cert {
             api::exec_with_empty(client, endpoint).await
         }
     }
+
+    pub mod cluster {
+        use crate::api;
+        use crate::api::pki::{
+            requests::{
+                ReadClusterConfigRequest, SetClusterConfigRequest, SetClusterConfigRequestBuilder,
+            },
+            responses::ReadClusterConfigResponse,
+        };
+        use crate::client::Client;
+        use crate::error::ClientError;
+
+        /// Reads the cluster configuration
+        ///
+        /// See [ReadClusterConfigRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read(
+            client: &impl Client,
+            mount: &str,
+        ) -> Result<ReadClusterConfigResponse, ClientError> {
+            let endpoint = ReadClusterConfigRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
+        /// Sets the cluster configuration
+        ///
+        /// See [SetClusterConfigRequest]
+        #[instrument(skip(client, opts), err)]
+        pub async fn set(
+            client: &impl Client,
+            mount: &str,
+            opts: Option<&mut SetClusterConfigRequestBuilder>,
+        ) -> Result<(), ClientError> {
+            let mut t = SetClusterConfigRequest::builder();
+            let endpoint = opts.unwrap_or(&mut t).mount(mount).build().unwrap();
+            api::exec_with_empty(client, endpoint).await
+        }
+    }
 }
 
 pub mod role {
     use crate::api;
     use crate::api::pki::{
         requests::{
-            DeleteRoleRequest, ListRolesRequest, ReadRoleRequest, SetRoleRequest,
-            SetRoleRequestBuilder,
+            DeleteRoleRequest, ListRolesRequest, ReadPkiRoleRequest, ReadRoleRequest,
+            SetPkiRoleRequest, SetRoleRequest, SetRoleRequestBuilder,
         },
         responses::{ListRolesResponse, ReadRoleResponse},
+        PkiRole,
     };
     use crate::client::Client;
     use crate::error::ClientError;
@@ -409,6 +647,43 @@ pub mod role {
         api::exec_with_result(client, endpoint).await
     }
 
+    /// Reads a role as a [PkiRole] which can be written back with [set_typed]
+    ///
+    /// See [ReadPkiRoleRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_typed(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+    ) -> Result<PkiRole, ClientError> {
+        let endpoint = ReadPkiRoleRequest::builder()
+            .mount(mount)
+            .name(name)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Creates or updates a role from a [PkiRole] after validating it
+    ///
+    /// See [SetPkiRoleRequest]
+    #[instrument(skip(client, role), err)]
+    pub async fn set_typed(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        role: &PkiRole,
+    ) -> Result<(), ClientError> {
+        role.validate()?;
+        let endpoint = SetPkiRoleRequest::builder()
+            .mount(mount)
+            .name(name)
+            .role(role.clone())
+            .build()
+            .unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
+
     /// Creates or updates a role
     ///
     /// See [SetRoleRequest]
@@ -429,3 +704,607 @@ pub mod role {
         api::exec_with_empty(client, endpoint).await
     }
 }
+
+pub mod issuer {
+    use crate::api;
+    use crate::api::pki::{
+        requests::{
+            DeleteIssuerRequest, ImportIssuersRequest, IssuerGenerateCertificateRequest,
+            IssuerGenerateCertificateRequestBuilder, IssuerSignCertificateRequest,
+            IssuerSignCertificateRequestBuilder, IssuerSignIntermediateRequest,
+            IssuerSignIntermediateRequestBuilder, IssuerSignSelfIssuedRequest,
+            IssuerSignVerbatimRequest, IssuerSignVerbatimRequestBuilder, ListIssuersRequest,
+            PatchIssuerRequest, PatchIssuerRequestBuilder, ReadIssuerRequest,
+            ReadIssuersConfigRequest, SetIssuersConfigRequest, SetIssuersConfigRequestBuilder,
+            UpdateIssuerRequest, UpdateIssuerRequestBuilder,
+        },
+        responses::{
+            GenerateCertificateResponse, ImportIssuersResponse, ListIssuersResponse,
+            ReadIssuerResponse, ReadIssuersConfigResponse, SignCertificateResponse,
+            SignIntermediateResponse, SignSelfIssuedResponse,
+        },
+    };
+    use crate::client::Client;
+    use crate::error::ClientError;
+
+    /// Deletes an issuer
+    ///
+    /// See [DeleteIssuerRequest]
+    #[instrument(skip(client), err)]
+    pub async fn delete(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+    ) -> Result<(), ClientError> {
+        let endpoint = DeleteIssuerRequest::builder()
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .build()
+            .unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
+
+    /// Imports the CA certificates and keys of a PEM bundle
+    ///
+    /// See [ImportIssuersRequest]
+    #[instrument(skip(client, pem_bundle), err)]
+    pub async fn import(
+        client: &impl Client,
+        mount: &str,
+        pem_bundle: &str,
+    ) -> Result<ImportIssuersResponse, ClientError> {
+        let endpoint = ImportIssuersRequest::builder()
+            .mount(mount)
+            .pem_bundle(pem_bundle)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Generates a certificate with an issuer using the given role
+    ///
+    /// See [IssuerGenerateCertificateRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn issue(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        role: &str,
+        opts: Option<&mut IssuerGenerateCertificateRequestBuilder>,
+    ) -> Result<GenerateCertificateResponse, ClientError> {
+        let mut t = IssuerGenerateCertificateRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .role(role)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Lists all issuers
+    ///
+    /// See [ListIssuersRequest]
+    #[instrument(skip(client), err)]
+    pub async fn list(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<ListIssuersResponse, ClientError> {
+        let endpoint = ListIssuersRequest::builder().mount(mount).build().unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Updates the given fields of an issuer's configuration
+    ///
+    /// See [PatchIssuerRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn patch(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        opts: Option<&mut PatchIssuerRequestBuilder>,
+    ) -> Result<ReadIssuerResponse, ClientError> {
+        let mut t = PatchIssuerRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Reads an issuer
+    ///
+    /// See [ReadIssuerRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+    ) -> Result<ReadIssuerResponse, ClientError> {
+        let endpoint = ReadIssuerRequest::builder()
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Reads the default issuer
+    ///
+    /// See [ReadIssuersConfigRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_default(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<ReadIssuersConfigResponse, ClientError> {
+        let endpoint = ReadIssuersConfigRequest::builder()
+            .mount(mount)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Renames an issuer, keeping the rest of its configuration
+    ///
+    /// See [PatchIssuerRequest]
+    #[instrument(skip(client), err)]
+    pub async fn rename(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        name: &str,
+    ) -> Result<ReadIssuerResponse, ClientError> {
+        patch(
+            client,
+            mount,
+            issuer_ref,
+            Some(PatchIssuerRequest::builder().issuer_name(name)),
+        )
+        .await
+    }
+
+    /// Sets the default issuer
+    ///
+    /// See [SetIssuersConfigRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn set_default(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        opts: Option<&mut SetIssuersConfigRequestBuilder>,
+    ) -> Result<ReadIssuersConfigResponse, ClientError> {
+        let mut t = SetIssuersConfigRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .default_issuer(issuer_ref)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Signs a certificate with an issuer using the given role
+    ///
+    /// See [IssuerSignCertificateRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn sign(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        role: &str,
+        csr: &str,
+        common_name: &str,
+        opts: Option<&mut IssuerSignCertificateRequestBuilder>,
+    ) -> Result<SignCertificateResponse, ClientError> {
+        let mut t = IssuerSignCertificateRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .role(role)
+            .csr(csr)
+            .common_name(common_name)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Signs an intermediate CA with an issuer
+    ///
+    /// See [IssuerSignIntermediateRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn sign_intermediate(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        csr: &str,
+        common_name: &str,
+        opts: Option<&mut IssuerSignIntermediateRequestBuilder>,
+    ) -> Result<SignIntermediateResponse, ClientError> {
+        let mut t = IssuerSignIntermediateRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .csr(csr)
+            .common_name(common_name)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Signs a self issued certificate with an issuer
+    ///
+    /// See [IssuerSignSelfIssuedRequest]
+    #[instrument(skip(client, certificate), err)]
+    pub async fn sign_self_issued(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        certificate: &str,
+    ) -> Result<SignSelfIssuedResponse, ClientError> {
+        let endpoint = IssuerSignSelfIssuedRequest::builder()
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .certificate(certificate)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Signs a CSR with an issuer without the restrictions of a role
+    ///
+    /// See [IssuerSignVerbatimRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn sign_verbatim(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        csr: &str,
+        opts: Option<&mut IssuerSignVerbatimRequestBuilder>,
+    ) -> Result<SignCertificateResponse, ClientError> {
+        let mut t = IssuerSignVerbatimRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .csr(csr)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Updates the configuration of an issuer
+    ///
+    /// See [UpdateIssuerRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn update(
+        client: &impl Client,
+        mount: &str,
+        issuer_ref: &str,
+        opts: Option<&mut UpdateIssuerRequestBuilder>,
+    ) -> Result<ReadIssuerResponse, ClientError> {
+        let mut t = UpdateIssuerRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .issuer_ref(issuer_ref)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+}
+
+pub mod key {
+    use crate::api;
+    use crate::api::pki::{
+        requests::{
+            DeleteKeyRequest, GenerateKeyRequest, GenerateKeyRequestBuilder, ImportKeyRequest,
+            ImportKeyRequestBuilder, ListKeysRequest, ReadKeyRequest, UpdateKeyRequest,
+        },
+        responses::{GenerateKeyResponse, ListKeysResponse, ReadKeyResponse},
+    };
+    use crate::client::Client;
+    use crate::error::ClientError;
+
+    /// Deletes a key
+    ///
+    /// See [DeleteKeyRequest]
+    #[instrument(skip(client), err)]
+    pub async fn delete(
+        client: &impl Client,
+        mount: &str,
+        key_ref: &str,
+    ) -> Result<(), ClientError> {
+        let endpoint = DeleteKeyRequest::builder()
+            .mount(mount)
+            .key_ref(key_ref)
+            .build()
+            .unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
+
+    /// Generates a new key
+    ///
+    /// See [GenerateKeyRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn generate(
+        client: &impl Client,
+        mount: &str,
+        key_kind: &str,
+        opts: Option<&mut GenerateKeyRequestBuilder>,
+    ) -> Result<GenerateKeyResponse, ClientError> {
+        let mut t = GenerateKeyRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .key_kind(key_kind)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Imports a PEM encoded private key
+    ///
+    /// See [ImportKeyRequest]
+    #[instrument(skip(client, pem_bundle, opts), err)]
+    pub async fn import(
+        client: &impl Client,
+        mount: &str,
+        pem_bundle: &str,
+        opts: Option<&mut ImportKeyRequestBuilder>,
+    ) -> Result<ReadKeyResponse, ClientError> {
+        let mut t = ImportKeyRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut t)
+            .mount(mount)
+            .pem_bundle(pem_bundle)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Lists all keys
+    ///
+    /// See [ListKeysRequest]
+    #[instrument(skip(client), err)]
+    pub async fn list(client: &impl Client, mount: &str) -> Result<ListKeysResponse, ClientError> {
+        let endpoint = ListKeysRequest::builder().mount(mount).build().unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Reads a key
+    ///
+    /// See [ReadKeyRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read(
+        client: &impl Client,
+        mount: &str,
+        key_ref: &str,
+    ) -> Result<ReadKeyResponse, ClientError> {
+        let endpoint = ReadKeyRequest::builder()
+            .mount(mount)
+            .key_ref(key_ref)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Renames a key
+    ///
+    /// See [UpdateKeyRequest]
+    #[instrument(skip(client), err)]
+    pub async fn rename(
+        client: &impl Client,
+        mount: &str,
+        key_ref: &str,
+        name: &str,
+    ) -> Result<ReadKeyResponse, ClientError> {
+        let endpoint = UpdateKeyRequest::builder()
+            .mount(mount)
+            .key_ref(key_ref)
+            .key_name(name)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+}
+
+pub mod acme {
+    use crate::api;
+    use crate::api::pki::{
+        requests::{ReadAcmeConfigRequest, SetAcmeConfigRequest, SetAcmeConfigRequestBuilder},
+        responses::ReadAcmeConfigResponse,
+    };
+    use crate::client::Client;
+    use crate::error::ClientError;
+
+    /// Reads the ACME configuration
+    ///
+    /// See [ReadAcmeConfigRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_config(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<ReadAcmeConfigResponse, ClientError> {
+        let endpoint = ReadAcmeConfigRequest::builder()
+            .mount(mount)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Sets the ACME configuration
+    ///
+    /// See [SetAcmeConfigRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn set_config(
+        client: &impl Client,
+        mount: &str,
+        opts: Option<&mut SetAcmeConfigRequestBuilder>,
+    ) -> Result<ReadAcmeConfigResponse, ClientError> {
+        let mut t = SetAcmeConfigRequest::builder();
+        let endpoint = opts.unwrap_or(&mut t).mount(mount).build().unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    pub mod eab {
+        use crate::api;
+        use crate::api::pki::{
+            requests::{DeleteEabRequest, GenerateEabRequest, ListEabRequest},
+            responses::{GenerateEabResponse, ListEabResponse},
+        };
+        use crate::client::Client;
+        use crate::error::ClientError;
+
+        /// Deletes an unused EAB key
+        ///
+        /// See [DeleteEabRequest]
+        #[instrument(skip(client), err)]
+        pub async fn delete(
+            client: &impl Client,
+            mount: &str,
+            key_id: &str,
+        ) -> Result<(), ClientError> {
+            let endpoint = DeleteEabRequest::builder()
+                .mount(mount)
+                .key_id(key_id)
+                .build()
+                .unwrap();
+            api::exec_with_empty(client, endpoint).await
+        }
+
+        /// Generates an EAB key for the given ACME directory, such as `acme`
+        /// or `roles/{role}/acme`
+        ///
+        /// See [GenerateEabRequest]
+        #[instrument(skip(client), err)]
+        pub async fn generate(
+            client: &impl Client,
+            mount: &str,
+            directory: &str,
+        ) -> Result<GenerateEabResponse, ClientError> {
+            let endpoint = GenerateEabRequest::builder()
+                .mount(mount)
+                .directory(directory)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
+        /// Lists the unused EAB keys
+        ///
+        /// See [ListEabRequest]
+        #[instrument(skip(client), err)]
+        pub async fn list(
+            client: &impl Client,
+            mount: &str,
+        ) -> Result<ListEabResponse, ClientError> {
+            let endpoint = ListEabRequest::builder().mount(mount).build().unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+    }
+
+    pub mod account {
+        use crate::api;
+        use crate::api::pki::{
+            requests::{ListAcmeAccountsRequest, ReadAcmeAccountRequest, UpdateAcmeAccountRequest},
+            responses::{ListAcmeAccountsResponse, ReadAcmeAccountResponse},
+        };
+        use crate::client::Client;
+        use crate::error::ClientError;
+
+        /// Lists the key IDs of the ACME accounts
+        ///
+        /// See [ListAcmeAccountsRequest]
+        #[instrument(skip(client), err)]
+        pub async fn list(
+            client: &impl Client,
+            mount: &str,
+        ) -> Result<ListAcmeAccountsResponse, ClientError> {
+            let endpoint = ListAcmeAccountsRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
+        /// Reads an ACME account using its key ID
+        ///
+        /// See [ReadAcmeAccountRequest]
+        #[instrument(skip(client), err)]
+        pub async fn read(
+            client: &impl Client,
+            mount: &str,
+            key_id: &str,
+        ) -> Result<ReadAcmeAccountResponse, ClientError> {
+            let endpoint = ReadAcmeAccountRequest::builder()
+                .mount(mount)
+                .key_id(key_id)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+
+        /// Sets the status of an ACME account, either `valid` or `revoked`
+        ///
+        /// See [UpdateAcmeAccountRequest]
+        #[instrument(skip(client), err)]
+        pub async fn update(
+            client: &impl Client,
+            mount: &str,
+            key_id: &str,
+            status: &str,
+        ) -> Result<ReadAcmeAccountResponse, ClientError> {
+            let endpoint = UpdateAcmeAccountRequest::builder()
+                .mount(mount)
+                .key_id(key_id)
+                .status(status)
+                .build()
+                .unwrap();
+            api::exec_with_result(client, endpoint).await
+        }
+    }
+}
+
+pub mod est {
+    use crate::api;
+    use crate::api::pki::{
+        requests::{ReadEstConfigRequest, SetEstConfigRequest, SetEstConfigRequestBuilder},
+        responses::ReadEstConfigResponse,
+    };
+    use crate::client::Client;
+    use crate::error::ClientError;
+
+    /// Reads the EST configuration
+    ///
+    /// See [ReadEstConfigRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_config(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<ReadEstConfigResponse, ClientError> {
+        let endpoint = ReadEstConfigRequest::builder()
+            .mount(mount)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Sets the EST configuration
+    ///
+    /// See [SetEstConfigRequest]
+    #[instrument(skip(client, opts), err)]
+    pub async fn set_config(
+        client: &impl Client,
+        mount: &str,
+        opts: Option<&mut SetEstConfigRequestBuilder>,
+    ) -> Result<(), ClientError> {
+        let mut t = SetEstConfigRequest::builder();
+        let endpoint = opts.unwrap_or(&mut t).mount(mount).build().unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
+}
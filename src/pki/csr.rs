@@ -0,0 +1,379 @@
+//! Certificates signed by the PKI engine for keys which never leave the
+//! process.
+//!
+//! [cert::generate][crate::pki::cert::generate] has Vault generate the
+//! private key and return it in the response. The functions of this module
+//! instead generate the key pair and a CSR locally and only send the CSR to
+//! Vault:
+//!
+//! ```ignore
+//! use vaultrs::pki::csr::{self, CsrSpecBuilder, KeyAlgorithm};
+//!
+//! let spec = CsrSpecBuilder::default()
+//!     .common_name("api.example.com")
+//!     .dns_names(vec!["api.internal".to_string()])
+//!     .build()
+//!     .unwrap();
+//! let bundle = csr::sign(&client, "pki", "api", KeyAlgorithm::EcdsaP256, &spec, None).await?;
+//! bundle.write_pem_files("api.crt", "api.key", Some("ca.crt")).await?;
+//! ```
+use std::{fmt, net::IpAddr, path::Path};
+
+use base64::{engine::general_purpose, Engine as _};
+use p12_keystore::{KeyStore, KeyStoreEntry, PrivateKeyChain};
+use rand::{rngs::OsRng, RngCore};
+use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair, SanType};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    api::pki::{
+        requests::{
+            SignCertificateRequest, SignCertificateRequestBuilder, SignVerbatimRequest,
+            SignVerbatimRequestBuilder,
+        },
+        responses::SignCertificateResponse,
+    },
+    client::Client,
+    error::ClientError,
+    pki::{cert::ca, x509},
+    template::{write_atomic_blocking, WriteOptions},
+};
+
+/// The type of a locally generated key
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    Rsa2048,
+    Rsa3072,
+    Rsa4096,
+    #[default]
+    EcdsaP256,
+    EcdsaP384,
+    Ed25519,
+}
+
+impl KeyAlgorithm {
+    fn signature_algorithm(&self) -> &'static rcgen::SignatureAlgorithm {
+        match self {
+            KeyAlgorithm::Rsa2048 | KeyAlgorithm::Rsa3072 | KeyAlgorithm::Rsa4096 => {
+                &rcgen::PKCS_RSA_SHA256
+            }
+            KeyAlgorithm::EcdsaP256 => &rcgen::PKCS_ECDSA_P256_SHA256,
+            KeyAlgorithm::EcdsaP384 => &rcgen::PKCS_ECDSA_P384_SHA384,
+            KeyAlgorithm::Ed25519 => &rcgen::PKCS_ED25519,
+        }
+    }
+}
+
+/// A PKCS#8 encoded private key
+#[derive(Clone)]
+pub struct PrivateKey {
+    algorithm: KeyAlgorithm,
+    der: Vec<u8>,
+}
+
+impl PrivateKey {
+    /// Generates a new key
+    pub fn generate(algorithm: KeyAlgorithm) -> Result<Self, ClientError> {
+        use rsa::pkcs8::EncodePrivateKey as _;
+
+        let der = match algorithm {
+            KeyAlgorithm::Rsa2048 | KeyAlgorithm::Rsa3072 | KeyAlgorithm::Rsa4096 => {
+                let bits = match algorithm {
+                    KeyAlgorithm::Rsa2048 => 2048,
+                    KeyAlgorithm::Rsa3072 => 3072,
+                    _ => 4096,
+                };
+                rsa::RsaPrivateKey::new(&mut OsRng, bits)
+                    .map_err(|e| key_error(&e.to_string()))?
+                    .to_pkcs8_der()
+                    .map_err(|e| key_error(&e.to_string()))?
+                    .as_bytes()
+                    .to_vec()
+            }
+            KeyAlgorithm::EcdsaP256 => p256::SecretKey::random(&mut OsRng)
+                .to_pkcs8_der()
+                .map_err(|e| key_error(&e.to_string()))?
+                .as_bytes()
+                .to_vec(),
+            KeyAlgorithm::EcdsaP384 => p384::SecretKey::random(&mut OsRng)
+                .to_pkcs8_der()
+                .map_err(|e| key_error(&e.to_string()))?
+                .as_bytes()
+                .to_vec(),
+            KeyAlgorithm::Ed25519 => {
+                let mut seed = [0u8; 32];
+                OsRng.fill_bytes(&mut seed);
+                // Without the public key, which isn't accepted by every parser
+                ed25519_dalek::pkcs8::KeypairBytes {
+                    secret_key: seed,
+                    public_key: None,
+                }
+                .to_pkcs8_der()
+                .map_err(|e| key_error(&e.to_string()))?
+                .as_bytes()
+                .to_vec()
+            }
+        };
+        Ok(PrivateKey { algorithm, der })
+    }
+
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the PKCS#8 DER encoding of the key
+    pub fn to_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Returns the PKCS#8 PEM encoding of the key
+    pub fn to_pem(&self) -> String {
+        pem_encode("PRIVATE KEY", &self.der)
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+/// The subject and subject alternative names of a CSR
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct CsrSpec {
+    pub common_name: String,
+    pub organization: Option<String>,
+    pub organizational_unit: Option<String>,
+    pub country: Option<String>,
+    pub province: Option<String>,
+    pub locality: Option<String>,
+    pub dns_names: Vec<String>,
+    pub email_addresses: Vec<String>,
+    pub ip_addresses: Vec<IpAddr>,
+    pub uris: Vec<String>,
+}
+
+/// Generates a PEM encoded CSR signed by the given key
+pub fn generate_csr(key: &PrivateKey, spec: &CsrSpec) -> Result<String, ClientError> {
+    let mut name = DistinguishedName::new();
+    name.push(DnType::CommonName, spec.common_name.as_str());
+    let optional = [
+        (DnType::OrganizationName, &spec.organization),
+        (DnType::OrganizationalUnitName, &spec.organizational_unit),
+        (DnType::CountryName, &spec.country),
+        (DnType::StateOrProvinceName, &spec.province),
+        (DnType::LocalityName, &spec.locality),
+    ];
+    for (ty, value) in optional.iter() {
+        if let Some(value) = value {
+            name.push(ty.clone(), value.as_str());
+        }
+    }
+
+    let mut params = CertificateParams::default();
+    params.alg = key.algorithm.signature_algorithm();
+    params.distinguished_name = name;
+    params.subject_alt_names = spec
+        .dns_names
+        .iter()
+        .map(|n| SanType::DnsName(n.clone()))
+        .chain(
+            spec.email_addresses
+                .iter()
+                .map(|e| SanType::Rfc822Name(e.clone())),
+        )
+        .chain(spec.ip_addresses.iter().map(|ip| SanType::IpAddress(*ip)))
+        .chain(spec.uris.iter().map(|u| SanType::URI(u.clone())))
+        .collect();
+    params.key_pair = Some(
+        KeyPair::from_der_and_sign_algo(&key.der, params.alg)
+            .map_err(|e| key_error(&e.to_string()))?,
+    );
+
+    rcgen::Certificate::from_params(params)
+        .and_then(|csr| csr.serialize_request_pem())
+        .map_err(|e| key_error(&format!("failed creating CSR: {}", e)))
+}
+
+/// A locally generated private key and the certificate signed for it
+#[derive(Clone, Debug)]
+pub struct KeyBundle {
+    pub private_key: PrivateKey,
+    pub serial_number: String,
+    /// The PEM encoded certificate
+    pub certificate: String,
+    /// The PEM encoded certificates of the issuing CA, starting with the
+    /// issuer of the certificate
+    pub ca_chain: Vec<String>,
+}
+
+impl KeyBundle {
+    /// Bundles a key with the certificate Vault signed for it
+    pub fn new(private_key: PrivateKey, response: SignCertificateResponse) -> Self {
+        let ca_chain = match response.ca_chain {
+            Some(chain) if !chain.is_empty() => chain,
+            _ => vec![response.issuing_ca],
+        };
+        KeyBundle {
+            private_key,
+            serial_number: response.serial_number,
+            certificate: response.certificate,
+            ca_chain,
+        }
+    }
+
+    /// Returns the certificate followed by the CA chain
+    pub fn full_chain_pem(&self) -> String {
+        std::iter::once(&self.certificate)
+            .chain(self.ca_chain.iter())
+            .map(|pem| format!("{}\n", pem.trim_end()))
+            .collect()
+    }
+
+    /// Returns the private key followed by the certificate and the CA chain
+    pub fn to_pem(&self) -> String {
+        format!("{}{}", self.private_key.to_pem(), self.full_chain_pem())
+    }
+
+    /// Returns a PKCS#12 archive containing the private key, the certificate
+    /// and the CA chain, protected by the given password
+    pub fn to_pkcs12(&self, password: &str) -> Result<Vec<u8>, ClientError> {
+        let certs = x509::parse_pem_chain(&self.full_chain_pem())?;
+        let leaf = certs
+            .first()
+            .ok_or_else(|| key_error("the bundle contains no certificate"))?;
+        let local_key_id = Sha256::digest(&leaf.der);
+        let alias = leaf
+            .subject
+            .common_name
+            .clone()
+            .unwrap_or_else(|| self.serial_number.clone());
+
+        let chain = certs
+            .iter()
+            .map(|c| p12_keystore::Certificate::from_der(&c.der))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| key_error(&format!("invalid certificate: {}", e)))?;
+        let mut store = KeyStore::new();
+        store.add_entry(
+            &alias,
+            KeyStoreEntry::PrivateKeyChain(PrivateKeyChain::new(
+                &self.private_key.der,
+                &local_key_id[..20],
+                chain,
+            )),
+        );
+        store
+            .writer(password)
+            .write()
+            .map_err(|e| key_error(&format!("failed creating PKCS#12 archive: {}", e)))
+    }
+
+    /// Writes the certificate, the private key and optionally the CA chain
+    /// to separate PEM files
+    ///
+    /// Each file is replaced atomically. On Unix, the key file is only
+    /// readable by its owner.
+    pub async fn write_pem_files(
+        &self,
+        certificate: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+        ca_chain: Option<impl AsRef<Path>>,
+    ) -> Result<(), ClientError> {
+        write_file(certificate.as_ref(), self.certificate.as_bytes(), false).await?;
+        write_file(
+            private_key.as_ref(),
+            self.private_key.to_pem().as_bytes(),
+            true,
+        )
+        .await?;
+        if let Some(path) = ca_chain {
+            let chain: String = self
+                .ca_chain
+                .iter()
+                .map(|pem| format!("{}\n", pem.trim_end()))
+                .collect();
+            write_file(path.as_ref(), chain.as_bytes(), false).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates a key and has the CSR for it signed using the given role
+///
+/// See [SignCertificateRequest]
+#[instrument(skip(client, spec, opts), err)]
+pub async fn sign(
+    client: &impl Client,
+    mount: &str,
+    role: &str,
+    algorithm: KeyAlgorithm,
+    spec: &CsrSpec,
+    opts: Option<&mut SignCertificateRequestBuilder>,
+) -> Result<KeyBundle, ClientError> {
+    let key = generate_key(algorithm).await?;
+    let csr = generate_csr(&key, spec)?;
+
+    let mut t = SignCertificateRequest::builder();
+    let opts = opts.unwrap_or(&mut t);
+    opts.format("pem");
+    let resp = ca::sign(client, mount, role, &csr, &spec.common_name, Some(opts)).await?;
+    Ok(KeyBundle::new(key, resp))
+}
+
+/// Generates a key and has the CSR for it signed verbatim
+///
+/// See [SignVerbatimRequest]
+#[instrument(skip(client, spec, opts), err)]
+pub async fn sign_verbatim(
+    client: &impl Client,
+    mount: &str,
+    algorithm: KeyAlgorithm,
+    spec: &CsrSpec,
+    opts: Option<&mut SignVerbatimRequestBuilder>,
+) -> Result<KeyBundle, ClientError> {
+    let key = generate_key(algorithm).await?;
+    let csr = generate_csr(&key, spec)?;
+
+    let mut t = SignVerbatimRequest::builder();
+    let opts = opts.unwrap_or(&mut t);
+    opts.format("pem");
+    let resp = ca::sign_verbatim(client, mount, &csr, Some(opts)).await?;
+    Ok(KeyBundle::new(key, resp))
+}
+
+/// Generates a key on the blocking thread pool, RSA keys taking seconds
+async fn generate_key(algorithm: KeyAlgorithm) -> Result<PrivateKey, ClientError> {
+    tokio::task::spawn_blocking(move || PrivateKey::generate(algorithm))
+        .await
+        .map_err(|e| key_error(&e.to_string()))?
+}
+
+/// Atomically replaces the file, so an existing file also ends up with the
+/// permissions of a newly created one
+async fn write_file(path: &Path, contents: &[u8], private: bool) -> Result<(), ClientError> {
+    let opts = WriteOptions {
+        mode: if private { 0o600 } else { 0o644 },
+    };
+    write_atomic_blocking(&path.to_string_lossy(), contents.to_vec(), &opts).await
+}
+
+fn pem_encode(label: &str, der: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+fn key_error(reason: &str) -> ClientError {
+    ClientError::CertificateError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,284 @@
+//! Lists every certificate issued by a PKI engine with its parsed metadata.
+//!
+//! [cert::list] only returns serial numbers, so each certificate is read and
+//! decoded, a bounded number at a time, and can be filtered by its expiry or
+//! the role which issued it before being exported for reviews:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use vaultrs::pki::inventory::{self, InventoryOptions};
+//!
+//! // Report the certificates expiring within the next 30 days
+//! let opts = InventoryOptions {
+//!     expiring_within: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+//!     include_expired: false,
+//!     include_revoked: false,
+//!     ..Default::default()
+//! };
+//! let entries = inventory::collect(&client, "pki", &opts).await?;
+//! std::fs::write("expiring.csv", inventory::to_csv(&entries))?;
+//! ```
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::{stream, Stream, StreamExt};
+use serde::{Serialize, Serializer};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    pki::{cert, x509::Certificate},
+};
+
+/// Options for listing the certificates of a mount
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct InventoryOptions {
+    /// The maximum number of certificates read at the same time
+    pub concurrency: usize,
+    /// Only include certificates expiring within this duration from now
+    pub expiring_within: Option<Duration>,
+    /// Whether certificates which already expired are included
+    pub include_expired: bool,
+    /// Whether revoked certificates are included
+    pub include_revoked: bool,
+    /// Only include certificates issued by this role, which requires the
+    /// certificate metadata only stored by Vault Enterprise
+    pub role: Option<String>,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        InventoryOptions {
+            concurrency: 8,
+            expiring_within: None,
+            include_expired: true,
+            include_revoked: true,
+            role: None,
+        }
+    }
+}
+
+impl InventoryOptions {
+    /// Returns whether an entry is selected by the expiry and revocation
+    /// filters of these options
+    pub fn matches(&self, entry: &InventoryEntry, now: SystemTime) -> bool {
+        if !self.include_revoked && entry.is_revoked() {
+            return false;
+        }
+        if !self.include_expired && entry.is_expired_at(now) {
+            return false;
+        }
+        match self.expiring_within {
+            Some(window) => entry.not_after <= now + window,
+            None => true,
+        }
+    }
+}
+
+/// A certificate issued by a PKI engine
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct InventoryEntry {
+    /// The serial number in the colon separated hex format used by Vault
+    pub serial_number: String,
+    pub common_name: Option<String>,
+    pub subject: String,
+    pub issuer: String,
+    pub dns_names: Vec<String>,
+    pub ip_addresses: Vec<String>,
+    pub email_addresses: Vec<String>,
+    pub uris: Vec<String>,
+    #[serde(serialize_with = "serialize_time")]
+    pub not_before: SystemTime,
+    #[serde(serialize_with = "serialize_time")]
+    pub not_after: SystemTime,
+    pub is_ca: bool,
+    /// When the certificate was revoked, if it was
+    #[serde(serialize_with = "serialize_optional_time")]
+    pub revocation_time: Option<SystemTime>,
+    /// The role which issued the certificate, only known when filtering by
+    /// role
+    pub role: Option<String>,
+}
+
+impl InventoryEntry {
+    /// Creates an entry from a decoded certificate and its revocation time
+    pub fn new(cert: &Certificate, revocation_time: Option<SystemTime>) -> Self {
+        let names = &cert.subject_alt_names;
+        InventoryEntry {
+            serial_number: cert.serial_number.clone(),
+            common_name: cert.subject.common_name.clone(),
+            subject: cert.subject.to_string(),
+            issuer: cert.issuer.to_string(),
+            dns_names: names.dns_names.clone(),
+            ip_addresses: names.ip_addresses.iter().map(|ip| ip.to_string()).collect(),
+            email_addresses: names.email_addresses.clone(),
+            uris: names.uris.clone(),
+            not_before: cert.not_before,
+            not_after: cert.not_after,
+            is_ca: cert.is_ca,
+            revocation_time,
+            role: None,
+        }
+    }
+
+    /// Returns whether the certificate was revoked
+    pub fn is_revoked(&self) -> bool {
+        self.revocation_time.is_some()
+    }
+
+    /// Returns whether the certificate is expired at the given time
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        self.not_after < time
+    }
+
+    /// Returns the time left until the certificate expires, or [None] if it
+    /// already expired
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.not_after.duration_since(SystemTime::now()).ok()
+    }
+}
+
+/// Streams the certificates of a mount which match the given options
+///
+/// Up to `concurrency` certificates are read at the same time, so entries
+/// aren't returned in any particular order. Errors reading a certificate are
+/// returned in the stream and the remaining certificates are still read.
+pub fn stream<'a>(
+    client: &'a impl Client,
+    mount: &'a str,
+    opts: &'a InventoryOptions,
+) -> impl Stream<Item = Result<InventoryEntry, ClientError>> + Send + 'a {
+    stream::once(async move {
+        match cert::list(client, mount).await {
+            Ok(serials) => serials.into_iter().map(Ok).collect(),
+            Err(ClientError::APIError { code: 404, .. }) => Vec::new(),
+            Err(e) => vec![Err(e)],
+        }
+    })
+    .flat_map(stream::iter)
+    .map(move |serial| async move { read_entry(client, mount, serial?, opts).await })
+    .buffer_unordered(opts.concurrency.max(1))
+    .filter_map(|res| async move { res.transpose() })
+}
+
+/// Returns the certificates of a mount which match the given options,
+/// ordered by their expiry
+#[instrument(skip(client, opts), err)]
+pub async fn collect(
+    client: &impl Client,
+    mount: &str,
+    opts: &InventoryOptions,
+) -> Result<Vec<InventoryEntry>, ClientError> {
+    let mut entries = stream(client, mount, opts)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|a, b| {
+        a.not_after
+            .cmp(&b.not_after)
+            .then_with(|| a.serial_number.cmp(&b.serial_number))
+    });
+    Ok(entries)
+}
+
+/// Exports entries as CSV with a header row
+///
+/// Lists of names are separated by spaces within their field.
+pub fn to_csv(entries: &[InventoryEntry]) -> String {
+    let mut csv = String::from(
+        "serial_number,common_name,subject,issuer,dns_names,ip_addresses,\
+         email_addresses,uris,not_before,not_after,is_ca,revocation_time,role\n",
+    );
+    for entry in entries {
+        let fields = [
+            entry.serial_number.clone(),
+            entry.common_name.clone().unwrap_or_default(),
+            entry.subject.clone(),
+            entry.issuer.clone(),
+            entry.dns_names.join(" "),
+            entry.ip_addresses.join(" "),
+            entry.email_addresses.join(" "),
+            entry.uris.join(" "),
+            format_time(entry.not_before),
+            format_time(entry.not_after),
+            entry.is_ca.to_string(),
+            entry.revocation_time.map(format_time).unwrap_or_default(),
+            entry.role.clone().unwrap_or_default(),
+        ];
+        let row = fields
+            .iter()
+            .map(|f| csv_field(f))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Exports entries as a pretty printed JSON array
+pub fn to_json(entries: &[InventoryEntry]) -> Result<String, ClientError> {
+    serde_json::to_string_pretty(entries).map_err(|e| ClientError::JsonParseError { source: e })
+}
+
+async fn read_entry(
+    client: &impl Client,
+    mount: &str,
+    serial: String,
+    opts: &InventoryOptions,
+) -> Result<Option<InventoryEntry>, ClientError> {
+    let resp = match cert::read(client, mount, &serial).await {
+        Ok(resp) => resp,
+        // The certificate was removed by a tidy since it was listed
+        Err(ClientError::APIError { code: 404, .. }) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let cert = Certificate::from_pem(&resp.certificate)?;
+    let revocation_time = match resp.revocation_time {
+        t if t > 0 => Some(UNIX_EPOCH + Duration::from_secs(t as u64)),
+        _ => None,
+    };
+    let mut entry = InventoryEntry::new(&cert, revocation_time);
+    if !opts.matches(&entry, SystemTime::now()) {
+        return Ok(None);
+    }
+
+    if let Some(role) = &opts.role {
+        let metadata = cert::read_metadata(client, mount, &serial).await?;
+        if metadata.role.as_deref() != Some(role.as_str()) {
+            return Ok(None);
+        }
+        entry.role = metadata.role;
+    }
+    Ok(Some(entry))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn format_time(time: SystemTime) -> String {
+    OffsetDateTime::from(time)
+        .format(&Rfc3339)
+        .unwrap_or_default()
+}
+
+fn serialize_time<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_time(*time))
+}
+
+fn serialize_optional_time<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serialize_time(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
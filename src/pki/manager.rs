@@ -0,0 +1,328 @@
+//! Serving certificates issued by a PKI role which are renewed in the
+//! background before they expire.
+//!
+//! A [CertManager] issues a certificate when it's started and reissues it
+//! once a configurable fraction of its lifetime has passed. The current
+//! certificate is published through a watch channel, and the [CertResolver]
+//! handed to rustls always presents the freshest one:
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use vaultrs::api::pki::requests::GenerateCertificateRequest;
+//! use vaultrs::pki::manager::{CertManager, ManagerOptions};
+//!
+//! let mut request = GenerateCertificateRequest::builder();
+//! request.common_name("api.example.com").ttl("24h");
+//! let manager = CertManager::start(
+//!     Arc::new(client),
+//!     "pki",
+//!     "api",
+//!     request,
+//!     ManagerOptions::default(),
+//! )
+//! .await?;
+//!
+//! let config = rustls::ServerConfig::builder()
+//!     .with_safe_defaults()
+//!     .with_no_client_auth()
+//!     .with_cert_resolver(Arc::new(manager.resolver()));
+//! ```
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use futures::future::{self, Either};
+use rustls::{
+    client::ResolvesClientCert,
+    server::{ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+    Certificate, ClientConfig, PrivateKey, RootCertStore, SignatureScheme,
+};
+use rustls_pemfile::Item;
+use tokio::sync::watch;
+
+use crate::{
+    api::pki::{
+        requests::GenerateCertificateRequestBuilder, responses::GenerateCertificateResponse,
+    },
+    client::Client,
+    error::ClientError,
+    pki::{
+        cert,
+        parse::{certificate_error, parse_certificate, system_time, until},
+    },
+};
+
+/// Options for [CertManager]
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct ManagerOptions {
+    /// The fraction of a certificate's lifetime after which it's reissued
+    pub renew_after: f64,
+    /// How long to wait before retrying a failed renewal
+    pub retry_interval: Duration,
+}
+
+impl Default for ManagerOptions {
+    fn default() -> Self {
+        ManagerOptions {
+            renew_after: 2.0 / 3.0,
+            retry_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A certificate and private key issued by a PKI role
+#[derive(Clone)]
+pub struct IssuedCertificate {
+    pub serial_number: String,
+    /// The PEM encoded certificate
+    pub certificate: String,
+    /// The PEM encoded private key
+    pub private_key: String,
+    /// The PEM encoded certificates of the issuing CA, starting with the
+    /// issuer of the certificate
+    pub ca_chain: Vec<String>,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    certified_key: Arc<CertifiedKey>,
+}
+
+impl IssuedCertificate {
+    /// Parses a certificate issued in PEM format
+    pub fn from_response(response: GenerateCertificateResponse) -> Result<Self, ClientError> {
+        let leaf = parse_certificates(&response.certificate)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| certificate_error("the response contains no certificate"))?;
+        let (not_before, not_after) = validity(&leaf)?;
+
+        let ca_chain = match response.ca_chain {
+            Some(chain) if !chain.is_empty() => chain,
+            _ => vec![response.issuing_ca],
+        };
+        let mut chain = vec![leaf];
+        for ca in &ca_chain {
+            chain.extend(parse_certificates(ca)?);
+        }
+        let key = sign::any_supported_type(&parse_private_key(&response.private_key)?)
+            .map_err(|_| certificate_error("the private key type isn't supported"))?;
+
+        Ok(IssuedCertificate {
+            serial_number: response.serial_number,
+            certificate: response.certificate,
+            private_key: response.private_key,
+            ca_chain,
+            not_before,
+            not_after,
+            certified_key: Arc::new(CertifiedKey::new(chain, key)),
+        })
+    }
+
+    /// Returns the certificate chain and signing key used by rustls
+    pub fn certified_key(&self) -> Arc<CertifiedKey> {
+        self.certified_key.clone()
+    }
+
+    /// Returns the time at which the given fraction of the certificate's
+    /// lifetime has passed
+    pub fn renewal_time(&self, fraction: f64) -> SystemTime {
+        let lifetime = self
+            .not_after
+            .duration_since(self.not_before)
+            .unwrap_or_default();
+        self.not_before + lifetime.mul_f64(fraction.clamp(0.0, 1.0))
+    }
+
+    /// Returns a root store which trusts the certificates of the CA chain
+    pub fn root_store(&self) -> Result<RootCertStore, ClientError> {
+        let mut roots = RootCertStore::empty();
+        for ca in &self.ca_chain {
+            for cert in parse_certificates(ca)? {
+                roots
+                    .add(&cert)
+                    .map_err(|e| certificate_error(&format!("invalid CA certificate: {}", e)))?;
+            }
+        }
+        Ok(roots)
+    }
+}
+
+impl fmt::Debug for IssuedCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IssuedCertificate")
+            .field("serial_number", &self.serial_number)
+            .field("not_before", &self.not_before)
+            .field("not_after", &self.not_after)
+            .finish()
+    }
+}
+
+/// Keeps a certificate issued by a PKI role up to date
+///
+/// Renewal runs on a background task which stops once the manager and every
+/// [CertResolver] and receiver obtained from it have been dropped.
+#[derive(Clone, Debug)]
+pub struct CertManager {
+    receiver: watch::Receiver<Arc<IssuedCertificate>>,
+}
+
+impl CertManager {
+    /// Issues a certificate and starts renewing it in the background
+    ///
+    /// `request` is used for every certificate issued, with the format
+    /// forced to PEM. Fails if the first certificate can't be issued; later
+    /// failures are logged and retried after
+    /// [retry_interval][ManagerOptions::retry_interval].
+    #[instrument(skip(client, request, opts), err)]
+    pub async fn start<C: Client + 'static>(
+        client: Arc<C>,
+        mount: &str,
+        role: &str,
+        request: GenerateCertificateRequestBuilder,
+        opts: ManagerOptions,
+    ) -> Result<Self, ClientError> {
+        if !(opts.renew_after > 0.0 && opts.renew_after <= 1.0) {
+            return Err(certificate_error(
+                "the renewal fraction must be greater than 0 and at most 1",
+            ));
+        }
+
+        let issued = issue(client.as_ref(), mount, role, &request).await?;
+        let (sender, receiver) = watch::channel(Arc::new(issued));
+        tokio::spawn(renew(
+            client,
+            mount.to_string(),
+            role.to_string(),
+            request,
+            opts,
+            sender,
+        ));
+        Ok(CertManager { receiver })
+    }
+
+    /// Returns the current certificate
+    pub fn current(&self) -> Arc<IssuedCertificate> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Returns a receiver which is notified whenever the certificate is
+    /// renewed
+    pub fn subscribe(&self) -> watch::Receiver<Arc<IssuedCertificate>> {
+        self.receiver.clone()
+    }
+
+    /// Returns a resolver which presents the current certificate
+    pub fn resolver(&self) -> CertResolver {
+        CertResolver {
+            receiver: self.receiver.clone(),
+        }
+    }
+
+    /// Returns a client configuration which trusts the given roots and
+    /// presents the current certificate as a client certificate
+    pub fn client_config(&self, roots: RootCertStore) -> ClientConfig {
+        ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_client_cert_resolver(Arc::new(self.resolver()))
+    }
+}
+
+/// Presents the current certificate of a [CertManager] to rustls
+///
+/// Can be used both by servers and by clients using mutual TLS.
+#[derive(Clone, Debug)]
+pub struct CertResolver {
+    receiver: watch::Receiver<Arc<IssuedCertificate>>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.receiver.borrow().certified_key())
+    }
+}
+
+impl ResolvesClientCert for CertResolver {
+    fn resolve(&self, _: &[&[u8]], _: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+        Some(self.receiver.borrow().certified_key())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+async fn issue<C: Client>(
+    client: &C,
+    mount: &str,
+    role: &str,
+    request: &GenerateCertificateRequestBuilder,
+) -> Result<IssuedCertificate, ClientError> {
+    let mut request = request.clone();
+    request.format("pem");
+    let response = cert::generate(client, mount, role, Some(&mut request)).await?;
+    IssuedCertificate::from_response(response)
+}
+
+async fn renew<C: Client>(
+    client: Arc<C>,
+    mount: String,
+    role: String,
+    request: GenerateCertificateRequestBuilder,
+    opts: ManagerOptions,
+    sender: watch::Sender<Arc<IssuedCertificate>>,
+) {
+    let mut wait = until(sender.borrow().renewal_time(opts.renew_after));
+    loop {
+        let sleep = Box::pin(tokio::time::sleep(wait));
+        let closed = Box::pin(sender.closed());
+        if let Either::Right(_) = future::select(sleep, closed).await {
+            return;
+        }
+
+        match issue(client.as_ref(), &mount, &role, &request).await {
+            Ok(issued) => {
+                info!(serial_number = %issued.serial_number, "renewed certificate");
+                wait = until(issued.renewal_time(opts.renew_after));
+                sender.send_replace(Arc::new(issued));
+            }
+            Err(e) => {
+                error!(error = %e, "failed renewing certificate");
+                wait = opts.retry_interval;
+            }
+        }
+    }
+}
+
+fn parse_certificates(pem: &str) -> Result<Vec<Certificate>, ClientError> {
+    rustls_pemfile::certs(&mut pem.as_bytes())
+        .map(|certs| certs.into_iter().map(Certificate).collect())
+        .map_err(|e| certificate_error(&format!("invalid PEM: {}", e)))
+}
+
+fn parse_private_key(pem: &str) -> Result<PrivateKey, ClientError> {
+    let mut reader = pem.as_bytes();
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .map_err(|e| certificate_error(&format!("invalid PEM: {}", e)))?
+        {
+            Some(Item::RSAKey(key)) | Some(Item::PKCS8Key(key)) | Some(Item::ECKey(key)) => {
+                return Ok(PrivateKey(key))
+            }
+            Some(_) => continue,
+            None => return Err(certificate_error("the response contains no private key")),
+        }
+    }
+}
+
+fn validity(cert: &Certificate) -> Result<(SystemTime, SystemTime), ClientError> {
+    let cert = parse_certificate(&cert.0)?;
+    let validity = cert.validity();
+    Ok((
+        system_time(validity.not_before.timestamp()),
+        system_time(validity.not_after.timestamp()),
+    ))
+}
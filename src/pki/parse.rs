@@ -0,0 +1,34 @@
+//! Certificate decoding shared by the `x509`, `revocation` and `manager`
+//! modules.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use x509_parser::{certificate::X509Certificate, prelude::FromDer};
+
+use crate::error::ClientError;
+
+/// Decodes a DER encoded certificate
+pub(crate) fn parse_certificate(der: &[u8]) -> Result<X509Certificate<'_>, ClientError> {
+    X509Certificate::from_der(der)
+        .map(|(_, cert)| cert)
+        .map_err(|e| certificate_error(&format!("invalid certificate: {}", e)))
+}
+
+/// Converts a Unix timestamp, which may be before the epoch
+pub(crate) fn system_time(timestamp: i64) -> SystemTime {
+    if timestamp >= 0 {
+        UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(timestamp.unsigned_abs())
+    }
+}
+
+/// Returns the time left until `time`, or zero if it has passed
+pub(crate) fn until(time: SystemTime) -> Duration {
+    time.duration_since(SystemTime::now()).unwrap_or_default()
+}
+
+pub(crate) fn certificate_error(reason: &str) -> ClientError {
+    ClientError::CertificateError {
+        reason: reason.to_string(),
+    }
+}
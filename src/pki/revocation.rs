@@ -0,0 +1,657 @@
+//! Checking whether certificates issued by a PKI mount have been revoked.
+//!
+//! A [RevocationChecker] downloads the CRL of the mount's default issuer,
+//! verifies its signature and caches it until its next update, refreshing it
+//! in the background. Serial numbers are checked against the cached CRL and
+//! Vault's OCSP responder is queried when no current CRL is available:
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use vaultrs::pki::revocation::{RevocationChecker, RevocationOptions, RevocationStatus};
+//!
+//! let checker = RevocationChecker::start(Arc::new(client), "pki", RevocationOptions::default())
+//!     .await?;
+//! if let RevocationStatus::Revoked { .. } = checker.check("1e:1d:ad:de").await? {
+//!     // reject the certificate
+//! }
+//! ```
+//!
+//! With the `cert-manager` feature, a [RevocationVerifier] wraps another rustls
+//! verifier and additionally rejects revoked end-entity certificates.
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    der::{
+        asn1::{Null, OctetString},
+        oid::AssociatedOid,
+        Decode, Encode,
+    },
+    serial_number::SerialNumber,
+    spki::AlgorithmIdentifierOwned,
+};
+use x509_ocsp::{
+    BasicOcspResponse, CertId, CertStatus, OcspRequest, OcspResponse, OcspResponseStatus, Request,
+    TbsRequest,
+};
+use x509_parser::{
+    certificate::X509Certificate, der_parser::asn1_rs::BitString, prelude::FromDer,
+    x509::AlgorithmIdentifier,
+};
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    pki::{
+        cert,
+        parse::{certificate_error, parse_certificate, system_time, until},
+        x509::Certificate,
+    },
+};
+
+/// Options for [RevocationChecker]
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct RevocationOptions {
+    /// The longest time a CRL is used before it's refreshed, even if its
+    /// next update is later
+    pub refresh_interval: Duration,
+    /// How long to wait before retrying a failed refresh
+    pub retry_interval: Duration,
+    /// Whether the delta CRL is merged into the CRL, which requires
+    /// `enable_delta` in the CRL configuration of the mount
+    pub delta: bool,
+    /// Whether Vault's OCSP responder is queried when no current CRL is
+    /// available
+    pub ocsp_fallback: bool,
+    /// Whether [RevocationVerifier] accepts certificates whose revocation
+    /// status can't be determined
+    pub fail_open: bool,
+}
+
+impl Default for RevocationOptions {
+    fn default() -> Self {
+        RevocationOptions {
+            refresh_interval: Duration::from_secs(60 * 60),
+            retry_interval: Duration::from_secs(30),
+            delta: false,
+            ocsp_fallback: true,
+            fail_open: false,
+        }
+    }
+}
+
+/// The revocation status of a certificate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationStatus {
+    Good,
+    Revoked {
+        revocation_time: SystemTime,
+    },
+    /// The OCSP responder doesn't know the certificate
+    Unknown,
+}
+
+/// A CRL whose signature has been verified
+#[derive(Clone, Debug)]
+pub struct Crl {
+    pub this_update: SystemTime,
+    pub next_update: Option<SystemTime>,
+    revoked: HashMap<Vec<u8>, SystemTime>,
+}
+
+impl Crl {
+    /// Parses a DER encoded CRL and verifies that it was signed by the given
+    /// issuer
+    pub fn from_der(der: &[u8], issuer: &Certificate) -> Result<Self, ClientError> {
+        let issuer = parse_certificate(&issuer.der)?;
+        let (_, crl) = x509_parser::parse_x509_crl(der)
+            .map_err(|e| certificate_error(&format!("invalid CRL: {}", e)))?;
+        if crl.issuer() != issuer.subject() {
+            return Err(certificate_error("the CRL belongs to a different issuer"));
+        }
+        crl.verify_signature(issuer.public_key())
+            .map_err(|_| certificate_error("the CRL isn't signed by the issuer"))?;
+
+        Ok(Crl {
+            this_update: system_time(crl.last_update().timestamp()),
+            next_update: crl.next_update().map(|t| system_time(t.timestamp())),
+            revoked: crl
+                .iter_revoked_certificates()
+                .map(|r| {
+                    (
+                        trim_serial(r.raw_serial()).to_vec(),
+                        system_time(r.revocation_date.timestamp()),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Parses a PEM encoded CRL and verifies that it was signed by the given
+    /// issuer
+    pub fn from_pem(pem: &str, issuer: &Certificate) -> Result<Self, ClientError> {
+        let (_, pem) = x509_parser::pem::parse_x509_pem(pem.as_bytes())
+            .map_err(|e| certificate_error(&format!("invalid PEM: {}", e)))?;
+        Crl::from_der(&pem.contents, issuer)
+    }
+
+    /// Returns the time at which the certificate with the given serial number
+    /// was revoked, or `None` if it isn't on the CRL
+    pub fn revocation_time(&self, serial_number: &str) -> Option<SystemTime> {
+        parse_serial(serial_number).and_then(|s| self.revoked.get(&s).copied())
+    }
+
+    /// Returns whether the CRL is still current at the given time
+    pub fn is_current_at(&self, time: SystemTime) -> bool {
+        match self.next_update {
+            Some(next) => time < next,
+            None => true,
+        }
+    }
+
+    /// Returns the number of revoked certificates
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+
+    /// Adds the entries of a delta CRL
+    ///
+    /// The CRL stays current only as long as both CRLs are.
+    pub fn merge(&mut self, delta: Crl) {
+        self.revoked.extend(delta.revoked);
+        self.next_update = match (self.next_update, delta.next_update) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    fn status(&self, serial: &[u8]) -> RevocationStatus {
+        match self.revoked.get(serial) {
+            Some(&revocation_time) => RevocationStatus::Revoked { revocation_time },
+            None => RevocationStatus::Good,
+        }
+    }
+}
+
+/// Checks serial numbers against the CRL and OCSP responder of a PKI mount
+///
+/// Only certificates issued by the default issuer of the mount can be
+/// checked. The CRL is refreshed on a background task which stops once every
+/// clone of the checker has been dropped.
+pub struct RevocationChecker<C: Client> {
+    inner: Arc<Inner<C>>,
+}
+
+struct Inner<C: Client> {
+    client: Arc<C>,
+    mount: String,
+    issuer: Certificate,
+    opts: RevocationOptions,
+    crl: RwLock<Option<CachedCrl>>,
+}
+
+struct CachedCrl {
+    crl: Arc<Crl>,
+    fetched: Instant,
+}
+
+impl<C: Client + 'static> RevocationChecker<C> {
+    /// Reads the default issuer of the mount and its CRL and starts
+    /// refreshing the CRL in the background
+    ///
+    /// Fails if the issuer can't be read. Failing to read the CRL is logged
+    /// and retried after [retry_interval][RevocationOptions::retry_interval].
+    #[instrument(skip(client, opts), err)]
+    pub async fn start(
+        client: Arc<C>,
+        mount: &str,
+        opts: RevocationOptions,
+    ) -> Result<Self, ClientError> {
+        let issuer = cert::read(client.as_ref(), mount, "ca").await?;
+        let checker = RevocationChecker {
+            inner: Arc::new(Inner {
+                client,
+                mount: mount.to_string(),
+                issuer: Certificate::from_pem(&issuer.certificate)?,
+                opts,
+                crl: RwLock::new(None),
+            }),
+        };
+
+        let wait = match checker.refresh().await {
+            Ok(crl) => checker.next_refresh(&crl),
+            Err(e) => {
+                warn!(error = %e, "failed reading CRL");
+                checker.inner.opts.retry_interval
+            }
+        };
+        tokio::spawn(refresh(Arc::downgrade(&checker.inner), wait));
+        Ok(checker)
+    }
+}
+
+impl<C: Client> RevocationChecker<C> {
+    /// Returns the issuer whose certificates are checked
+    pub fn issuer(&self) -> &Certificate {
+        &self.inner.issuer
+    }
+
+    /// Returns the cached CRL
+    pub fn crl(&self) -> Option<Arc<Crl>> {
+        self.inner
+            .crl
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|cached| cached.crl.clone())
+    }
+
+    /// Downloads and verifies the CRL, merging the delta CRL if enabled, and
+    /// replaces the cached one
+    pub async fn refresh(&self) -> Result<Arc<Crl>, ClientError> {
+        let inner = &self.inner;
+        let der = cert::crl::read(inner.client.as_ref(), &inner.mount).await?;
+        let mut crl = Crl::from_der(&der, &inner.issuer)?;
+        if inner.opts.delta {
+            let delta = cert::crl::read_delta(inner.client.as_ref(), &inner.mount).await?;
+            crl.merge(Crl::from_der(&delta, &inner.issuer)?);
+        }
+
+        let crl = Arc::new(crl);
+        *inner.crl.write().unwrap() = Some(CachedCrl {
+            crl: crl.clone(),
+            fetched: Instant::now(),
+        });
+        Ok(crl)
+    }
+
+    /// Returns the revocation status according to the cached CRL, or `None`
+    /// if there's no current CRL
+    pub fn check_cached(&self, serial_number: &str) -> Option<RevocationStatus> {
+        parse_serial(serial_number).and_then(|serial| self.cached_status(&serial))
+    }
+
+    /// Returns the revocation status of the certificate with the given serial
+    /// number
+    ///
+    /// Uses the cached CRL while it's current and refreshes it otherwise. A
+    /// CRL which Vault serves past its next update is used until
+    /// [retry_interval][RevocationOptions::retry_interval] has passed.
+    /// Vault's OCSP responder is queried if the CRL can't be refreshed and
+    /// [ocsp_fallback][RevocationOptions::ocsp_fallback] is set.
+    #[instrument(skip(self), err)]
+    pub async fn check(&self, serial_number: &str) -> Result<RevocationStatus, ClientError> {
+        let serial = parse_serial(serial_number)
+            .ok_or_else(|| certificate_error("invalid serial number"))?;
+        if let Some(status) = self.cached_status(&serial) {
+            return Ok(status);
+        }
+        if let Some(crl) = self.recently_fetched() {
+            return Ok(crl.status(&serial));
+        }
+
+        match self.refresh().await {
+            Ok(crl) => Ok(crl.status(&serial)),
+            Err(e) if self.inner.opts.ocsp_fallback => {
+                warn!(error = %e, "failed reading CRL, falling back to OCSP");
+                self.check_ocsp(serial_number).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the revocation status reported by Vault's OCSP responder
+    #[instrument(skip(self), err)]
+    pub async fn check_ocsp(&self, serial_number: &str) -> Result<RevocationStatus, ClientError> {
+        let serial = parse_serial(serial_number)
+            .ok_or_else(|| certificate_error("invalid serial number"))?;
+        let issuer = parse_certificate(&self.inner.issuer.der)?;
+        let request = ocsp_request(&issuer, &serial)?;
+        let response = cert::ocsp(self.inner.client.as_ref(), &self.inner.mount, &request).await?;
+        ocsp_status(&response, &issuer, &serial, SystemTime::now())
+    }
+
+    fn cached_status(&self, serial: &[u8]) -> Option<RevocationStatus> {
+        self.crl()
+            .filter(|crl| crl.is_current_at(SystemTime::now()))
+            .map(|crl| crl.status(serial))
+    }
+
+    fn recently_fetched(&self) -> Option<Arc<Crl>> {
+        let cached = self.inner.crl.read().unwrap();
+        cached
+            .as_ref()
+            .filter(|cached| cached.fetched.elapsed() < self.inner.opts.retry_interval)
+            .map(|cached| cached.crl.clone())
+    }
+
+    fn next_refresh(&self, crl: &Crl) -> Duration {
+        let opts = &self.inner.opts;
+        match crl.next_update.map(until) {
+            // Vault hasn't rebuilt the CRL yet
+            Some(Duration::ZERO) => opts.retry_interval,
+            Some(wait) => wait.min(opts.refresh_interval),
+            None => opts.refresh_interval,
+        }
+    }
+}
+
+impl<C: Client> Clone for RevocationChecker<C> {
+    fn clone(&self) -> Self {
+        RevocationChecker {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: Client> fmt::Debug for RevocationChecker<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RevocationChecker")
+            .field("mount", &self.inner.mount)
+            .field("issuer", &self.inner.issuer.subject)
+            .field("opts", &self.inner.opts)
+            .finish()
+    }
+}
+
+async fn refresh<C: Client>(inner: Weak<Inner<C>>, mut wait: Duration) {
+    loop {
+        tokio::time::sleep(wait).await;
+        let checker = match inner.upgrade() {
+            Some(inner) => RevocationChecker { inner },
+            None => return,
+        };
+
+        wait = match checker.refresh().await {
+            Ok(crl) => {
+                debug!(revoked = crl.len(), "refreshed CRL");
+                checker.next_refresh(&crl)
+            }
+            Err(e) => {
+                error!(error = %e, "failed refreshing CRL");
+                checker.inner.opts.retry_interval
+            }
+        };
+    }
+}
+
+#[cfg(feature = "cert-manager")]
+pub use verifier::RevocationVerifier;
+
+#[cfg(feature = "cert-manager")]
+mod verifier {
+    use std::time::SystemTime;
+
+    use rustls::{
+        client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        server::{ClientCertVerified, ClientCertVerifier},
+        Certificate, CertificateError, DigitallySignedStruct, DistinguishedName, Error, ServerName,
+        SignatureScheme,
+    };
+
+    use super::{ocsp_status, parse_certificate, trim_serial, RevocationChecker, RevocationStatus};
+    use crate::client::Client;
+
+    /// A rustls verifier which rejects revoked certificates
+    ///
+    /// Certificates are first verified by the wrapped verifier. The end-entity
+    /// certificate is then checked against the cached CRL of the
+    /// [RevocationChecker] or, when verifying a server, against a stapled OCSP
+    /// response. Since rustls verifies synchronously, Vault's OCSP responder
+    /// isn't queried. Certificates whose status can't be determined, including
+    /// those not issued by the checker's issuer, are rejected unless
+    /// [fail_open][super::RevocationOptions::fail_open] is set.
+    #[derive(Debug)]
+    pub struct RevocationVerifier<V, C: Client> {
+        inner: V,
+        checker: RevocationChecker<C>,
+    }
+
+    impl<V, C: Client> RevocationVerifier<V, C> {
+        pub fn new(inner: V, checker: RevocationChecker<C>) -> Self {
+            RevocationVerifier { inner, checker }
+        }
+
+        fn check(
+            &self,
+            end_entity: &Certificate,
+            ocsp_response: &[u8],
+            now: SystemTime,
+        ) -> Result<(), Error> {
+            let issuer = parse_certificate(&self.checker.inner.issuer.der)
+                .map_err(|e| Error::General(e.to_string()))?;
+            let cert = parse_certificate(&end_entity.0)
+                .map_err(|_| Error::InvalidCertificate(CertificateError::BadEncoding))?;
+
+            let status = if cert.issuer() == issuer.subject() {
+                let serial = trim_serial(cert.raw_serial());
+                self.checker.cached_status(serial).or_else(|| {
+                    if ocsp_response.is_empty() {
+                        None
+                    } else {
+                        ocsp_status(ocsp_response, &issuer, serial, now).ok()
+                    }
+                })
+            } else {
+                None
+            };
+
+            match status {
+                Some(RevocationStatus::Good) => Ok(()),
+                Some(RevocationStatus::Revoked { .. }) => {
+                    Err(Error::InvalidCertificate(CertificateError::Revoked))
+                }
+                _ if self.checker.inner.opts.fail_open => Ok(()),
+                _ => Err(Error::General(
+                    "the revocation status of the certificate is unknown".into(),
+                )),
+            }
+        }
+    }
+
+    impl<V: ServerCertVerifier, C: Client> ServerCertVerifier for RevocationVerifier<V, C> {
+        fn verify_server_cert(
+            &self,
+            end_entity: &Certificate,
+            intermediates: &[Certificate],
+            server_name: &ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: SystemTime,
+        ) -> Result<ServerCertVerified, Error> {
+            let verified = self.inner.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            )?;
+            self.check(end_entity, ocsp_response, now)?;
+            Ok(verified)
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &Certificate,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            ServerCertVerifier::verify_tls12_signature(&self.inner, message, cert, dss)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &Certificate,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            ServerCertVerifier::verify_tls13_signature(&self.inner, message, cert, dss)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            ServerCertVerifier::supported_verify_schemes(&self.inner)
+        }
+
+        fn request_scts(&self) -> bool {
+            self.inner.request_scts()
+        }
+    }
+
+    impl<V: ClientCertVerifier, C: Client> ClientCertVerifier for RevocationVerifier<V, C> {
+        fn offer_client_auth(&self) -> bool {
+            self.inner.offer_client_auth()
+        }
+
+        fn client_auth_mandatory(&self) -> bool {
+            self.inner.client_auth_mandatory()
+        }
+
+        fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+            self.inner.client_auth_root_subjects()
+        }
+
+        fn verify_client_cert(
+            &self,
+            end_entity: &Certificate,
+            intermediates: &[Certificate],
+            now: SystemTime,
+        ) -> Result<ClientCertVerified, Error> {
+            let verified = self
+                .inner
+                .verify_client_cert(end_entity, intermediates, now)?;
+            self.check(end_entity, &[], now)?;
+            Ok(verified)
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &Certificate,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            ClientCertVerifier::verify_tls12_signature(&self.inner, message, cert, dss)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &Certificate,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            ClientCertVerifier::verify_tls13_signature(&self.inner, message, cert, dss)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            ClientCertVerifier::supported_verify_schemes(&self.inner)
+        }
+    }
+}
+
+/// Returns a DER encoded OCSP request for a certificate of the issuer
+fn ocsp_request(issuer: &X509Certificate, serial: &[u8]) -> Result<Vec<u8>, ClientError> {
+    let encoding =
+        |e: x509_cert::der::Error| certificate_error(&format!("invalid OCSP request: {}", e));
+    let cert_id = CertId {
+        hash_algorithm: AlgorithmIdentifierOwned {
+            oid: Sha256::OID,
+            parameters: Some(Null.into()),
+        },
+        issuer_name_hash: OctetString::new(Sha256::digest(issuer.subject().as_raw()).to_vec())
+            .map_err(encoding)?,
+        issuer_key_hash: OctetString::new(
+            Sha256::digest(&issuer.public_key().subject_public_key.data).to_vec(),
+        )
+        .map_err(encoding)?,
+        serial_number: SerialNumber::new(serial).map_err(encoding)?,
+    };
+    OcspRequest {
+        tbs_request: TbsRequest {
+            request_list: vec![Request {
+                req_cert: cert_id,
+                single_request_extensions: None,
+            }],
+            ..Default::default()
+        },
+        optional_signature: None,
+    }
+    .to_der()
+    .map_err(encoding)
+}
+
+/// Returns the status of a certificate from a DER encoded OCSP response
+/// signed by its issuer
+fn ocsp_status(
+    der: &[u8],
+    issuer: &X509Certificate,
+    serial: &[u8],
+    now: SystemTime,
+) -> Result<RevocationStatus, ClientError> {
+    let invalid =
+        |e: x509_cert::der::Error| certificate_error(&format!("invalid OCSP response: {}", e));
+    let response = OcspResponse::from_der(der).map_err(invalid)?;
+    if response.response_status != OcspResponseStatus::Successful {
+        return Err(certificate_error(&format!(
+            "the OCSP responder failed with {:?}",
+            response.response_status
+        )));
+    }
+    let bytes = response
+        .response_bytes
+        .filter(|b| b.response_type == BasicOcspResponse::OID)
+        .ok_or_else(|| certificate_error("the OCSP response isn't a basic response"))?;
+    let basic = BasicOcspResponse::from_der(bytes.response.as_bytes()).map_err(invalid)?;
+
+    let algorithm = basic.signature_algorithm.to_der().map_err(invalid)?;
+    let (_, algorithm) = AlgorithmIdentifier::from_der(&algorithm)
+        .map_err(|_| certificate_error("invalid OCSP signature algorithm"))?;
+    let signature = BitString::new(basic.signature.unused_bits(), basic.signature.raw_bytes());
+    let data = basic.tbs_response_data.to_der().map_err(invalid)?;
+    x509_parser::verify::verify_signature(issuer.public_key(), &algorithm, &signature, &data)
+        .map_err(|_| certificate_error("the OCSP response isn't signed by the issuer"))?;
+
+    let single = basic
+        .tbs_response_data
+        .responses
+        .into_iter()
+        .find(|r| trim_serial(r.cert_id.serial_number.as_bytes()) == serial)
+        .ok_or_else(|| certificate_error("the OCSP response doesn't cover the certificate"))?;
+    if let Some(next_update) = single.next_update {
+        if now >= UNIX_EPOCH + next_update.0.to_unix_duration() {
+            return Err(certificate_error("the OCSP response has expired"));
+        }
+    }
+    Ok(match single.cert_status {
+        CertStatus::Good(_) => RevocationStatus::Good,
+        CertStatus::Revoked(info) => RevocationStatus::Revoked {
+            revocation_time: UNIX_EPOCH + info.revocation_time.0.to_unix_duration(),
+        },
+        CertStatus::Unknown(_) => RevocationStatus::Unknown,
+    })
+}
+
+/// Parses a serial number formatted as hex bytes separated by colons or
+/// hyphens
+fn parse_serial(serial: &str) -> Option<Vec<u8>> {
+    let bytes = serial
+        .split([':', '-'])
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some(trim_serial(&bytes).to_vec())
+}
+
+/// Strips the leading zeros of a DER encoded serial number
+fn trim_serial(serial: &[u8]) -> &[u8] {
+    let start = serial.iter().position(|&b| b != 0).unwrap_or(serial.len());
+    &serial[start..]
+}
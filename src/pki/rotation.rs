@@ -0,0 +1,388 @@
+//! Rotates the default issuer of a PKI engine to a new root or intermediate
+//! CA.
+//!
+//! The new CA is cross-signed by the current default issuer so certificates
+//! chaining to either of them stay trusted while clients move to the new one.
+//! Each step updates a [RotationState] which can be persisted, so a rotation
+//! which failed part way through can be resumed with [run]:
+//!
+//! ```ignore
+//! use vaultrs::pki::rotation::{self, RotationOptions};
+//!
+//! let opts = RotationOptions {
+//!     common_name: "Example Root CA 2024".into(),
+//!     issuer_name: Some("root-2024".into()),
+//!     ..Default::default()
+//! };
+//! let mut state = rotation::prepare(&client, "pki").await?;
+//! if let Err(e) = rotation::run(&client, &mut state, &opts).await {
+//!     // Save the state and call `run` with it again later
+//!     std::fs::write("rotation.json", serde_json::to_vec(&state)?)?;
+//! }
+//! ```
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::pki::requests::{
+        GenerateIntermediateRequest, GenerateRootRequest, IssuerSignIntermediateRequest,
+    },
+    client::Client,
+    error::ClientError,
+    pki::{cert, issuer},
+};
+
+/// Where the new CA comes from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RotationKind {
+    /// A new self-signed root CA
+    #[default]
+    Root,
+    /// A new intermediate CA signed by an issuer of another mount
+    Intermediate {
+        parent_mount: String,
+        parent_issuer: String,
+    },
+}
+
+/// Options for rotating the default issuer of a mount
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct RotationOptions {
+    pub kind: RotationKind,
+    pub common_name: String,
+    pub issuer_name: Option<String>,
+    pub key_name: Option<String>,
+    pub key_type: Option<String>,
+    pub key_bits: Option<u64>,
+    /// The TTL of the new CA's certificate and of its cross-signed certificate
+    pub ttl: Option<String>,
+    /// Whether the new CA is cross-signed by the current default issuer
+    pub cross_sign: bool,
+    /// Whether the new CA becomes the default issuer
+    pub set_default: bool,
+}
+
+impl Default for RotationOptions {
+    fn default() -> Self {
+        RotationOptions {
+            kind: RotationKind::Root,
+            common_name: String::new(),
+            issuer_name: None,
+            key_name: None,
+            key_type: None,
+            key_bits: None,
+            ttl: None,
+            cross_sign: true,
+            set_default: true,
+        }
+    }
+}
+
+impl RotationOptions {
+    /// Returns the steps of the given rotation with these options, in order
+    ///
+    /// The new CA is only cross-signed when the mount had a default issuer.
+    pub fn steps(&self, state: &RotationState) -> Vec<RotationStep> {
+        let mut steps = vec![RotationStep::Generate];
+        if let RotationKind::Intermediate { .. } = self.kind {
+            steps.push(RotationStep::SignWithParent);
+            steps.push(RotationStep::Import);
+        }
+        if self.cross_sign && state.old_issuer.is_some() {
+            steps.push(RotationStep::CrossSign);
+            steps.push(RotationStep::ImportCrossSigned);
+        }
+        if self.set_default {
+            steps.push(RotationStep::SetDefault);
+        }
+        steps.push(RotationStep::Done);
+        steps
+    }
+}
+
+/// A step of a rotation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStep {
+    /// Generates the key of the new CA, along with its certificate for a root
+    /// or a CSR for an intermediate
+    #[default]
+    Generate,
+    /// Signs the CSR of a new intermediate with the parent issuer
+    SignWithParent,
+    /// Imports the signed intermediate as a new issuer
+    Import,
+    /// Signs a CSR for the key of the new CA with the old issuer
+    CrossSign,
+    /// Imports the cross-signed certificate as an issuer of the new key
+    ImportCrossSigned,
+    /// Makes the new CA the default issuer
+    SetDefault,
+    Done,
+}
+
+/// The progress of a rotation
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationState {
+    pub mount: String,
+    /// The next step to run
+    pub step: RotationStep,
+    /// The steps planned by the first call to [step], see
+    /// [RotationOptions::steps]
+    #[serde(default)]
+    pub steps: Vec<RotationStep>,
+    /// The default issuer before the rotation, if the mount had one
+    pub old_issuer: Option<String>,
+    pub new_key: Option<String>,
+    pub new_issuer: Option<String>,
+    /// The CSR of a new intermediate
+    pub csr: Option<String>,
+    /// The new intermediate signed by its parent, followed by its CA chain
+    pub certificate: Option<String>,
+    pub cross_signed_certificate: Option<String>,
+    pub cross_signed_issuer: Option<String>,
+}
+
+impl RotationState {
+    /// Returns whether every step of the rotation was run
+    pub fn is_done(&self) -> bool {
+        self.step == RotationStep::Done
+    }
+}
+
+/// Starts a rotation of the default issuer of a mount
+///
+/// Nothing is changed until the returned state is passed to [step] or [run].
+#[instrument(skip(client), err)]
+pub async fn prepare(client: &impl Client, mount: &str) -> Result<RotationState, ClientError> {
+    let old_issuer = match issuer::read_default(client, mount).await {
+        Ok(config) if !config.default.is_empty() => Some(config.default),
+        Ok(_) | Err(ClientError::APIError { code: 404, .. }) => None,
+        Err(e) => return Err(e),
+    };
+    Ok(RotationState {
+        mount: mount.to_string(),
+        old_issuer,
+        ..Default::default()
+    })
+}
+
+/// Runs the remaining steps of a rotation
+///
+/// The state is updated after each successful step, so when a step fails
+/// calling this again with the same state continues from that step. The
+/// options must plan the same steps as the ones the rotation was started
+/// with.
+#[instrument(skip(client, state, opts), fields(mount = %state.mount), err)]
+pub async fn run(
+    client: &impl Client,
+    state: &mut RotationState,
+    opts: &RotationOptions,
+) -> Result<(), ClientError> {
+    while !state.is_done() {
+        let current = state.step;
+        step(client, state, opts).await?;
+        info!(step = ?current, next = ?state.step, "ran rotation step");
+    }
+    Ok(())
+}
+
+/// Runs the next step of a rotation and returns the step after it
+#[instrument(skip(client, state, opts), fields(mount = %state.mount, step = ?state.step), err)]
+pub async fn step(
+    client: &impl Client,
+    state: &mut RotationState,
+    opts: &RotationOptions,
+) -> Result<RotationStep, ClientError> {
+    if state.is_done() {
+        return Ok(RotationStep::Done);
+    }
+    let planned = opts.steps(state);
+    if state.steps.is_empty() {
+        state.steps = planned;
+    } else if state.steps != planned {
+        return Err(rotation_error(
+            "the options plan different steps than the rotation was started with",
+        ));
+    }
+    let next = state
+        .steps
+        .iter()
+        .skip_while(|s| **s != state.step)
+        .nth(1)
+        .copied()
+        .ok_or_else(|| rotation_error("the next step isn't one of the planned steps"))?;
+
+    let mount = state.mount.clone();
+    match state.step {
+        RotationStep::Generate => generate(client, &mount, state, opts).await?,
+        RotationStep::SignWithParent => {
+            let (parent_mount, parent_issuer) = match &opts.kind {
+                RotationKind::Intermediate {
+                    parent_mount,
+                    parent_issuer,
+                } => (parent_mount, parent_issuer),
+                RotationKind::Root => return Err(rotation_error("a root has no parent")),
+            };
+            let csr = required(&state.csr, "CSR")?;
+            let mut sign = IssuerSignIntermediateRequest::builder();
+            sign.use_csr_values(true);
+            if let Some(ttl) = &opts.ttl {
+                sign.ttl(ttl);
+            }
+            let resp = issuer::sign_intermediate(
+                client,
+                parent_mount,
+                parent_issuer,
+                &csr,
+                &opts.common_name,
+                Some(&mut sign),
+            )
+            .await?;
+            let mut bundle = vec![resp.certificate];
+            bundle.extend(resp.ca_chain.unwrap_or_default());
+            state.certificate = Some(bundle.join("\n"));
+        }
+        RotationStep::Import => {
+            let bundle = required(&state.certificate, "signed certificate")?;
+            state.new_issuer = Some(import(client, &mount, &bundle, &state.new_key).await?);
+        }
+        RotationStep::CrossSign => {
+            let old_issuer = required(&state.old_issuer, "old issuer")?;
+            let new_key = required(&state.new_key, "new key")?;
+            let resp = cert::ca::int::generate(
+                client,
+                &mount,
+                "existing",
+                &opts.common_name,
+                Some(GenerateIntermediateRequest::builder().key_ref(new_key)),
+            )
+            .await?;
+            let mut sign = IssuerSignIntermediateRequest::builder();
+            sign.use_csr_values(true);
+            if let Some(ttl) = &opts.ttl {
+                sign.ttl(ttl);
+            }
+            let resp = issuer::sign_intermediate(
+                client,
+                &mount,
+                &old_issuer,
+                &resp.csr,
+                &opts.common_name,
+                Some(&mut sign),
+            )
+            .await?;
+            state.cross_signed_certificate = Some(resp.certificate);
+        }
+        RotationStep::ImportCrossSigned => {
+            let certificate =
+                required(&state.cross_signed_certificate, "cross-signed certificate")?;
+            state.cross_signed_issuer =
+                Some(import(client, &mount, &certificate, &state.new_key).await?);
+        }
+        RotationStep::SetDefault => {
+            let new_issuer = required(&state.new_issuer, "new issuer")?;
+            issuer::set_default(client, &mount, &new_issuer, None).await?;
+        }
+        RotationStep::Done => return Ok(RotationStep::Done),
+    }
+
+    state.step = next;
+    Ok(next)
+}
+
+async fn generate(
+    client: &impl Client,
+    mount: &str,
+    state: &mut RotationState,
+    opts: &RotationOptions,
+) -> Result<(), ClientError> {
+    if opts.common_name.is_empty() {
+        return Err(rotation_error("a common name is required"));
+    }
+    match opts.kind {
+        RotationKind::Root => {
+            let mut req = GenerateRootRequest::builder();
+            req.common_name(&opts.common_name);
+            if let Some(name) = &opts.issuer_name {
+                req.issuer_name(name);
+            }
+            if let Some(name) = &opts.key_name {
+                req.key_name(name);
+            }
+            if let Some(key_type) = &opts.key_type {
+                req.key_type(key_type);
+            }
+            if let Some(bits) = opts.key_bits {
+                req.key_bits(bits);
+            }
+            if let Some(ttl) = &opts.ttl {
+                req.ttl(ttl);
+            }
+            let resp = cert::ca::generate(client, mount, "internal", Some(&mut req))
+                .await?
+                .ok_or_else(|| rotation_error("no root was generated"))?;
+            state.new_issuer = Some(
+                resp.issuer_id
+                    .ok_or_else(|| rotation_error("the mount doesn't support multiple issuers"))?,
+            );
+            state.new_key = resp.key_id;
+        }
+        RotationKind::Intermediate { .. } => {
+            let mut req = GenerateIntermediateRequest::builder();
+            if let Some(name) = &opts.key_name {
+                req.key_name(name);
+            }
+            if let Some(key_type) = &opts.key_type {
+                req.key_type(key_type);
+            }
+            if let Some(bits) = opts.key_bits {
+                req.key_bits(bits);
+            }
+            let resp = cert::ca::int::generate(
+                client,
+                mount,
+                "internal",
+                &opts.common_name,
+                Some(&mut req),
+            )
+            .await?;
+            state.csr = Some(resp.csr);
+            state.new_key = resp.key_id;
+        }
+    }
+    Ok(())
+}
+
+/// Imports a certificate bundle, returning the issuer using the given key
+async fn import(
+    client: &impl Client,
+    mount: &str,
+    bundle: &str,
+    key: &Option<String>,
+) -> Result<String, ClientError> {
+    let resp = issuer::import(client, mount, bundle).await?;
+    let by_key = resp.mapping.and_then(|mapping| {
+        mapping
+            .into_iter()
+            .find(|(_, k)| Some(k) == key.as_ref())
+            .map(|(issuer, _)| issuer)
+    });
+    let imported = resp.imported_issuers.unwrap_or_default();
+    let existing = resp.existing_issuers.unwrap_or_default();
+    by_key
+        .or_else(|| imported.into_iter().chain(existing).next())
+        .ok_or_else(|| rotation_error("no issuer was imported"))
+}
+
+fn required(value: &Option<String>, name: &str) -> Result<String, ClientError> {
+    value
+        .clone()
+        .ok_or_else(|| rotation_error(&format!("the state has no {}", name)))
+}
+
+fn rotation_error(reason: &str) -> ClientError {
+    ClientError::RotationError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,434 @@
+//! Typed views of the PEM encoded certificates returned by the PKI engine.
+//!
+//! Responses which contain certificates gain a `parsed()` accessor which
+//! decodes the certificate together with its CA chain:
+//!
+//! ```ignore
+//! use vaultrs::api::pki::requests::GenerateCertificateRequest;
+//! use vaultrs::pki::cert;
+//!
+//! let resp = cert::generate(&client, "pki", "web", Some(GenerateCertificateRequest::builder().common_name("example.com"))).await?;
+//! let parsed = resp.parsed()?;
+//! println!("{} expires at {:?}", parsed.certificate.serial_number, parsed.certificate.not_after);
+//! parsed.verify_chain()?;
+//! ```
+use std::{
+    convert::TryFrom,
+    fmt,
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use x509_parser::{
+    extensions::{GeneralName, ParsedExtension},
+    pem::Pem,
+    x509::{AttributeTypeAndValue, X509Name},
+};
+
+use crate::{
+    api::pki::responses::{
+        GenerateCertificateResponse, ReadCertificateResponse, SignCertificateResponse,
+        SignIntermediateResponse,
+    },
+    error::ClientError,
+    pki::parse::{certificate_error, parse_certificate, system_time},
+};
+
+/// A distinguished name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Name {
+    pub common_name: Option<String>,
+    pub organization: Vec<String>,
+    pub organizational_unit: Vec<String>,
+    pub country: Vec<String>,
+    pub province: Vec<String>,
+    pub locality: Vec<String>,
+    display: String,
+    raw: Vec<u8>,
+}
+
+impl Name {
+    fn from_x509(name: &X509Name) -> Self {
+        Name {
+            common_name: strings(name.iter_common_name()).into_iter().next(),
+            organization: strings(name.iter_organization()),
+            organizational_unit: strings(name.iter_organizational_unit()),
+            country: strings(name.iter_country()),
+            province: strings(name.iter_state_or_province()),
+            locality: strings(name.iter_locality()),
+            display: name.to_string(),
+            raw: name.as_raw().to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display)
+    }
+}
+
+/// The subject alternative names of a certificate
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubjectAltNames {
+    pub dns_names: Vec<String>,
+    pub email_addresses: Vec<String>,
+    pub ip_addresses: Vec<IpAddr>,
+    pub uris: Vec<String>,
+}
+
+/// A purpose of the key of a certificate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyUsage {
+    DigitalSignature,
+    NonRepudiation,
+    KeyEncipherment,
+    DataEncipherment,
+    KeyAgreement,
+    KeyCertSign,
+    CrlSign,
+    EncipherOnly,
+    DecipherOnly,
+}
+
+/// An extended purpose of the key of a certificate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtendedKeyUsage {
+    Any,
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OcspSigning,
+    /// Any other usage, identified by its OID
+    Other(String),
+}
+
+/// A decoded X.509 certificate
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    /// The serial number in the colon separated hex format used by Vault
+    pub serial_number: String,
+    pub subject: Name,
+    pub issuer: Name,
+    pub subject_alt_names: SubjectAltNames,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    pub key_usage: Vec<KeyUsage>,
+    pub extended_key_usage: Vec<ExtendedKeyUsage>,
+    pub is_ca: bool,
+    pub path_length: Option<u32>,
+    pub subject_key_id: Option<Vec<u8>>,
+    pub authority_key_id: Option<Vec<u8>>,
+    /// The DER encoded certificate
+    pub der: Vec<u8>,
+}
+
+impl Certificate {
+    /// Decodes a DER encoded certificate
+    pub fn from_der(der: &[u8]) -> Result<Self, ClientError> {
+        let cert = parse_certificate(der)?;
+        let invalid = |e: x509_parser::error::X509Error| {
+            certificate_error(&format!("invalid certificate extension: {}", e))
+        };
+
+        let mut subject_alt_names = SubjectAltNames::default();
+        if let Some(san) = cert.subject_alternative_name().map_err(invalid)? {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(name) => {
+                        subject_alt_names.dns_names.push(name.to_string())
+                    }
+                    GeneralName::RFC822Name(email) => {
+                        subject_alt_names.email_addresses.push(email.to_string())
+                    }
+                    GeneralName::URI(uri) => subject_alt_names.uris.push(uri.to_string()),
+                    GeneralName::IPAddress(ip) => {
+                        if let Some(ip) = ip_address(ip) {
+                            subject_alt_names.ip_addresses.push(ip)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut key_usage = Vec::new();
+        if let Some(usage) = cert.key_usage().map_err(invalid)? {
+            let usage = usage.value;
+            let flags = [
+                (usage.digital_signature(), KeyUsage::DigitalSignature),
+                (usage.non_repudiation(), KeyUsage::NonRepudiation),
+                (usage.key_encipherment(), KeyUsage::KeyEncipherment),
+                (usage.data_encipherment(), KeyUsage::DataEncipherment),
+                (usage.key_agreement(), KeyUsage::KeyAgreement),
+                (usage.key_cert_sign(), KeyUsage::KeyCertSign),
+                (usage.crl_sign(), KeyUsage::CrlSign),
+                (usage.encipher_only(), KeyUsage::EncipherOnly),
+                (usage.decipher_only(), KeyUsage::DecipherOnly),
+            ];
+            key_usage.extend(flags.iter().filter(|(set, _)| *set).map(|(_, u)| *u));
+        }
+
+        let mut extended_key_usage = Vec::new();
+        if let Some(usage) = cert.extended_key_usage().map_err(invalid)? {
+            let usage = usage.value;
+            let flags = [
+                (usage.any, ExtendedKeyUsage::Any),
+                (usage.server_auth, ExtendedKeyUsage::ServerAuth),
+                (usage.client_auth, ExtendedKeyUsage::ClientAuth),
+                (usage.code_signing, ExtendedKeyUsage::CodeSigning),
+                (usage.email_protection, ExtendedKeyUsage::EmailProtection),
+                (usage.time_stamping, ExtendedKeyUsage::TimeStamping),
+                (usage.ocsp_signing, ExtendedKeyUsage::OcspSigning),
+            ];
+            extended_key_usage.extend(flags.iter().filter(|(set, _)| *set).map(|(_, u)| u.clone()));
+            extended_key_usage.extend(
+                usage
+                    .other
+                    .iter()
+                    .map(|oid| ExtendedKeyUsage::Other(oid.to_id_string())),
+            );
+        }
+
+        let constraints = cert.basic_constraints().map_err(invalid)?;
+        let mut subject_key_id = None;
+        let mut authority_key_id = None;
+        for ext in cert.extensions() {
+            match ext.parsed_extension() {
+                ParsedExtension::SubjectKeyIdentifier(id) => subject_key_id = Some(id.0.to_vec()),
+                ParsedExtension::AuthorityKeyIdentifier(aki) => {
+                    authority_key_id = aki.key_identifier.as_ref().map(|id| id.0.to_vec())
+                }
+                _ => {}
+            }
+        }
+
+        let validity = cert.validity();
+        Ok(Certificate {
+            serial_number: cert.raw_serial_as_string(),
+            subject: Name::from_x509(cert.subject()),
+            issuer: Name::from_x509(cert.issuer()),
+            subject_alt_names,
+            not_before: system_time(validity.not_before.timestamp()),
+            not_after: system_time(validity.not_after.timestamp()),
+            key_usage,
+            extended_key_usage,
+            is_ca: constraints.as_ref().map(|c| c.value.ca).unwrap_or(false),
+            path_length: constraints.and_then(|c| c.value.path_len_constraint),
+            subject_key_id,
+            authority_key_id,
+            der: der.to_vec(),
+        })
+    }
+
+    /// Decodes the first certificate of a PEM encoded bundle
+    pub fn from_pem(pem: &str) -> Result<Self, ClientError> {
+        parse_pem_chain(pem)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| certificate_error("no certificate found"))
+    }
+
+    /// Returns whether the current time is within the validity period
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_at(SystemTime::now())
+    }
+
+    /// Returns whether the given time is within the validity period
+    pub fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// Returns the time left until the certificate expires, or `None` if it
+    /// has already expired
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.not_after.duration_since(SystemTime::now()).ok()
+    }
+
+    /// Returns whether the certificate was signed by its own key
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer && self.verify_signature(self).is_ok()
+    }
+
+    /// Checks that the certificate was signed by the key of `issuer`
+    pub fn verify_signature(&self, issuer: &Certificate) -> Result<(), ClientError> {
+        let cert = parse_certificate(&self.der)?;
+        let issuer = parse_certificate(&issuer.der)?;
+        cert.verify_signature(Some(issuer.public_key()))
+            .map_err(|_| certificate_error("the signature doesn't match the issuer's key"))
+    }
+}
+
+/// A certificate returned by the PKI engine together with its CA chain
+#[derive(Clone, Debug)]
+pub struct ParsedCertificate {
+    pub certificate: Certificate,
+    /// The CA chain, starting with the issuer of the certificate
+    pub chain: Vec<Certificate>,
+}
+
+impl ParsedCertificate {
+    /// Parses a PEM encoded certificate and the PEM encoded certificates of
+    /// its CA chain
+    pub fn from_pem(certificate: &str, chain: &[String]) -> Result<Self, ClientError> {
+        let mut parsed = Vec::new();
+        for ca in chain {
+            parsed.extend(parse_pem_chain(ca)?);
+        }
+        Ok(ParsedCertificate {
+            certificate: Certificate::from_pem(certificate)?,
+            chain: parsed,
+        })
+    }
+
+    /// Validates the chain at the current time
+    ///
+    /// See [ParsedCertificate::verify_chain_at]
+    pub fn verify_chain(&self) -> Result<(), ClientError> {
+        self.verify_chain_at(SystemTime::now())
+    }
+
+    /// Validates the chain at the given time
+    ///
+    /// Every certificate must be valid at `time` and signed by the next one
+    /// in the chain, which must be a CA allowed to sign certificates and
+    /// within its path length constraint. The last certificate of the chain
+    /// is treated as the trust anchor and only checked for its signature if
+    /// it's self-signed.
+    pub fn verify_chain_at(&self, time: SystemTime) -> Result<(), ClientError> {
+        let certs: Vec<&Certificate> = std::iter::once(&self.certificate)
+            .chain(self.chain.iter())
+            .collect();
+        for (depth, cert) in certs.iter().enumerate() {
+            if !cert.is_valid_at(time) {
+                return Err(chain_error(depth, cert, "isn't valid at the given time"));
+            }
+
+            let issuer = match certs.get(depth + 1) {
+                Some(issuer) => issuer,
+                None if cert.subject == cert.issuer => {
+                    cert.verify_signature(cert)
+                        .map_err(|_| chain_error(depth, cert, "has an invalid self-signature"))?;
+                    break;
+                }
+                None => break,
+            };
+            if cert.issuer != issuer.subject {
+                return Err(chain_error(
+                    depth,
+                    cert,
+                    &format!("wasn't issued by {}", issuer.subject),
+                ));
+            }
+            if !issuer.is_ca
+                || (!issuer.key_usage.is_empty()
+                    && !issuer.key_usage.contains(&KeyUsage::KeyCertSign))
+            {
+                return Err(chain_error(
+                    depth + 1,
+                    issuer,
+                    "isn't allowed to sign certificates",
+                ));
+            }
+            // Self-issued certificates don't count towards the path length
+            let intermediates = certs[1..=depth]
+                .iter()
+                .filter(|c| c.subject != c.issuer)
+                .count() as u32;
+            if let Some(limit) = issuer.path_length {
+                if intermediates > limit {
+                    return Err(chain_error(
+                        depth + 1,
+                        issuer,
+                        "exceeds its path length constraint",
+                    ));
+                }
+            }
+            cert.verify_signature(issuer)
+                .map_err(|e| chain_error(depth, cert, &e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl GenerateCertificateResponse {
+    /// Parses the certificate and its CA chain
+    pub fn parsed(&self) -> Result<ParsedCertificate, ClientError> {
+        ParsedCertificate::from_pem(
+            &self.certificate,
+            ca_chain(self.ca_chain.as_ref(), &self.issuing_ca),
+        )
+    }
+}
+
+impl ReadCertificateResponse {
+    /// Parses the certificate
+    ///
+    /// The response doesn't contain the CA chain, so the chain of the result
+    /// is empty.
+    pub fn parsed(&self) -> Result<ParsedCertificate, ClientError> {
+        ParsedCertificate::from_pem(&self.certificate, &[])
+    }
+}
+
+impl SignCertificateResponse {
+    /// Parses the certificate and its CA chain
+    pub fn parsed(&self) -> Result<ParsedCertificate, ClientError> {
+        ParsedCertificate::from_pem(
+            &self.certificate,
+            ca_chain(self.ca_chain.as_ref(), &self.issuing_ca),
+        )
+    }
+}
+
+impl SignIntermediateResponse {
+    /// Parses the certificate and its CA chain
+    pub fn parsed(&self) -> Result<ParsedCertificate, ClientError> {
+        ParsedCertificate::from_pem(
+            &self.certificate,
+            ca_chain(self.ca_chain.as_ref(), &self.issuing_ca),
+        )
+    }
+}
+
+/// Parses every certificate of a PEM encoded bundle
+pub fn parse_pem_chain(pem: &str) -> Result<Vec<Certificate>, ClientError> {
+    let mut certs = Vec::new();
+    for block in Pem::iter_from_buffer(pem.as_bytes()) {
+        let block = block.map_err(|e| certificate_error(&format!("invalid PEM: {}", e)))?;
+        if block.label == "CERTIFICATE" {
+            certs.push(Certificate::from_der(&block.contents)?);
+        }
+    }
+    Ok(certs)
+}
+
+fn ca_chain<'a>(chain: Option<&'a Vec<String>>, issuing_ca: &'a String) -> &'a [String] {
+    match chain {
+        Some(chain) if !chain.is_empty() => chain,
+        _ => std::slice::from_ref(issuing_ca),
+    }
+}
+
+fn strings<'a, 'b: 'a>(values: impl Iterator<Item = &'a AttributeTypeAndValue<'b>>) -> Vec<String> {
+    values
+        .filter_map(|v| v.as_str().ok().map(|s| s.to_string()))
+        .collect()
+}
+
+fn ip_address(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes).ok().map(IpAddr::from),
+        16 => <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from),
+        _ => None,
+    }
+}
+
+fn chain_error(depth: usize, cert: &Certificate, reason: &str) -> ClientError {
+    certificate_error(&format!(
+        "certificate {} ({}) {}",
+        depth, cert.subject, reason
+    ))
+}
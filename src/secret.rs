@@ -0,0 +1,153 @@
+//! Typed loading of secrets spread across one or more KV engines.
+//!
+//! The [VaultSecret] trait describes a type which can be loaded from Vault in
+//! a single call. It's usually implemented with the derive macro available
+//! through the `derive` feature:
+//!
+//! ```ignore
+//! use vaultrs::secret::VaultSecret;
+//!
+//! #[derive(VaultSecret)]
+//! #[vault(mount = "secret", path = "app/db")]
+//! struct DbConfig {
+//!     user: String,
+//!     #[vault(key = "password")]
+//!     pass: String,
+//!     #[vault(path = "app/features")]
+//!     pool_size: Option<u32>,
+//! }
+//!
+//! let config = DbConfig::load(&client).await?;
+//! ```
+//!
+//! The derived loader reads every referenced secret once using [kv1::get] or
+//! [kv2::read] and then extracts the individual keys using [Secrets]. A secret
+//! which doesn't exist only fails the load when a required field reads it.
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{client::Client, error::ClientError, kv1, kv2};
+
+#[cfg(feature = "derive")]
+pub use vaultrs_derive::VaultSecret;
+
+/// The future returned by [VaultSecret::load]
+pub type LoadFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, ClientError>> + Send + 'a>>;
+
+/// A type which can be loaded from one or more secrets stored in Vault
+pub trait VaultSecret: Sized {
+    /// Reads all secrets referenced by this type and builds it from them
+    fn load<'a, C: Client>(client: &'a C) -> LoadFuture<'a, Self>;
+}
+
+/// The version of the KV engine a secret is stored in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KvVersion {
+    V1,
+    V2,
+}
+
+/// The location of a secret within a KV engine
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SecretLocation {
+    pub version: KvVersion,
+    pub mount: String,
+    pub path: String,
+}
+
+impl SecretLocation {
+    pub fn new(version: KvVersion, mount: &str, path: &str) -> Self {
+        SecretLocation {
+            version,
+            mount: mount.to_string(),
+            path: path.to_string(),
+        }
+    }
+}
+
+/// A set of secrets read from Vault from which individual keys can be taken
+#[derive(Debug, Default)]
+pub struct Secrets {
+    docs: HashMap<SecretLocation, Map<String, Value>>,
+}
+
+impl Secrets {
+    /// Reads the secrets at the given locations. Each distinct location is
+    /// only read once.
+    ///
+    /// Secrets at the `optional` locations which don't exist are skipped, so
+    /// their keys are treated as missing, unless the location is also listed
+    /// in `locations`.
+    #[instrument(skip(client), err)]
+    pub async fn fetch(
+        client: &impl Client,
+        locations: Vec<SecretLocation>,
+        optional: Vec<SecretLocation>,
+    ) -> Result<Self, ClientError> {
+        let mut unique = locations;
+        unique.sort();
+        unique.dedup();
+        let mut optional = optional;
+        optional.sort();
+        optional.dedup();
+        optional.retain(|loc| unique.binary_search(loc).is_err());
+
+        let locations = unique
+            .into_iter()
+            .map(|loc| (loc, false))
+            .chain(optional.into_iter().map(|loc| (loc, true)));
+        let mut docs = HashMap::new();
+        for (loc, optional) in locations {
+            let res = match loc.version {
+                KvVersion::V1 => kv1::get(client, &loc.mount, &loc.path).await,
+                KvVersion::V2 => kv2::read(client, &loc.mount, &loc.path).await,
+            };
+            match res {
+                Ok(doc) => {
+                    docs.insert(loc, doc);
+                }
+                Err(ClientError::APIError { code: 404, .. }) if optional => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Secrets { docs })
+    }
+
+    /// Deserializes the value of the key in the secret at the given location,
+    /// returning a [ClientError::SecretKeyMissingError] if it's not present
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        location: &SecretLocation,
+        key: &str,
+    ) -> Result<T, ClientError> {
+        self.get_optional(location, key)?
+            .ok_or_else(|| ClientError::SecretKeyMissingError {
+                mount: location.mount.clone(),
+                path: location.path.clone(),
+                key: key.to_string(),
+            })
+    }
+
+    /// Deserializes the value of the key in the secret at the given location,
+    /// returning `None` if it's not present
+    pub fn get_optional<T: DeserializeOwned>(
+        &self,
+        location: &SecretLocation,
+        key: &str,
+    ) -> Result<Option<T>, ClientError> {
+        let value = self.docs.get(location).and_then(|doc| doc.get(key));
+        match value {
+            Some(v) => serde_json::value::from_value(v.clone())
+                .map(Some)
+                .map_err(|e| ClientError::SecretKeyParseError {
+                    source: e,
+                    mount: location.mount.clone(),
+                    path: location.path.clone(),
+                    key: key.to_string(),
+                }),
+            None => Ok(None),
+        }
+    }
+}
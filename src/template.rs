@@ -0,0 +1,453 @@
+//! Renders files from secrets stored in KV engines.
+//!
+//! Templates are plain text containing `{{ secret "<path>" "<key>" }}` tags
+//! which are replaced with the value of the key in the referenced secret:
+//!
+//! ```text
+//! [database]
+//! user = "{{ secret "secret/data/app/db" "user" }}"
+//! password = "{{ secret "secret/data/app/db" "password" }}"
+//! api_key = "{{ secret "kv/legacy/app" "api_key" }}"
+//! ```
+//!
+//! A path is read from a KV v2 engine mounted before its `/data/` segment,
+//! like the API path of the secret, and otherwise from a KV v1 engine mounted
+//! at its first segment. The `kv1` and `kv2` functions take the path without
+//! the `data/` segment and don't guess the engine version:
+//!
+//! ```text
+//! user = "{{ kv2 "secret/app/db" "user" }}"
+//! api_key = "{{ kv1 "kv/legacy/app" "api_key" }}"
+//! ```
+//!
+//! Every function also accepts the mount as a separate first argument, which
+//! is needed for mounts with more than one segment:
+//!
+//! ```text
+//! token = "{{ secret "team/secret" "data/app" "token" }}"
+//! token = "{{ kv2 "team/secret" "app" "token" }}"
+//! ```
+//!
+//! Every referenced secret is read once per render.
+//!
+//! Whole secrets can also be exported as dotenv, JSON or shell `export`
+//! lines with [export]. With the `fs` feature, files are written atomically
+//! with [write_atomic] so readers never observe a partially written file.
+use std::str::FromStr;
+#[cfg(feature = "fs")]
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    secret::{KvVersion, SecretLocation, Secrets},
+};
+
+/// A parsed template which can be rendered multiple times
+#[derive(Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Secret {
+        location: SecretLocation,
+        key: String,
+    },
+}
+
+impl Template {
+    /// Parses the given template source
+    pub fn parse(source: &str) -> Result<Self, ClientError> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| template_error(source, rest, start, "unclosed tag"))?;
+            let tag = &rest[start + 2..start + end];
+            let (location, key) =
+                parse_tag(tag).map_err(|reason| template_error(source, rest, start, &reason))?;
+            parts.push(Part::Secret { location, key });
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Returns the locations of the secrets referenced by this template
+    pub fn locations(&self) -> impl Iterator<Item = &SecretLocation> {
+        self.parts.iter().filter_map(|p| match p {
+            Part::Secret { location, .. } => Some(location),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Renders this template, reading every referenced secret once
+    #[instrument(skip(self, client), err)]
+    pub async fn render(&self, client: &impl Client) -> Result<String, ClientError> {
+        let locations = self.locations().cloned().collect();
+        let secrets = Secrets::fetch(client, locations, vec![]).await?;
+
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Secret { location, key } => {
+                    out.push_str(&value_to_string(&secrets.get(location, key)?))
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl FromStr for Template {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::parse(s)
+    }
+}
+
+/// The formats a secret can be exported in with [export]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `KEY="value"` lines as read by most dotenv implementations
+    Dotenv,
+    /// A JSON object
+    Json,
+    /// `export KEY='value'` lines which can be sourced by POSIX shells
+    Shell,
+}
+
+/// Options for writing files with [write_atomic]
+#[cfg(feature = "fs")]
+#[derive(Clone, Debug)]
+pub struct WriteOptions {
+    /// The permissions of the written file, only used on Unix platforms
+    pub mode: u32,
+}
+
+#[cfg(feature = "fs")]
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { mode: 0o600 }
+    }
+}
+
+/// Renders the given template source
+///
+/// See [Template]
+pub async fn render(client: &impl Client, source: &str) -> Result<String, ClientError> {
+    Template::parse(source)?.render(client).await
+}
+
+/// Renders the template at `source` and atomically writes the result to
+/// `dest`
+///
+/// See [Template]
+#[cfg(feature = "fs")]
+#[instrument(skip(client, opts), err)]
+pub async fn render_file(
+    client: &impl Client,
+    source: &str,
+    dest: &str,
+    opts: &WriteOptions,
+) -> Result<(), ClientError> {
+    let template =
+        tokio::fs::read_to_string(source)
+            .await
+            .map_err(|e| ClientError::FileReadError {
+                source: e,
+                path: source.to_string(),
+            })?;
+    let rendered = render(client, &template).await?;
+    write_atomic_blocking(dest, rendered.into_bytes(), opts).await
+}
+
+/// Exports all keys of the secret at the given location in the given format
+#[instrument(skip(client), err)]
+pub async fn export(
+    client: &impl Client,
+    location: &SecretLocation,
+    format: ExportFormat,
+) -> Result<String, ClientError> {
+    let data: Map<String, Value> = match location.version {
+        KvVersion::V1 => crate::kv1::get(client, &location.mount, &location.path).await?,
+        KvVersion::V2 => crate::kv2::read(client, &location.mount, &location.path).await?,
+    };
+    format_secret(&data, format)
+}
+
+/// Exports all keys of the secret at the given location in the given format
+/// and atomically writes the result to `dest`
+#[cfg(feature = "fs")]
+#[instrument(skip(client, opts), err)]
+pub async fn export_file(
+    client: &impl Client,
+    location: &SecretLocation,
+    format: ExportFormat,
+    dest: &str,
+    opts: &WriteOptions,
+) -> Result<(), ClientError> {
+    let exported = export(client, location, format).await?;
+    write_atomic_blocking(dest, exported.into_bytes(), opts).await
+}
+
+/// Formats the keys of a secret in the given format
+pub fn format_secret(
+    data: &Map<String, Value>,
+    format: ExportFormat,
+) -> Result<String, ClientError> {
+    match format {
+        ExportFormat::Dotenv => env_lines(data, |key, value| {
+            format!("{}=\"{}\"\n", key, escape_dotenv(value))
+        }),
+        ExportFormat::Json => serde_json::to_string_pretty(data)
+            .map(|json| json + "\n")
+            .map_err(|e| ClientError::JsonParseError { source: e }),
+        ExportFormat::Shell => env_lines(data, |key, value| {
+            format!("export {}='{}'\n", key, value.replace('\'', "'\\''"))
+        }),
+    }
+}
+
+/// Formats every key of a secret as a line, requiring the keys to be valid
+/// environment variable names
+fn env_lines(
+    data: &Map<String, Value>,
+    line: impl Fn(&str, &str) -> String,
+) -> Result<String, ClientError> {
+    let mut out = String::new();
+    for (key, value) in data {
+        if !is_env_name(key) {
+            return Err(ClientError::InvalidEnvironmentKeyError { key: key.clone() });
+        }
+        out.push_str(&line(key, &value_to_string(value)));
+    }
+    Ok(out)
+}
+
+/// Writes `contents` to `dest` by writing a temporary file in the same
+/// directory and renaming it over the destination
+#[cfg(feature = "fs")]
+#[instrument(skip(contents), err)]
+pub fn write_atomic(dest: &str, contents: &[u8], opts: &WriteOptions) -> Result<(), ClientError> {
+    let path = Path::new(dest);
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(".{}.{:x}.tmp", name, rand::random::<u64>()));
+
+    let res = write_tmp(&tmp, contents, opts).and_then(|_| fs::rename(&tmp, path));
+    res.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        ClientError::FileWriteError {
+            source: e,
+            path: dest.to_string(),
+        }
+    })
+}
+
+/// Runs [write_atomic] on the blocking thread pool of the runtime
+#[cfg(feature = "fs")]
+pub(crate) async fn write_atomic_blocking(
+    dest: &str,
+    contents: Vec<u8>,
+    opts: &WriteOptions,
+) -> Result<(), ClientError> {
+    let (path, opts) = (dest.to_string(), opts.clone());
+    tokio::task::spawn_blocking(move || write_atomic(&path, &contents, &opts))
+        .await
+        .map_err(|e| ClientError::FileWriteError {
+            source: io::Error::other(e),
+            path: dest.to_string(),
+        })?
+}
+
+#[cfg(feature = "fs")]
+fn write_tmp(tmp: &Path, contents: &[u8], opts: &WriteOptions) -> io::Result<()> {
+    let mut open = OpenOptions::new();
+    open.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        open.mode(opts.mode);
+        let file = open.open(tmp)?;
+        // The mode passed on creation is subject to the umask
+        file.set_permissions(fs::Permissions::from_mode(opts.mode))?;
+        write_all_synced(file, contents)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = opts;
+        write_all_synced(open.open(tmp)?, contents)
+    }
+}
+
+#[cfg(feature = "fs")]
+fn write_all_synced(mut file: fs::File, contents: &[u8]) -> io::Result<()> {
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// The functions which can be used in tags
+#[derive(Clone, Copy)]
+enum Function {
+    Kv1,
+    Kv2,
+    /// Detects the engine version from the `data` segment of the path
+    Secret,
+}
+
+/// Parses the contents of a `{{ <function> ["mount"] "path" "key" }}` tag
+fn parse_tag(tag: &str) -> Result<(SecretLocation, String), String> {
+    let tag = tag.trim();
+    let (function, args) = [
+        ("kv1", Function::Kv1),
+        ("kv2", Function::Kv2),
+        ("secret", Function::Secret),
+    ]
+    .iter()
+    .find_map(|(name, function)| {
+        tag.strip_prefix(name)
+            .filter(|r| r.starts_with(char::is_whitespace))
+            .map(|args| (*function, args))
+    })
+    .ok_or_else(|| format!("unknown function in tag {{{{{}}}}}", tag))?;
+
+    let mut args = parse_strings(args)?;
+    let key = match args.len() {
+        2 | 3 => args.pop().unwrap(),
+        n => {
+            return Err(format!(
+                "expected an optional mount, a path and a key but got {} arguments",
+                n
+            ))
+        }
+    };
+    let path = args.pop().unwrap();
+    let location = match args.pop() {
+        Some(mount) => mount_location(function, &mount, &path)?,
+        None => path_location(function, &path)?,
+    };
+    Ok((location, key))
+}
+
+/// Converts a path which starts with the mount into a [SecretLocation]
+fn path_location(function: Function, path: &str) -> Result<SecretLocation, String> {
+    let path = path.trim_matches('/');
+    let split = match function {
+        Function::Kv1 => path.split_once('/').map(|s| (KvVersion::V1, s)),
+        Function::Kv2 => path.split_once('/').map(|s| (KvVersion::V2, s)),
+        Function::Secret => path
+            .split_once("/data/")
+            .map(|s| (KvVersion::V2, s))
+            .or_else(|| path.split_once('/').map(|s| (KvVersion::V1, s))),
+    };
+    match split {
+        Some((version, (mount, secret))) if !mount.is_empty() && !secret.is_empty() => {
+            Ok(SecretLocation::new(version, mount, secret))
+        }
+        _ => Err(format!("path {} must contain a mount and a secret", path)),
+    }
+}
+
+/// Converts an explicit mount and the path of a secret below it into a
+/// [SecretLocation]
+fn mount_location(function: Function, mount: &str, path: &str) -> Result<SecretLocation, String> {
+    let (mount, path) = (mount.trim_matches('/'), path.trim_matches('/'));
+    let (version, path) = match function {
+        Function::Kv1 => (KvVersion::V1, path),
+        Function::Kv2 => (KvVersion::V2, path),
+        Function::Secret => match path.strip_prefix("data/") {
+            Some(secret) => (KvVersion::V2, secret),
+            None => (KvVersion::V1, path),
+        },
+    };
+    if mount.is_empty() || path.is_empty() {
+        return Err(format!(
+            "mount {} and path {} must not be empty",
+            mount, path
+        ));
+    }
+    Ok(SecretLocation::new(version, mount, path))
+}
+
+/// Parses a whitespace separated list of double quoted strings
+fn parse_strings(input: &str) -> Result<Vec<String>, String> {
+    let mut strings = Vec::new();
+    let mut chars = input.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c != '"' {
+            return Err(format!("expected a quoted string but found {}", c));
+        }
+        let mut s = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some(e) => s.push(e),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some(c) => s.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+        strings.push(s);
+    }
+    Ok(strings)
+}
+
+fn template_error(source: &str, rest: &str, offset: usize, reason: &str) -> ClientError {
+    let pos = source.len() - rest.len() + offset;
+    let line = source[..pos].matches('\n').count() + 1;
+    ClientError::TemplateError {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        v => v.to_string(),
+    }
+}
+
+fn escape_dotenv(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('$', "\\$")
+}
+
+fn is_env_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
@@ -1,14 +1,49 @@
+#[cfg(feature = "transit-crypto")]
+mod dek;
+pub mod encrypted;
+#[cfg(feature = "transit-crypto")]
+pub mod envelope;
+#[cfg(feature = "transit-crypto")]
+pub mod jwt;
+#[cfg(feature = "transit-crypto")]
+pub mod offline;
+pub mod policy;
+#[cfg(feature = "streams")]
+pub mod rewrap;
+#[cfg(all(feature = "transit-crypto", feature = "fs"))]
+pub mod stream;
+#[cfg(feature = "transit-crypto")]
+pub mod wrapping;
+
+/// The default number of items sent per request by the batch functions of
+/// [data] and [generate]
+pub const DEFAULT_BATCH_SIZE: usize = 250;
+
 pub mod key {
+    #[cfg(feature = "transit-crypto")]
+    use super::wrapping::{self, KeyMaterial};
     use crate::api::transit::{
         requests::{
             BackupKeyRequest, CreateKeyRequest, CreateKeyRequestBuilder, DeleteKeyRequest,
             ExportKeyRequest, ExportKeyType, ExportVersion, ListKeysRequest, ReadKeyRequest,
-            RestoreKeyRequest, RestoreKeyRequestBuilder, RotateKeyRequest, TrimKeyRequest,
-            UpdateKeyConfigurationRequest, UpdateKeyConfigurationRequestBuilder,
+            ReadWrappingKeyRequest, RestoreKeyRequest, RestoreKeyRequestBuilder, RotateKeyRequest,
+            TrimKeyRequest, UpdateKeyConfigurationRequest, UpdateKeyConfigurationRequestBuilder,
+        },
+        responses::{
+            BackupKeyResponse, ExportKeyResponse, ListKeysResponse, ReadKeyResponse,
+            ReadWrappingKeyResponse,
+        },
+    };
+    #[cfg(feature = "transit-crypto")]
+    use crate::api::transit::{
+        requests::{
+            ImportKeyRequest, ImportKeyRequestBuilder, ImportKeyVersionRequest,
+            ImportKeyVersionRequestBuilder,
         },
-        responses::{BackupKeyResponse, ExportKeyResponse, ListKeysResponse, ReadKeyResponse},
+        KeyType,
     };
     use crate::{api, client::Client, error::ClientError};
+    use std::time::Duration;
 
     /// Create a new encryption key.
     ///
@@ -76,6 +111,70 @@ pub mod key {
         api::exec_with_empty(client, endpoint).await
     }
 
+    /// Allow all valid versions of a key to be exported. This cannot be
+    /// disabled afterwards.
+    ///
+    /// See [UpdateKeyConfigurationRequest]
+    #[instrument(skip(client), err)]
+    pub async fn allow_export(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+    ) -> Result<(), ClientError> {
+        update(
+            client,
+            mount,
+            name,
+            Some(UpdateKeyConfigurationRequest::builder().exportable(true)),
+        )
+        .await
+    }
+
+    /// Allow plaintext backups of a key. This cannot be disabled afterwards.
+    ///
+    /// Backups additionally require the key to be exportable, see
+    /// [allow_export].
+    ///
+    /// See [UpdateKeyConfigurationRequest]
+    #[instrument(skip(client), err)]
+    pub async fn allow_plaintext_backup(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+    ) -> Result<(), ClientError> {
+        update(
+            client,
+            mount,
+            name,
+            Some(UpdateKeyConfigurationRequest::builder().allow_plaintext_backup(true)),
+        )
+        .await
+    }
+
+    /// Set the period at which a key is rotated automatically. A zero period
+    /// disables automatic rotation, otherwise it can't be shorter than one
+    /// hour.
+    ///
+    /// See [UpdateKeyConfigurationRequest]
+    #[instrument(skip(client), err)]
+    pub async fn set_auto_rotate_period(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        period: Duration,
+    ) -> Result<(), ClientError> {
+        update(
+            client,
+            mount,
+            name,
+            Some(
+                UpdateKeyConfigurationRequest::builder()
+                    .auto_rotate_period(format!("{}s", period.as_secs())),
+            ),
+        )
+        .await
+    }
+
     /// Delete a named encryption key.
     ///
     /// See [DeleteKeyRequest]
@@ -178,20 +277,107 @@ pub mod key {
             .unwrap();
         api::exec_with_empty(client, endpoint).await
     }
+
+    /// Read the RSA public key used to wrap keys for importing.
+    ///
+    /// See [ReadWrappingKeyRequest]
+    #[instrument(skip(client), err)]
+    pub async fn read_wrapping_key(
+        client: &impl Client,
+        mount: &str,
+    ) -> Result<ReadWrappingKeyResponse, ClientError> {
+        let endpoint = ReadWrappingKeyRequest::builder()
+            .mount(mount)
+            .build()
+            .unwrap();
+        api::exec_with_result(client, endpoint).await
+    }
+
+    /// Import externally generated key material as a new key.
+    ///
+    /// The key material is wrapped with the mount's wrapping key before it's
+    /// sent to Vault, see [wrapping].
+    ///
+    /// See [ImportKeyRequest]
+    #[cfg(feature = "transit-crypto")]
+    #[instrument(skip(client, key, opts), err)]
+    pub async fn import(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        key_type: KeyType,
+        key: &KeyMaterial,
+        opts: Option<&mut ImportKeyRequestBuilder>,
+    ) -> Result<(), ClientError> {
+        let key = key.to_import_bytes(key_type)?;
+        let wrapping_key = read_wrapping_key(client, mount).await?.public_key;
+        let ciphertext = wrapping::wrap(&wrapping_key, &key)?;
+
+        let mut builder = ImportKeyRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut builder)
+            .mount(mount)
+            .name(name)
+            .key_type(key_type)
+            .ciphertext(ciphertext)
+            .hash_function(wrapping::HASH_FUNCTION)
+            .build()
+            .unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
+
+    /// Import externally generated key material as a new version of an
+    /// existing imported key.
+    ///
+    /// The type of the key is read from Vault to validate the key material.
+    ///
+    /// See [ImportKeyVersionRequest]
+    #[cfg(feature = "transit-crypto")]
+    #[instrument(skip(client, key, opts), err)]
+    pub async fn import_version(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        key: &KeyMaterial,
+        opts: Option<&mut ImportKeyVersionRequestBuilder>,
+    ) -> Result<(), ClientError> {
+        let key_type = read(client, mount, name).await?.key_type;
+        let key = key.to_import_bytes(key_type)?;
+        let wrapping_key = read_wrapping_key(client, mount).await?.public_key;
+        let ciphertext = wrapping::wrap(&wrapping_key, &key)?;
+
+        let mut builder = ImportKeyVersionRequest::builder();
+        let endpoint = opts
+            .unwrap_or(&mut builder)
+            .mount(mount)
+            .name(name)
+            .ciphertext(ciphertext)
+            .hash_function(wrapping::HASH_FUNCTION)
+            .build()
+            .unwrap();
+        api::exec_with_empty(client, endpoint).await
+    }
 }
 
 pub mod data {
+    use super::batch;
     use crate::api::transit::{
         requests::{
-            DecryptDataRequest, DecryptDataRequestBuilder, EncryptDataRequest,
-            EncryptDataRequestBuilder, RewrapDataRequest, RewrapDataRequestBuilder,
-            SignDataRequest, SignDataRequestBuilder, VerifySignedDataRequest,
+            DecryptDataBatchInput, DecryptDataBatchRequest, DecryptDataBatchRequestBuilder,
+            DecryptDataRequest, DecryptDataRequestBuilder, EncryptDataBatchInput,
+            EncryptDataBatchRequest, EncryptDataBatchRequestBuilder, EncryptDataRequest,
+            EncryptDataRequestBuilder, RewrapDataBatchRequest, RewrapDataBatchRequestBuilder,
+            RewrapDataRequest, RewrapDataRequestBuilder, SignDataBatchInput, SignDataBatchRequest,
+            SignDataBatchRequestBuilder, SignDataRequest, SignDataRequestBuilder,
+            VerifySignedDataBatchInput, VerifySignedDataBatchRequest,
+            VerifySignedDataBatchRequestBuilder, VerifySignedDataRequest,
             VerifySignedDataRequestBuilder,
         },
         responses::{
             DecryptDataResponse, EncryptDataResponse, RewrapDataResponse, SignDataResponse,
             VerifySignedDataResponse,
         },
+        Ciphertext, Hmac, Signature,
     };
     use crate::{api, client::Client, error::ClientError};
 
@@ -225,7 +411,7 @@ pub mod data {
         client: &impl Client,
         mount: &str,
         name: &str,
-        ciphertext: &str,
+        ciphertext: &Ciphertext,
         opts: Option<&mut DecryptDataRequestBuilder>,
     ) -> Result<DecryptDataResponse, ClientError> {
         let mut builder = DecryptDataRequest::builder();
@@ -233,7 +419,7 @@ pub mod data {
             .unwrap_or(&mut builder)
             .mount(mount)
             .name(name)
-            .ciphertext(ciphertext)
+            .ciphertext(ciphertext.as_str())
             .build()
             .unwrap();
         api::exec_with_result(client, endpoint).await
@@ -248,7 +434,7 @@ pub mod data {
         client: &impl Client,
         mount: &str,
         name: &str,
-        ciphertext: &str,
+        ciphertext: &Ciphertext,
         opts: Option<&mut RewrapDataRequestBuilder>,
     ) -> Result<RewrapDataResponse, ClientError> {
         let mut builder = RewrapDataRequest::builder();
@@ -256,7 +442,7 @@ pub mod data {
             .unwrap_or(&mut builder)
             .mount(mount)
             .name(name)
-            .ciphertext(ciphertext)
+            .ciphertext(ciphertext.as_str())
             .build()
             .unwrap();
         api::exec_with_result(client, endpoint).await
@@ -284,10 +470,10 @@ pub mod data {
         api::exec_with_result(client, endpoint).await
     }
 
-    /// Return whether the provided signature is valid for the base64-encoded
-    /// input data.
+    /// Return whether the provided signature or HMAC is valid for the
+    /// base64-encoded input data.
     ///
-    /// See [SignDataRequest]
+    /// See [VerifySignedDataRequest]
     #[instrument(skip(client, opts), err)]
     pub async fn verify(
         client: &impl Client,
@@ -306,15 +492,162 @@ pub mod data {
             .unwrap();
         api::exec_with_result(client, endpoint).await
     }
+
+    /// Return whether the provided signature is valid for the base64-encoded
+    /// input data.
+    ///
+    /// See [verify]
+    #[instrument(skip(client, opts), err)]
+    pub async fn verify_signature(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        input: &str,
+        signature: &Signature,
+        opts: Option<&mut VerifySignedDataRequestBuilder>,
+    ) -> Result<bool, ClientError> {
+        let mut builder = VerifySignedDataRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).signature(signature.as_str());
+        Ok(verify(client, mount, name, input, Some(builder))
+            .await?
+            .valid)
+    }
+
+    /// Return whether the provided HMAC is valid for the base64-encoded input
+    /// data.
+    ///
+    /// See [verify] and [generate::hmac][super::generate::hmac]
+    #[instrument(skip(client, opts), err)]
+    pub async fn verify_hmac(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        input: &str,
+        hmac: &Hmac,
+        opts: Option<&mut VerifySignedDataRequestBuilder>,
+    ) -> Result<bool, ClientError> {
+        let mut builder = VerifySignedDataRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).hmac(hmac.as_str());
+        Ok(verify(client, mount, name, input, Some(builder))
+            .await?
+            .valid)
+    }
+
+    /// Encrypt a list of base64-encoded plaintexts using the named key.
+    ///
+    /// The items are sent in requests of at most `batch_size` items. The
+    /// results are returned in the same order as the items, an item which
+    /// failed returns a [ClientError::BatchItemError].
+    ///
+    /// See [EncryptDataBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn encrypt_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[EncryptDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut EncryptDataBatchRequestBuilder>,
+    ) -> Result<Vec<Result<EncryptDataResponse, ClientError>>, ClientError> {
+        let mut builder = EncryptDataBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
+
+    /// Decrypt a list of ciphertexts using the named key.
+    ///
+    /// See [encrypt_batch] and [DecryptDataBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn decrypt_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[DecryptDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut DecryptDataBatchRequestBuilder>,
+    ) -> Result<Vec<Result<DecryptDataResponse, ClientError>>, ClientError> {
+        let mut builder = DecryptDataBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
+
+    /// Rewrap a list of ciphertexts using the latest version of the named key.
+    ///
+    /// See [encrypt_batch] and [RewrapDataBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn rewrap_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[DecryptDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut RewrapDataBatchRequestBuilder>,
+    ) -> Result<Vec<Result<RewrapDataResponse, ClientError>>, ClientError> {
+        let mut builder = RewrapDataBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
+
+    /// Return the cryptographic signatures of a list of base64-encoded inputs.
+    ///
+    /// See [encrypt_batch] and [SignDataBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn sign_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[SignDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut SignDataBatchRequestBuilder>,
+    ) -> Result<Vec<Result<SignDataResponse, ClientError>>, ClientError> {
+        let mut builder = SignDataBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
+
+    /// Return whether the provided signatures are valid for a list of
+    /// base64-encoded inputs.
+    ///
+    /// See [encrypt_batch] and [VerifySignedDataBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn verify_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[VerifySignedDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut VerifySignedDataBatchRequestBuilder>,
+    ) -> Result<Vec<Result<VerifySignedDataResponse, ClientError>>, ClientError> {
+        let mut builder = VerifySignedDataBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
 }
 
 pub mod generate {
+    use super::batch;
     use crate::api::transit::{
         requests::{
             DataKeyType, GenerateDataKeyRequest, GenerateDataKeyRequestBuilder,
-            GenerateHmacRequest, GenerateHmacRequestBuilder, GenerateRandomBytesRequest,
+            GenerateHmacBatchRequest, GenerateHmacBatchRequestBuilder, GenerateHmacRequest,
+            GenerateHmacRequestBuilder, GenerateRandomBytesRequest,
             GenerateRandomBytesRequestBuilder, HashDataRequest, HashDataRequestBuilder,
-            RandomBytesSource,
+            RandomBytesSource, SignDataBatchInput,
         },
         responses::{
             GenerateDataKeyResponse, GenerateHmacResponse, GenerateRandomBytesResponse,
@@ -410,6 +743,27 @@ pub mod generate {
             .unwrap();
         api::exec_with_result(client, endpoint).await
     }
+
+    /// Return the digests of a list of base64-encoded inputs.
+    ///
+    /// See [data::encrypt_batch][super::data::encrypt_batch] and
+    /// [GenerateHmacBatchRequest]
+    #[instrument(skip(client, items, opts), err)]
+    pub async fn hmac_batch(
+        client: &impl Client,
+        mount: &str,
+        name: &str,
+        items: &[SignDataBatchInput],
+        batch_size: usize,
+        opts: Option<&mut GenerateHmacBatchRequestBuilder>,
+    ) -> Result<Vec<Result<GenerateHmacResponse, ClientError>>, ClientError> {
+        let mut builder = GenerateHmacBatchRequest::builder();
+        let builder = opts.unwrap_or(&mut builder).mount(mount).name(name);
+        batch::exec(client, items, batch_size, |chunk| {
+            builder.batch_input(chunk).build().unwrap()
+        })
+        .await
+    }
 }
 
 pub mod cache {
@@ -451,3 +805,70 @@ pub mod cache {
         api::exec_with_empty(client, endpoint).await
     }
 }
+
+mod batch {
+    use rustify::{endpoint::Endpoint, errors::ClientError as RestClientError};
+    use serde::de::DeserializeOwned;
+
+    use crate::api::{transit::responses::BatchResponse, EndpointResult};
+    use crate::{api, client::Client, error::ClientError};
+
+    /// Executes the endpoint returned by `endpoint` for every chunk of at most
+    /// `batch_size` items and collects the results of all items in order
+    pub async fn exec<I, T, E, F>(
+        client: &impl Client,
+        items: &[I],
+        batch_size: usize,
+        mut endpoint: F,
+    ) -> Result<Vec<Result<T, ClientError>>, ClientError>
+    where
+        I: Clone,
+        T: DeserializeOwned + Send + Sync,
+        E: Endpoint<Response = BatchResponse<T>>,
+        F: FnMut(Vec<I>) -> E,
+    {
+        let mut results = Vec::with_capacity(items.len());
+        for chunk in items.chunks(batch_size.max(1)) {
+            let resp = match api::exec_with_result(client, endpoint(chunk.to_vec())).await {
+                Ok(resp) => resp,
+                Err(e) => failed_batch(e)?,
+            };
+            for item in resp.batch_results {
+                let index = results.len();
+                results.push(match (item.error, item.data) {
+                    (Some(message), _) if !message.is_empty() => Err(ClientError::BatchItemError {
+                        index,
+                        reference: item.reference,
+                        message,
+                    }),
+                    (_, Some(data)) => Ok(data),
+                    (_, None) => Err(ClientError::ResponseDataEmptyError),
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    /// Vault responds with a 400 when every item of a batch failed but still
+    /// includes the results of the items in the response body
+    fn failed_batch<T: DeserializeOwned>(
+        error: ClientError,
+    ) -> Result<BatchResponse<T>, ClientError> {
+        if let ClientError::RestClientError {
+            source:
+                RestClientError::ServerResponseError {
+                    code: 400,
+                    content: Some(content),
+                },
+        } = &error
+        {
+            if let Ok(EndpointResult {
+                data: Some(data), ..
+            }) = serde_json::from_str::<EndpointResult<BatchResponse<T>>>(content)
+            {
+                return Ok(data);
+            }
+        }
+        Err(error)
+    }
+}
@@ -0,0 +1,147 @@
+//! Data keys (DEKs) generated by a transit engine and the header fields
+//! shared by [envelope][crate::transit::envelope] and
+//! [stream][crate::transit::stream].
+use std::convert::{TryFrom, TryInto};
+
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::{
+    api::transit::{
+        requests::{DataKeyType, DecryptDataRequest, GenerateDataKeyRequest},
+        Ciphertext,
+    },
+    client::Client,
+    error::ClientError,
+    transit::{data, generate},
+};
+
+/// The size of a DEK in bytes
+pub(crate) const KEY_SIZE: usize = 32;
+
+/// Builds the error returned when a DEK or a header is invalid
+pub(crate) type ErrorFn = fn(&str) -> ClientError;
+
+/// A plaintext DEK along with the DEK wrapped by a transit key
+pub(crate) struct DataKey {
+    pub key: [u8; KEY_SIZE],
+    /// The DEK as returned by Vault, e.g. `vault:v1:...`
+    pub wrapped: String,
+    /// The version of the transit key which wrapped the DEK
+    pub version: u64,
+}
+
+/// Generates a new 256-bit DEK wrapped by the named transit key
+pub(crate) async fn generate(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    context: Option<&str>,
+    error: ErrorFn,
+) -> Result<DataKey, ClientError> {
+    let mut opts = GenerateDataKeyRequest::builder();
+    opts.bits(256u16);
+    if let Some(context) = context {
+        opts.context(context);
+    }
+    let resp =
+        generate::data_key(client, mount, name, DataKeyType::Plaintext, Some(&mut opts)).await?;
+    let plaintext = resp
+        .plaintext
+        .ok_or_else(|| error("Vault did not return the plaintext data key"))?;
+    Ok(DataKey {
+        key: decode_key(&plaintext, error)?,
+        version: resp.ciphertext.version(),
+        wrapped: resp.ciphertext.into(),
+    })
+}
+
+/// Unwraps a DEK with the named transit key
+pub(crate) async fn unwrap(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    wrapped: &str,
+    context: Option<&str>,
+    error: ErrorFn,
+) -> Result<[u8; KEY_SIZE], ClientError> {
+    let mut opts = DecryptDataRequest::builder();
+    if let Some(context) = context {
+        opts.context(context);
+    }
+    let wrapped = Ciphertext::parse(wrapped).map_err(|_| error("invalid wrapped data key"))?;
+    let resp = data::decrypt(client, mount, name, &wrapped, Some(&mut opts)).await?;
+    decode_key(&resp.plaintext, error)
+}
+
+/// Appends the key name, key version and wrapped DEK, each string prefixed
+/// with its 2 byte big-endian length
+///
+/// Fails if a field doesn't fit its encoded size.
+pub(crate) fn write_key_fields(
+    out: &mut Vec<u8>,
+    key: &str,
+    version: u64,
+    wrapped: &str,
+    error: ErrorFn,
+) -> Result<(), ClientError> {
+    let version = u32::try_from(version).map_err(|_| error("the key version exceeds 32 bits"))?;
+    write_string(out, key, error)?;
+    out.extend_from_slice(&version.to_be_bytes());
+    write_string(out, wrapped, error)
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str, error: ErrorFn) -> Result<(), ClientError> {
+    let len = u16::try_from(s.len())
+        .map_err(|_| error(&format!("a field is longer than {} bytes", u16::MAX)))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+/// Reads the fields of a serialized header
+pub(crate) struct HeaderReader<'a> {
+    bytes: &'a [u8],
+    error: ErrorFn,
+}
+
+impl<'a> HeaderReader<'a> {
+    pub fn new(bytes: &'a [u8], error: ErrorFn) -> Self {
+        HeaderReader { bytes, error }
+    }
+
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], ClientError> {
+        if self.bytes.len() < n {
+            return Err((self.error)("the header is truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Reads the fields written by [write_key_fields]
+    pub fn key_fields(&mut self) -> Result<(String, u64, String), ClientError> {
+        let key = self.string()?;
+        let version = u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64;
+        let wrapped = self.string()?;
+        Ok((key, version, wrapped))
+    }
+
+    /// Returns the bytes which haven't been read
+    pub fn rest(self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn string(&mut self) -> Result<String, ClientError> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| (self.error)("the header contains invalid UTF-8"))
+    }
+}
+
+fn decode_key(plaintext: &str, error: ErrorFn) -> Result<[u8; KEY_SIZE], ClientError> {
+    general_purpose::STANDARD
+        .decode(plaintext)
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| error("data key is not a base64 encoded 256-bit key"))
+}
@@ -0,0 +1,364 @@
+//! Transparent encryption of struct fields with transit keys.
+//!
+//! Fields wrapped in [Encrypted] serialize to their transit ciphertext, so a
+//! struct can be persisted with the usual serde machinery without its
+//! sensitive fields ever leaving the process in plaintext. Since serde is
+//! synchronous, the fields are encrypted with an async [seal] pass before
+//! serializing and decrypted with an [unseal] pass after deserializing. Each
+//! pass sends all fields of a value which use the same key in a single batch
+//! request:
+//!
+//! ```ignore
+//! use serde::{Deserialize, Serialize};
+//! use vaultrs::transit::encrypted::{self, Encrypted, EncryptedField, Sealable, TransitKey};
+//!
+//! struct UserKey;
+//! impl TransitKey for UserKey {
+//!     const MOUNT: &'static str = "transit";
+//!     const NAME: &'static str = "users";
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     name: String,
+//!     ssn: Encrypted<String, UserKey>,
+//! }
+//!
+//! impl Sealable for User {
+//!     fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+//!         vec![&mut self.ssn]
+//!     }
+//! }
+//!
+//! let mut user = User { name: "bob".into(), ssn: Encrypted::new("123-45-6789".into()) };
+//! encrypted::seal(&client, &mut user).await?;
+//! let json = serde_json::to_string(&user)?;
+//!
+//! let mut user: User = serde_json::from_str(&json)?;
+//! encrypted::unseal(&client, &mut user).await?;
+//! assert_eq!(user.ssn.get().unwrap(), "123-45-6789");
+//! ```
+//!
+//! Fields encrypted with a derived key must be given the same context with
+//! [Encrypted::set_context] before both passes.
+use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{
+    de::DeserializeOwned, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    api::transit::requests::{DecryptDataBatchInput, EncryptDataBatchInput},
+    client::Client,
+    error::ClientError,
+    transit::{data, DEFAULT_BATCH_SIZE},
+};
+
+/// The transit key used to encrypt the fields of an [Encrypted] type
+pub trait TransitKey {
+    /// The mount of the transit engine
+    const MOUNT: &'static str;
+    /// The name of the key
+    const NAME: &'static str;
+}
+
+/// A field which is stored as a transit ciphertext
+///
+/// The value is serialized as JSON and encrypted by [seal]. Serializing an
+/// [Encrypted] which hasn't been sealed since it was last modified fails.
+/// Deserializing only reads the ciphertext, the value is available after
+/// calling [unseal].
+pub struct Encrypted<T, K> {
+    value: Option<T>,
+    ciphertext: Option<String>,
+    context: Option<Vec<u8>>,
+    key: PhantomData<K>,
+}
+
+impl<T, K> Encrypted<T, K> {
+    /// Wraps a plaintext value
+    pub fn new(value: T) -> Self {
+        Encrypted {
+            value: Some(value),
+            ciphertext: None,
+            context: None,
+            key: PhantomData,
+        }
+    }
+
+    /// Wraps a ciphertext produced by the transit key
+    pub fn from_ciphertext(ciphertext: String) -> Self {
+        Encrypted {
+            value: None,
+            ciphertext: Some(ciphertext),
+            context: None,
+            key: PhantomData,
+        }
+    }
+
+    /// Sets the context used for key derivation
+    pub fn with_context(mut self, context: impl Into<Vec<u8>>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Sets the context used for key derivation
+    ///
+    /// The context isn't serialized and must be set again before unsealing
+    /// a deserialized value.
+    pub fn set_context(&mut self, context: impl Into<Vec<u8>>) {
+        self.context = Some(context.into());
+    }
+
+    /// Returns the plaintext value if it's known
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Returns the plaintext value for modification
+    ///
+    /// This discards the ciphertext, so the field has to be sealed again
+    /// before it can be serialized.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.value.is_some() {
+            self.ciphertext = None;
+        }
+        self.value.as_mut()
+    }
+
+    /// Replaces the plaintext value, discarding the ciphertext
+    pub fn set(&mut self, value: T) {
+        self.value = Some(value);
+        self.ciphertext = None;
+    }
+
+    /// Returns the plaintext value if it's known
+    pub fn into_inner(self) -> Option<T> {
+        self.value
+    }
+
+    /// Returns the ciphertext if the field has been sealed
+    pub fn ciphertext(&self) -> Option<&str> {
+        self.ciphertext.as_deref()
+    }
+}
+
+impl<T: Clone, K> Clone for Encrypted<T, K> {
+    fn clone(&self) -> Self {
+        Encrypted {
+            value: self.value.clone(),
+            ciphertext: self.ciphertext.clone(),
+            context: self.context.clone(),
+            key: PhantomData,
+        }
+    }
+}
+
+impl<T, K: TransitKey> fmt::Debug for Encrypted<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encrypted")
+            .field("key", &K::NAME)
+            .field("ciphertext", &self.ciphertext)
+            .finish()
+    }
+}
+
+impl<T, K> Serialize for Encrypted<T, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.ciphertext {
+            Some(ciphertext) => serializer.serialize_str(ciphertext),
+            None => Err(S::Error::custom(
+                "encrypted field must be sealed before it's serialized",
+            )),
+        }
+    }
+}
+
+impl<'de, T, K> Deserialize<'de> for Encrypted<T, K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from_ciphertext)
+    }
+}
+
+/// A single field processed by [seal] and [unseal]
+///
+/// This is implemented by [Encrypted] and is object safe so fields using
+/// different value types and keys can be processed together.
+pub trait EncryptedField: Send {
+    /// The mount of the transit engine
+    fn mount(&self) -> &str;
+    /// The name of the key
+    fn key(&self) -> &str;
+    /// The context used for key derivation
+    fn context(&self) -> Option<&[u8]>;
+    /// Returns the serialized plaintext if the field needs to be sealed
+    fn plaintext(&self) -> Result<Option<Vec<u8>>, ClientError>;
+    /// Returns the ciphertext if the field needs to be unsealed
+    fn sealed(&self) -> Option<&str>;
+    fn set_ciphertext(&mut self, ciphertext: String);
+    fn set_plaintext(&mut self, plaintext: &[u8]) -> Result<(), ClientError>;
+}
+
+impl<T, K> EncryptedField for Encrypted<T, K>
+where
+    T: Serialize + DeserializeOwned + Send,
+    K: TransitKey + Send,
+{
+    fn mount(&self) -> &str {
+        K::MOUNT
+    }
+
+    fn key(&self) -> &str {
+        K::NAME
+    }
+
+    fn context(&self) -> Option<&[u8]> {
+        self.context.as_deref()
+    }
+
+    fn plaintext(&self) -> Result<Option<Vec<u8>>, ClientError> {
+        match (&self.value, &self.ciphertext) {
+            (Some(value), None) => serde_json::to_vec(value)
+                .map(Some)
+                .map_err(|e| ClientError::JsonParseError { source: e }),
+            _ => Ok(None),
+        }
+    }
+
+    fn sealed(&self) -> Option<&str> {
+        match self.value {
+            Some(_) => None,
+            None => self.ciphertext.as_deref(),
+        }
+    }
+
+    fn set_ciphertext(&mut self, ciphertext: String) {
+        self.ciphertext = Some(ciphertext);
+    }
+
+    fn set_plaintext(&mut self, plaintext: &[u8]) -> Result<(), ClientError> {
+        self.value = Some(
+            serde_json::from_slice(plaintext)
+                .map_err(|e| ClientError::JsonParseError { source: e })?,
+        );
+        Ok(())
+    }
+}
+
+/// A value containing [Encrypted] fields
+pub trait Sealable {
+    /// Returns every encrypted field of the value
+    fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField>;
+}
+
+impl<T, K> Sealable for Encrypted<T, K>
+where
+    T: Serialize + DeserializeOwned + Send,
+    K: TransitKey + Send,
+{
+    fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+        vec![self]
+    }
+}
+
+impl<S: Sealable> Sealable for Option<S> {
+    fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+        match self {
+            Some(value) => value.encrypted_fields(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl<S: Sealable> Sealable for Vec<S> {
+    fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+        self.iter_mut().flat_map(|v| v.encrypted_fields()).collect()
+    }
+}
+
+impl<S: Sealable> Sealable for [S] {
+    fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+        self.iter_mut().flat_map(|v| v.encrypted_fields()).collect()
+    }
+}
+
+/// Encrypts every field of the value which was modified since it was last
+/// sealed
+///
+/// One batch request is sent per transit key. Fails with the first error
+/// returned for any field, in which case fields encrypted with other keys
+/// may already have been sealed.
+#[instrument(skip(client, value), err)]
+pub async fn seal<S: Sealable + ?Sized>(
+    client: &impl Client,
+    value: &mut S,
+) -> Result<(), ClientError> {
+    let mut fields = value.encrypted_fields();
+    let mut groups: BTreeMap<(String, String), Vec<(usize, EncryptDataBatchInput)>> =
+        BTreeMap::new();
+    for (i, field) in fields.iter().enumerate() {
+        if let Some(plaintext) = field.plaintext()? {
+            let item = EncryptDataBatchInput {
+                plaintext: general_purpose::STANDARD.encode(plaintext),
+                context: field.context().map(|c| general_purpose::STANDARD.encode(c)),
+                ..Default::default()
+            };
+            groups
+                .entry((field.mount().to_string(), field.key().to_string()))
+                .or_default()
+                .push((i, item));
+        }
+    }
+
+    for ((mount, key), group) in groups {
+        let (indices, items): (Vec<_>, Vec<_>) = group.into_iter().unzip();
+        let results =
+            data::encrypt_batch(client, &mount, &key, &items, DEFAULT_BATCH_SIZE, None).await?;
+        for (i, res) in indices.into_iter().zip(results) {
+            fields[i].set_ciphertext(res?.ciphertext.into());
+        }
+    }
+    Ok(())
+}
+
+/// Decrypts every field of the value whose plaintext isn't known
+///
+/// See [seal]
+#[instrument(skip(client, value), err)]
+pub async fn unseal<S: Sealable + ?Sized>(
+    client: &impl Client,
+    value: &mut S,
+) -> Result<(), ClientError> {
+    let mut fields = value.encrypted_fields();
+    let mut groups: BTreeMap<(String, String), Vec<(usize, DecryptDataBatchInput)>> =
+        BTreeMap::new();
+    for (i, field) in fields.iter().enumerate() {
+        if let Some(ciphertext) = field.sealed() {
+            let item = DecryptDataBatchInput {
+                ciphertext: ciphertext.to_string(),
+                context: field.context().map(|c| general_purpose::STANDARD.encode(c)),
+                ..Default::default()
+            };
+            groups
+                .entry((field.mount().to_string(), field.key().to_string()))
+                .or_default()
+                .push((i, item));
+        }
+    }
+
+    for ((mount, key), group) in groups {
+        let (indices, items): (Vec<_>, Vec<_>) = group.into_iter().unzip();
+        let results =
+            data::decrypt_batch(client, &mount, &key, &items, DEFAULT_BATCH_SIZE, None).await?;
+        for (i, res) in indices.into_iter().zip(results) {
+            let plaintext = general_purpose::STANDARD
+                .decode(res?.plaintext)
+                .map_err(|e| ClientError::EncryptedFieldError {
+                    reason: format!("Vault returned invalid base64: {}", e),
+                })?;
+            fields[i].set_plaintext(&plaintext)?;
+        }
+    }
+    Ok(())
+}
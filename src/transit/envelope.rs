@@ -0,0 +1,356 @@
+//! Envelope encryption using data keys generated by a transit engine.
+//!
+//! Every envelope is encrypted locally with AES-256-GCM using a data key
+//! (DEK) generated with [data_key][crate::transit::generate::data_key]. The
+//! DEK wrapped by the named transit key is stored in the envelope, so only
+//! the wrapped key needs to be sent to Vault when decrypting:
+//!
+//! ```ignore
+//! use vaultrs::transit::envelope::{Envelope, EnvelopeCipher};
+//!
+//! let cipher = EnvelopeCipher::new(&client, "transit", "my-key", Default::default());
+//! let envelope = cipher.encrypt(b"super secret data").await?;
+//! let stored: Vec<u8> = envelope.to_bytes()?;
+//!
+//! let envelope = Envelope::from_bytes(&stored)?;
+//! let plaintext = cipher.decrypt(&envelope).await?;
+//! ```
+//!
+//! Unwrapped DEKs are cached in memory for [EnvelopeOptions::ttl]. While the
+//! DEK used for encryption is cached, it's reused for up to
+//! [EnvelopeOptions::max_uses] envelopes encrypted with the same
+//! [EnvelopeCipher].
+//!
+//! # Limits
+//!
+//! Each envelope uses a random 96-bit nonce, so the number of envelopes
+//! encrypted with a single DEK must stay well below 2^32 to keep the chance
+//! of a nonce collision negligible. [EnvelopeOptions::max_uses] caps it at one
+//! million by default, after which a new DEK is generated.
+//!
+//! Envelopes are encrypted in a single AES-GCM operation, so the whole
+//! plaintext and ciphertext are held in memory, including by
+//! [EnvelopeCipher::encrypt_reader] and [EnvelopeCipher::decrypt_reader]. Use
+//! [stream][crate::transit::stream] to encrypt large files in chunks.
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit, Nonce,
+};
+use futures::io::{AsyncRead, AsyncReadExt};
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    transit::dek::{self, HeaderReader, KEY_SIZE},
+};
+
+/// The bytes every serialized [Envelope] starts with
+const MAGIC: &[u8; 4] = b"VENV";
+/// The version of the serialized [Envelope] format
+const FORMAT_VERSION: u8 = 1;
+const NONCE_SIZE: usize = 12;
+
+/// Data encrypted with a DEK along with the information needed to decrypt it
+///
+/// Serialized with [Envelope::to_bytes] an envelope is laid out as:
+///
+/// | Field          | Size                                   |
+/// |----------------|----------------------------------------|
+/// | `VENV`         | 4 bytes                                |
+/// | format version | 1 byte                                 |
+/// | key name       | 2 byte big-endian length + UTF-8 bytes |
+/// | key version    | 4 bytes big-endian                     |
+/// | wrapped DEK    | 2 byte big-endian length + UTF-8 bytes |
+/// | nonce          | 12 bytes                               |
+/// | ciphertext     | remaining bytes, including the GCM tag |
+///
+/// Everything before the ciphertext is authenticated as additional data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// The name of the transit key which wrapped the DEK
+    pub key: String,
+    /// The version of the transit key which wrapped the DEK
+    pub key_version: u64,
+    /// The DEK as returned by Vault, e.g. `vault:v1:...`
+    pub wrapped_key: String,
+    pub nonce: [u8; NONCE_SIZE],
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Serializes this envelope
+    ///
+    /// Fails if the key name or the wrapped DEK is longer than 65535 bytes or
+    /// the key version doesn't fit in 32 bits.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ClientError> {
+        let mut out = self.header()?;
+        out.extend_from_slice(&self.ciphertext);
+        Ok(out)
+    }
+
+    /// Parses an envelope serialized with [Envelope::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClientError> {
+        let mut reader = HeaderReader::new(bytes, envelope_error);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(envelope_error("missing envelope header"));
+        }
+        let version = reader.take(1)?[0];
+        if version != FORMAT_VERSION {
+            return Err(envelope_error(&format!(
+                "unsupported envelope version {}",
+                version
+            )));
+        }
+        let (key, key_version, wrapped_key) = reader.key_fields()?;
+        let nonce = reader.take(NONCE_SIZE)?.try_into().unwrap();
+        Ok(Envelope {
+            key,
+            key_version,
+            wrapped_key,
+            nonce,
+            ciphertext: reader.rest().to_vec(),
+        })
+    }
+
+    /// Returns the serialized fields preceding the ciphertext
+    fn header(&self) -> Result<Vec<u8>, ClientError> {
+        let mut out = Vec::with_capacity(
+            MAGIC.len() + 9 + self.key.len() + self.wrapped_key.len() + NONCE_SIZE,
+        );
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        dek::write_key_fields(
+            &mut out,
+            &self.key,
+            self.key_version,
+            &self.wrapped_key,
+            envelope_error,
+        )?;
+        out.extend_from_slice(&self.nonce);
+        Ok(out)
+    }
+}
+
+/// Options for an [EnvelopeCipher]
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct EnvelopeOptions {
+    /// How long unwrapped DEKs are kept in memory. A zero duration disables
+    /// caching and generates a new DEK for every envelope.
+    pub ttl: Duration,
+    /// The maximum number of envelopes encrypted with the same DEK
+    pub max_uses: u64,
+    /// The base64 encoded context for key derivation. This is required if
+    /// key derivation is enabled for the transit key.
+    pub context: Option<String>,
+}
+
+impl Default for EnvelopeOptions {
+    fn default() -> Self {
+        EnvelopeOptions {
+            ttl: Duration::from_secs(300),
+            max_uses: 1_000_000,
+            context: None,
+        }
+    }
+}
+
+/// Encrypts and decrypts [Envelope]s using a named transit key
+pub struct EnvelopeCipher<'a, C: Client> {
+    client: &'a C,
+    mount: String,
+    key: String,
+    opts: EnvelopeOptions,
+    /// The DEK used for encryption
+    current: Mutex<Option<CachedKey>>,
+    /// Unwrapped DEKs keyed by transit key name and wrapped DEK
+    cache: Mutex<HashMap<(String, String), CachedKey>>,
+}
+
+#[derive(Clone)]
+struct CachedKey {
+    wrapped: String,
+    version: u64,
+    key: [u8; KEY_SIZE],
+    expires: Instant,
+    /// The number of envelopes encrypted with this key
+    uses: u64,
+}
+
+impl<'a, C: Client> EnvelopeCipher<'a, C> {
+    /// Creates a cipher which wraps DEKs with the key `key` of the transit
+    /// engine mounted at `mount`
+    pub fn new(client: &'a C, mount: &str, key: &str, opts: EnvelopeOptions) -> Self {
+        EnvelopeCipher {
+            client,
+            mount: mount.to_string(),
+            key: key.to_string(),
+            opts,
+            current: Mutex::new(None),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Encrypts the given plaintext into a new envelope
+    #[instrument(skip(self, plaintext), fields(mount = %self.mount, key = %self.key), err)]
+    pub async fn encrypt(&self, plaintext: &[u8]) -> Result<Envelope, ClientError> {
+        let dek = self.encryption_key().await?;
+        let mut envelope = Envelope {
+            key: self.key.clone(),
+            key_version: dek.version,
+            wrapped_key: dek.wrapped,
+            nonce: rand::random(),
+            ciphertext: Vec::new(),
+        };
+        let aad = envelope.header()?;
+        envelope.ciphertext = Aes256Gcm::new(&dek.key.into())
+            .encrypt(
+                Nonce::from_slice(&envelope.nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| envelope_error("encryption failed"))?;
+        Ok(envelope)
+    }
+
+    /// Decrypts the given envelope, unwrapping its DEK with
+    /// [data::decrypt][crate::transit::data::decrypt] unless it's cached
+    #[instrument(skip(self, envelope), fields(mount = %self.mount, key = %envelope.key), err)]
+    pub async fn decrypt(&self, envelope: &Envelope) -> Result<Vec<u8>, ClientError> {
+        let aad = envelope.header()?;
+        let key = self.decryption_key(envelope).await?;
+        Aes256Gcm::new(&key.into())
+            .decrypt(
+                Nonce::from_slice(&envelope.nonce),
+                Payload {
+                    msg: &envelope.ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| envelope_error("decryption failed"))
+    }
+
+    /// Reads `reader` to the end and encrypts its contents into a new
+    /// envelope
+    ///
+    /// The whole contents are buffered in memory.
+    pub async fn encrypt_reader<R>(&self, mut reader: R) -> Result<Envelope, ClientError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut plaintext = Vec::new();
+        reader
+            .read_to_end(&mut plaintext)
+            .await
+            .map_err(|e| ClientError::StreamError { source: e })?;
+        self.encrypt(&plaintext).await
+    }
+
+    /// Reads a serialized envelope from `reader` and decrypts it
+    ///
+    /// The whole envelope is buffered in memory.
+    pub async fn decrypt_reader<R>(&self, mut reader: R) -> Result<Vec<u8>, ClientError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| ClientError::StreamError { source: e })?;
+        self.decrypt(&Envelope::from_bytes(&bytes)?).await
+    }
+
+    /// Removes all DEKs from memory
+    pub fn clear_cache(&self) {
+        self.current.lock().unwrap().take();
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Returns the cached DEK for encryption or generates a new one
+    async fn encryption_key(&self) -> Result<CachedKey, ClientError> {
+        if let Some(dek) = self.current.lock().unwrap().as_mut() {
+            if dek.expires > Instant::now() && dek.uses < self.opts.max_uses {
+                dek.uses += 1;
+                return Ok(dek.clone());
+            }
+        }
+
+        let key = dek::generate(
+            self.client,
+            &self.mount,
+            &self.key,
+            self.opts.context.as_deref(),
+            envelope_error,
+        )
+        .await?;
+        let dek = CachedKey {
+            version: key.version,
+            wrapped: key.wrapped,
+            key: key.key,
+            expires: Instant::now() + self.opts.ttl,
+            uses: 1,
+        };
+
+        if !self.opts.ttl.is_zero() {
+            *self.current.lock().unwrap() = Some(dek.clone());
+            self.insert((self.key.clone(), dek.wrapped.clone()), dek.clone());
+        }
+        Ok(dek)
+    }
+
+    /// Returns the cached DEK of the envelope or unwraps it
+    async fn decryption_key(&self, envelope: &Envelope) -> Result<[u8; KEY_SIZE], ClientError> {
+        let id = (envelope.key.clone(), envelope.wrapped_key.clone());
+        if let Some(dek) = self.cache.lock().unwrap().get(&id) {
+            if dek.expires > Instant::now() {
+                return Ok(dek.key);
+            }
+        }
+
+        let key = dek::unwrap(
+            self.client,
+            &self.mount,
+            &envelope.key,
+            &envelope.wrapped_key,
+            self.opts.context.as_deref(),
+            envelope_error,
+        )
+        .await?;
+
+        if !self.opts.ttl.is_zero() {
+            let dek = CachedKey {
+                wrapped: envelope.wrapped_key.clone(),
+                version: envelope.key_version,
+                key,
+                expires: Instant::now() + self.opts.ttl,
+                uses: 0,
+            };
+            self.insert(id, dek);
+        }
+        Ok(key)
+    }
+
+    /// Caches a DEK, evicting all expired ones
+    fn insert(&self, id: (String, String), dek: CachedKey) {
+        let now = Instant::now();
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, v| v.expires > now);
+        cache.insert(id, dek);
+    }
+}
+
+fn envelope_error(reason: &str) -> ClientError {
+    ClientError::EnvelopeError {
+        reason: reason.to_string(),
+    }
+}
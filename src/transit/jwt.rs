@@ -0,0 +1,457 @@
+//! Signs JWTs with transit keys and publishes their public keys as a JWKS.
+//!
+//! Tokens are signed with [data::sign] using [MarshalingAlgorithm::Jws], so
+//! the private key never leaves Vault:
+//!
+//! ```ignore
+//! use vaultrs::transit::jwt::{self, JwtClaims};
+//!
+//! let claims = JwtClaims {
+//!     sub: Some("user-1".into()),
+//!     exp: Some(1700000000),
+//!     ..Default::default()
+//! };
+//! let token = jwt::sign(&client, "transit", "jwt-key", &claims, &Default::default()).await?;
+//!
+//! // Serve this document to verifiers, e.g. at /.well-known/jwks.json
+//! let jwks = jwt::jwks(&client, "transit", "jwt-key", None).await?;
+//! ```
+//!
+//! Every token carries the key version it was signed with in its `kid`
+//! header (see [kid]). The JWKS contains one key per version which can still
+//! be used for verification, so verifiers keep accepting tokens signed with
+//! older versions after the key is rotated.
+use base64::{engine::general_purpose, Engine as _};
+use p256::{elliptic_curve::sec1::ToEncodedPoint, pkcs8::DecodePublicKey};
+use rsa::{traits::PublicKeyParts, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    api::transit::{
+        requests::SignDataRequest,
+        responses::{ReadKeyData, ReadKeyResponse},
+        HashAlgorithm, KeyType, MarshalingAlgorithm, SignatureAlgorithm,
+    },
+    client::Client,
+    error::ClientError,
+    transit::{data, key},
+};
+
+/// The JWS algorithms which can be used with transit keys
+///
+/// The `PS*` algorithms sign with a salt as long as the hash, which requires
+/// the `salt_length` parameter added in Vault 1.11.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JwsAlgorithm {
+    EdDSA,
+    ES256,
+    ES384,
+    ES512,
+    RS256,
+    RS384,
+    RS512,
+    PS256,
+    PS384,
+    PS512,
+}
+
+impl JwsAlgorithm {
+    /// Returns the default algorithm for keys of the given type
+    pub fn for_key_type(key_type: KeyType) -> Option<Self> {
+        match key_type {
+            KeyType::Ed25519 => Some(Self::EdDSA),
+            KeyType::EcdsaP256 => Some(Self::ES256),
+            KeyType::EcdsaP384 => Some(Self::ES384),
+            KeyType::EcdsaP521 => Some(Self::ES512),
+            KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096 => Some(Self::RS256),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this algorithm can be used with keys of the given type
+    pub fn supports(&self, key_type: KeyType) -> bool {
+        match self {
+            Self::RS256 | Self::RS384 | Self::RS512 | Self::PS256 | Self::PS384 | Self::PS512 => {
+                matches!(
+                    key_type,
+                    KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096
+                )
+            }
+            _ => Self::for_key_type(key_type) == Some(*self),
+        }
+    }
+
+    fn hash_algorithm(&self) -> HashAlgorithm {
+        match self {
+            Self::ES384 | Self::RS384 | Self::PS384 => HashAlgorithm::Sha2_384,
+            Self::ES512 | Self::RS512 | Self::PS512 => HashAlgorithm::Sha2_512,
+            _ => HashAlgorithm::Sha2_256,
+        }
+    }
+}
+
+impl std::fmt::Display for JwsAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The header of a JWS
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwsHeader {
+    pub alg: JwsAlgorithm,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+    pub kid: String,
+}
+
+/// The `aud` claim of a JWT, either a single audience or a list of them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Returns whether the given audience is included
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Self::Single(aud) => aud == audience,
+            Self::Multiple(auds) => auds.iter().any(|aud| aud == audience),
+        }
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(audience: &str) -> Self {
+        Self::Single(audience.to_string())
+    }
+}
+
+impl From<String> for Audience {
+    fn from(audience: String) -> Self {
+        Self::Single(audience)
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(audiences: Vec<String>) -> Self {
+        Self::Multiple(audiences)
+    }
+}
+
+/// The registered claims of a JWT along with any additional claims
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JwtClaims {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Options for [sign] and [sign_jws]
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct JwsOptions {
+    /// The algorithm to sign with, defaults to
+    /// [JwsAlgorithm::for_key_type]
+    pub algorithm: Option<JwsAlgorithm>,
+    /// The `cty` header parameter
+    pub content_type: Option<String>,
+}
+
+/// A JSON Web Key
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<JwsAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+}
+
+/// A JSON Web Key Set
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    /// Builds a JWKS from the public keys of every version of the key which
+    /// is at least its minimum decryption version
+    ///
+    /// The keys are published with the given algorithm, which should be the
+    /// one tokens are signed with. Without one, the `alg` of RSA keys is
+    /// omitted since they can sign with several algorithms, while other keys
+    /// get the only algorithm they support.
+    pub fn from_key(
+        key: &ReadKeyResponse,
+        algorithm: Option<JwsAlgorithm>,
+    ) -> Result<Self, ClientError> {
+        let default = JwsAlgorithm::for_key_type(key.key_type).ok_or_else(|| {
+            jws_error(&format!(
+                "{:?} keys can't be used for signing",
+                key.key_type
+            ))
+        })?;
+        let alg = match algorithm {
+            Some(alg) if alg.supports(key.key_type) => Some(alg),
+            Some(alg) => {
+                return Err(jws_error(&format!(
+                    "{} can't be used with {:?} keys",
+                    alg, key.key_type
+                )))
+            }
+            None if matches!(
+                key.key_type,
+                KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096
+            ) =>
+            {
+                None
+            }
+            None => Some(default),
+        };
+        let entries = match &key.keys {
+            ReadKeyData::Asymmetric(entries) => entries,
+            ReadKeyData::Symmetric(_) => return Err(jws_error("the key has no public keys")),
+        };
+
+        let mut versions = entries
+            .iter()
+            .filter_map(|(version, entry)| Some((version.parse::<u64>().ok()?, entry)))
+            .filter(|(version, _)| *version >= key.min_decryption_version)
+            .collect::<Vec<_>>();
+        versions.sort_by_key(|(version, _)| *version);
+
+        let keys = versions
+            .into_iter()
+            .map(|(version, entry)| {
+                jwk(
+                    key.key_type,
+                    &entry.public_key,
+                    kid(&key.name, version),
+                    alg,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Jwks { keys })
+    }
+}
+
+/// Returns the `kid` of the given version of a key
+pub fn kid(name: &str, version: u64) -> String {
+    format!("{}:v{}", name, version)
+}
+
+/// Signs the claims with the latest version of the key and returns the
+/// compact serialization of the JWT
+#[instrument(skip(client, claims, opts), err)]
+pub async fn sign<T: Serialize>(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    claims: &T,
+    opts: &JwsOptions,
+) -> Result<String, ClientError> {
+    let payload =
+        serde_json::to_vec(claims).map_err(|e| ClientError::JsonParseError { source: e })?;
+    sign_jws(client, mount, name, Some("JWT"), &payload, opts).await
+}
+
+/// Signs an arbitrary payload with the latest version of the key and returns
+/// the compact serialization of the JWS
+#[instrument(skip(client, payload, opts), err)]
+pub async fn sign_jws(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    typ: Option<&str>,
+    payload: &[u8],
+    opts: &JwsOptions,
+) -> Result<String, ClientError> {
+    let key = key::read(client, mount, name).await?;
+    let alg = match opts.algorithm {
+        Some(alg) if alg.supports(key.key_type) => alg,
+        Some(alg) => {
+            return Err(jws_error(&format!(
+                "{} can't be used with {:?} keys",
+                alg, key.key_type
+            )))
+        }
+        None => JwsAlgorithm::for_key_type(key.key_type).ok_or_else(|| {
+            jws_error(&format!(
+                "{:?} keys can't be used for signing",
+                key.key_type
+            ))
+        })?,
+    };
+    let version = key
+        .latest_version
+        .ok_or_else(|| jws_error("Vault did not return the latest key version"))?;
+
+    let header = JwsHeader {
+        alg,
+        typ: typ.map(String::from),
+        cty: opts.content_type.clone(),
+        kid: kid(name, version),
+    };
+    let header =
+        serde_json::to_vec(&header).map_err(|e| ClientError::JsonParseError { source: e })?;
+    let signing_input = format!(
+        "{}.{}",
+        general_purpose::URL_SAFE_NO_PAD.encode(header),
+        general_purpose::URL_SAFE_NO_PAD.encode(payload)
+    );
+
+    let mut sign_opts = SignDataRequest::builder();
+    sign_opts
+        .key_version(version)
+        .hash_algorithm(alg.hash_algorithm())
+        .marshaling_algorithm(MarshalingAlgorithm::Jws);
+    match alg {
+        JwsAlgorithm::RS256 | JwsAlgorithm::RS384 | JwsAlgorithm::RS512 => {
+            sign_opts.signature_algorithm(SignatureAlgorithm::Pkcs1v15);
+        }
+        JwsAlgorithm::PS256 | JwsAlgorithm::PS384 | JwsAlgorithm::PS512 => {
+            sign_opts
+                .signature_algorithm(SignatureAlgorithm::Pss)
+                .salt_length("hash");
+        }
+        _ => {}
+    }
+    let signature = data::sign(
+        client,
+        mount,
+        name,
+        &general_purpose::STANDARD.encode(&signing_input),
+        Some(&mut sign_opts),
+    )
+    .await?
+    .signature;
+
+    // Signatures are returned as vault:vN:<base64url>
+    let signature = signature
+        .splitn(3, ':')
+        .nth(2)
+        .ok_or_else(|| jws_error("Vault returned a signature in an unknown format"))?;
+    Ok(format!("{}.{}", signing_input, signature))
+}
+
+/// Reads the public keys of the key and returns them as a JWKS
+///
+/// See [Jwks::from_key]
+#[instrument(skip(client), err)]
+pub async fn jwks(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    algorithm: Option<JwsAlgorithm>,
+) -> Result<Jwks, ClientError> {
+    Jwks::from_key(&key::read(client, mount, name).await?, algorithm)
+}
+
+fn jwk(
+    key_type: KeyType,
+    public_key: &str,
+    kid: String,
+    alg: Option<JwsAlgorithm>,
+) -> Result<Jwk, ClientError> {
+    let b64 = |bytes: &[u8]| general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+    let invalid = |e: &dyn std::fmt::Display| jws_error(&format!("invalid public key: {}", e));
+    let mut jwk = Jwk {
+        kty: String::new(),
+        kid,
+        key_use: "sig".to_string(),
+        alg,
+        crv: None,
+        x: None,
+        y: None,
+        n: None,
+        e: None,
+    };
+
+    let (crv, point) = match key_type {
+        KeyType::Ed25519 => {
+            let x = general_purpose::STANDARD
+                .decode(public_key)
+                .map_err(|e| invalid(&e))?;
+            jwk.kty = "OKP".to_string();
+            jwk.crv = Some("Ed25519".to_string());
+            jwk.x = Some(b64(&x));
+            return Ok(jwk);
+        }
+        KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096 => {
+            let key = RsaPublicKey::from_public_key_pem(public_key).map_err(|e| invalid(&e))?;
+            jwk.kty = "RSA".to_string();
+            jwk.n = Some(b64(&key.n().to_bytes_be()));
+            jwk.e = Some(b64(&key.e().to_bytes_be()));
+            return Ok(jwk);
+        }
+        KeyType::EcdsaP256 => (
+            "P-256",
+            p256::PublicKey::from_public_key_pem(public_key)
+                .map_err(|e| invalid(&e))?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        KeyType::EcdsaP384 => (
+            "P-384",
+            p384::PublicKey::from_public_key_pem(public_key)
+                .map_err(|e| invalid(&e))?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        KeyType::EcdsaP521 => (
+            "P-521",
+            p521::PublicKey::from_public_key_pem(public_key)
+                .map_err(|e| invalid(&e))?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        _ => return Err(jws_error("the key has no public keys")),
+    };
+
+    // Uncompressed SEC1 points are 0x04 || x || y
+    let (x, y) = point[1..].split_at((point.len() - 1) / 2);
+    jwk.kty = "EC".to_string();
+    jwk.crv = Some(crv.to_string());
+    jwk.x = Some(b64(x));
+    jwk.y = Some(b64(y));
+    Ok(jwk)
+}
+
+fn jws_error(reason: &str) -> ClientError {
+    ClientError::JwsError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,351 @@
+//! Verifies signatures created by a transit engine without contacting Vault.
+//!
+//! The public keys of every version of an asymmetric key are read once with
+//! [key::read] and signatures are verified locally:
+//!
+//! ```ignore
+//! use vaultrs::api::transit::Signature;
+//! use vaultrs::transit::offline::{OfflineVerifier, VerifyOptions};
+//!
+//! let verifier = OfflineVerifier::new(&client, "transit", "my-key").await?;
+//! let signature = Signature::parse("vault:v1:MEUCIQ...")?;
+//! let valid = verifier
+//!     .verify(b"some data", &signature, &VerifyOptions::default())
+//!     .await?;
+//! ```
+//!
+//! When a signature references a key version which is newer than the ones
+//! loaded, the public keys are read again before verifying it.
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::{Mutex, MutexGuard},
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::Verifier;
+use p256::{ecdsa::signature::hazmat::PrehashVerifier, pkcs8::DecodePublicKey};
+use rsa::{Pkcs1v15Sign, Pss, RsaPublicKey};
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+
+use crate::{
+    api::transit::{
+        responses::{ReadKeyData, ReadKeyResponse},
+        HashAlgorithm, KeyType, MarshalingAlgorithm, Signature, SignatureAlgorithm,
+    },
+    client::Client,
+    error::ClientError,
+    transit::key,
+};
+
+/// Options for verifying a signature with [OfflineVerifier::verify]
+///
+/// These must match the options which were used to create the signature.
+/// Unset options use the same defaults as Vault.
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct VerifyOptions {
+    /// The hash algorithm used for ECDSA and RSA keys, defaults to SHA2-256
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// The signature algorithm used for RSA keys, defaults to PSS
+    pub signature_algorithm: Option<SignatureAlgorithm>,
+    /// The way ECDSA signatures were marshaled, defaults to ASN.1
+    pub marshaling_algorithm: Option<MarshalingAlgorithm>,
+    /// Whether the input is already hashed with `hash_algorithm`
+    pub prehashed: bool,
+}
+
+/// Verifies signatures of an asymmetric transit key using its public keys
+pub struct OfflineVerifier<'a, C: Client> {
+    client: &'a C,
+    mount: String,
+    name: String,
+    keys: Mutex<KeySet>,
+}
+
+/// The public keys of a transit key
+struct KeySet {
+    key_type: KeyType,
+    min_version: u64,
+    latest_version: u64,
+    versions: HashMap<u64, PublicKey>,
+}
+
+enum PublicKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+    P521(p521::ecdsa::VerifyingKey),
+    Rsa(RsaPublicKey),
+}
+
+impl<'a, C: Client> OfflineVerifier<'a, C> {
+    /// Creates a verifier for the key `name` of the transit engine mounted at
+    /// `mount`, reading its public keys
+    #[instrument(skip(client), err)]
+    pub async fn new(client: &'a C, mount: &str, name: &str) -> Result<Self, ClientError> {
+        let keys = KeySet::parse(key::read(client, mount, name).await?)?;
+        Ok(OfflineVerifier {
+            client,
+            mount: mount.to_string(),
+            name: name.to_string(),
+            keys: Mutex::new(keys),
+        })
+    }
+
+    /// Reads the public keys of the key again
+    #[instrument(skip(self), fields(mount = %self.mount, name = %self.name), err)]
+    pub async fn refresh(&self) -> Result<(), ClientError> {
+        let keys = KeySet::parse(key::read(self.client, &self.mount, &self.name).await?)?;
+        *self.lock() = keys;
+        Ok(())
+    }
+
+    /// Returns the latest version of the key which was loaded
+    pub fn latest_version(&self) -> u64 {
+        self.lock().latest_version
+    }
+
+    /// Returns whether `signature` is a valid signature of `input`
+    ///
+    /// The input is the raw data, not base64 encoded like for
+    /// [data::verify][crate::transit::data::verify]. Signatures which can't
+    /// be decoded or reference a key version which doesn't exist or is below
+    /// the key's minimum decryption version return an error.
+    #[instrument(skip(self, input, opts), fields(mount = %self.mount, name = %self.name), err)]
+    pub async fn verify(
+        &self,
+        input: &[u8],
+        signature: &Signature,
+        opts: &VerifyOptions,
+    ) -> Result<bool, ClientError> {
+        let version = signature.version();
+        let signature = decode_signature(signature, opts)?;
+        if version > self.latest_version() {
+            self.refresh().await?;
+        }
+        self.lock().verify(version, input, &signature, opts)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, KeySet> {
+        self.keys.lock().unwrap()
+    }
+}
+
+impl KeySet {
+    fn parse(resp: ReadKeyResponse) -> Result<Self, ClientError> {
+        if resp.derived {
+            return Err(offline_error("keys using key derivation are not supported"));
+        }
+        let keys = match resp.keys {
+            ReadKeyData::Asymmetric(keys) => keys,
+            ReadKeyData::Symmetric(_) => {
+                return Err(offline_error(&format!(
+                    "{:?} keys have no public keys",
+                    resp.key_type
+                )))
+            }
+        };
+
+        let mut versions = HashMap::new();
+        for (version, entry) in keys {
+            let version = version
+                .parse()
+                .map_err(|_| offline_error(&format!("invalid key version {}", version)))?;
+            let public_key = parse_public_key(resp.key_type, &entry.public_key)
+                .map_err(|e| offline_error(&format!("invalid public key {}: {}", version, e)))?;
+            versions.insert(version, public_key);
+        }
+        Ok(KeySet {
+            key_type: resp.key_type,
+            min_version: resp.min_decryption_version,
+            latest_version: resp
+                .latest_version
+                .or_else(|| versions.keys().max().copied())
+                .unwrap_or_default(),
+            versions,
+        })
+    }
+
+    fn verify(
+        &self,
+        version: u64,
+        input: &[u8],
+        signature: &[u8],
+        opts: &VerifyOptions,
+    ) -> Result<bool, ClientError> {
+        if version < self.min_version {
+            return Err(offline_error(&format!(
+                "key version {} is below the minimum version {}",
+                version, self.min_version
+            )));
+        }
+        let public_key = self
+            .versions
+            .get(&version)
+            .ok_or_else(|| offline_error(&format!("key version {} not found", version)))?;
+
+        let hash = opts.hash_algorithm.unwrap_or(HashAlgorithm::Sha2_256);
+        let hashed = || match opts.prehashed {
+            true => input.to_vec(),
+            false => digest(hash, input),
+        };
+        let jws = matches!(opts.marshaling_algorithm, Some(MarshalingAlgorithm::Jws));
+        let valid = match public_key {
+            PublicKey::Ed25519(key) => match ed25519_dalek::Signature::from_slice(signature) {
+                Ok(sig) => key.verify(input, &sig).is_ok(),
+                Err(_) => false,
+            },
+            PublicKey::P256(key) => {
+                let sig = match jws {
+                    true => p256::ecdsa::Signature::from_slice(signature),
+                    false => p256::ecdsa::Signature::from_der(signature),
+                };
+                sig.map(|sig| key.verify_prehash(&hashed(), &sig).is_ok())
+                    .unwrap_or(false)
+            }
+            PublicKey::P384(key) => {
+                let sig = match jws {
+                    true => p384::ecdsa::Signature::from_slice(signature),
+                    false => p384::ecdsa::Signature::from_der(signature),
+                };
+                sig.map(|sig| key.verify_prehash(&hashed(), &sig).is_ok())
+                    .unwrap_or(false)
+            }
+            PublicKey::P521(key) => {
+                let sig = match jws {
+                    true => p521::ecdsa::Signature::from_slice(signature),
+                    false => p521::ecdsa::Signature::from_der(signature),
+                };
+                // Short digests are left padded, which doesn't change their
+                // value as an integer but satisfies the minimum prehash size
+                let mut prehash = hashed();
+                if prehash.len() < 66 {
+                    let mut padded = vec![0; 66 - prehash.len()];
+                    padded.extend_from_slice(&prehash);
+                    prehash = padded;
+                }
+                sig.map(|sig| key.verify_prehash(&prehash, &sig).is_ok())
+                    .unwrap_or(false)
+            }
+            PublicKey::Rsa(key) => verify_rsa(key, hash, &hashed(), signature, opts),
+        };
+        debug!(
+            "verified {:?} signature with key version {}: {}",
+            self.key_type, version, valid
+        );
+        Ok(valid)
+    }
+}
+
+/// Decodes the payload of a signature
+fn decode_signature(signature: &Signature, opts: &VerifyOptions) -> Result<Vec<u8>, ClientError> {
+    // JWS marshaled signatures use unpadded URL-safe base64
+    let decoded = match opts.marshaling_algorithm {
+        Some(MarshalingAlgorithm::Jws) => {
+            general_purpose::URL_SAFE_NO_PAD.decode(signature.payload())
+        }
+        _ => general_purpose::STANDARD.decode(signature.payload()),
+    };
+    decoded.map_err(|_| offline_error("signature is not valid base64"))
+}
+
+fn parse_public_key(key_type: KeyType, public_key: &str) -> Result<PublicKey, String> {
+    match key_type {
+        KeyType::Ed25519 => {
+            let bytes: [u8; 32] = general_purpose::STANDARD
+                .decode(public_key)
+                .ok()
+                .and_then(|b| b.try_into().ok())
+                .ok_or("expected a base64 encoded 32 byte key")?;
+            ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+                .map(PublicKey::Ed25519)
+                .map_err(|e| e.to_string())
+        }
+        KeyType::EcdsaP256 => p256::ecdsa::VerifyingKey::from_public_key_pem(public_key)
+            .map(PublicKey::P256)
+            .map_err(|e| e.to_string()),
+        KeyType::EcdsaP384 => p384::ecdsa::VerifyingKey::from_public_key_pem(public_key)
+            .map(PublicKey::P384)
+            .map_err(|e| e.to_string()),
+        KeyType::EcdsaP521 => p521::PublicKey::from_public_key_pem(public_key)
+            .map_err(|e| e.to_string())
+            .and_then(|key| {
+                p521::ecdsa::VerifyingKey::from_affine(*key.as_affine()).map_err(|e| e.to_string())
+            })
+            .map(PublicKey::P521),
+        KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096 => {
+            RsaPublicKey::from_public_key_pem(public_key)
+                .map(PublicKey::Rsa)
+                .map_err(|e| e.to_string())
+        }
+        _ => Err(format!("{:?} keys can't be used for signing", key_type)),
+    }
+}
+
+fn verify_rsa(
+    key: &RsaPublicKey,
+    hash: HashAlgorithm,
+    digest: &[u8],
+    signature: &[u8],
+    opts: &VerifyOptions,
+) -> bool {
+    match opts.signature_algorithm {
+        Some(SignatureAlgorithm::Pkcs1v15) => key.verify(pkcs1v15(hash), digest, signature).is_ok(),
+        // Vault signs with the maximum salt length unless configured
+        // otherwise, so the salt length of the digest size is tried as well
+        Some(SignatureAlgorithm::Pss) | None => {
+            let em_len = (rsa::traits::PublicKeyParts::n(key).bits() + 6) / 8;
+            let max_salt = em_len.saturating_sub(digest.len() + 2);
+            [max_salt, digest.len()]
+                .iter()
+                .any(|salt| key.verify(pss(hash, *salt), digest, signature).is_ok())
+        }
+    }
+}
+
+fn digest(hash: HashAlgorithm, input: &[u8]) -> Vec<u8> {
+    match hash {
+        HashAlgorithm::Sha2_224 => Sha224::digest(input).to_vec(),
+        HashAlgorithm::Sha2_256 => Sha256::digest(input).to_vec(),
+        HashAlgorithm::Sha2_384 => Sha384::digest(input).to_vec(),
+        HashAlgorithm::Sha2_512 => Sha512::digest(input).to_vec(),
+        HashAlgorithm::Sha3_224 => Sha3_224::digest(input).to_vec(),
+        HashAlgorithm::Sha3_256 => Sha3_256::digest(input).to_vec(),
+        HashAlgorithm::Sha3_384 => Sha3_384::digest(input).to_vec(),
+        HashAlgorithm::Sha3_512 => Sha3_512::digest(input).to_vec(),
+    }
+}
+
+fn pkcs1v15(hash: HashAlgorithm) -> Pkcs1v15Sign {
+    match hash {
+        HashAlgorithm::Sha2_224 => Pkcs1v15Sign::new::<Sha224>(),
+        HashAlgorithm::Sha2_256 => Pkcs1v15Sign::new::<Sha256>(),
+        HashAlgorithm::Sha2_384 => Pkcs1v15Sign::new::<Sha384>(),
+        HashAlgorithm::Sha2_512 => Pkcs1v15Sign::new::<Sha512>(),
+        HashAlgorithm::Sha3_224 => Pkcs1v15Sign::new::<Sha3_224>(),
+        HashAlgorithm::Sha3_256 => Pkcs1v15Sign::new::<Sha3_256>(),
+        HashAlgorithm::Sha3_384 => Pkcs1v15Sign::new::<Sha3_384>(),
+        HashAlgorithm::Sha3_512 => Pkcs1v15Sign::new::<Sha3_512>(),
+    }
+}
+
+fn pss(hash: HashAlgorithm, salt: usize) -> Pss {
+    match hash {
+        HashAlgorithm::Sha2_224 => Pss::new_with_salt::<Sha224>(salt),
+        HashAlgorithm::Sha2_256 => Pss::new_with_salt::<Sha256>(salt),
+        HashAlgorithm::Sha2_384 => Pss::new_with_salt::<Sha384>(salt),
+        HashAlgorithm::Sha2_512 => Pss::new_with_salt::<Sha512>(salt),
+        HashAlgorithm::Sha3_224 => Pss::new_with_salt::<Sha3_224>(salt),
+        HashAlgorithm::Sha3_256 => Pss::new_with_salt::<Sha3_256>(salt),
+        HashAlgorithm::Sha3_384 => Pss::new_with_salt::<Sha3_384>(salt),
+        HashAlgorithm::Sha3_512 => Pss::new_with_salt::<Sha3_512>(salt),
+    }
+}
+
+fn offline_error(reason: &str) -> ClientError {
+    ClientError::OfflineVerificationError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,287 @@
+//! Typed configuration of transit keys which is validated before any request
+//! is sent.
+//!
+//! A [KeyPolicy] describes both the settings fixed at creation and the ones
+//! which can be tuned later. Combinations which Vault would reject, or which
+//! can't be applied to an existing key, are caught client-side:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use vaultrs::api::transit::KeyType;
+//! use vaultrs::transit::policy::{self, KeyPolicyBuilder};
+//!
+//! let policy = KeyPolicyBuilder::default()
+//!     .key_type(KeyType::Aes256Gcm96)
+//!     .derived(true)
+//!     .convergent_encryption(true)
+//!     .auto_rotate_period(Duration::from_secs(30 * 24 * 60 * 60))
+//!     .build()
+//!     .unwrap();
+//! policy::create(&client, "transit", "my-key", &policy).await?;
+//! ```
+use std::time::Duration;
+
+use crate::{
+    api::transit::{
+        requests::{
+            CreateKeyRequest, UpdateKeyConfigurationRequest, UpdateKeyConfigurationRequestBuilder,
+        },
+        responses::ReadKeyResponse,
+        KeyType,
+    },
+    client::Client,
+    error::ClientError,
+    transit::key,
+};
+
+/// The shortest automatic rotation period accepted by Vault
+pub const MIN_AUTO_ROTATE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The configuration of a transit key
+///
+/// `key_type`, `derived`, `convergent_encryption`, `key_size` and the
+/// managed key reference can only be set when the key is created. When
+/// applied to an existing key, the ones which aren't set are left unchecked.
+/// `exportable` and `allow_plaintext_backup` can be enabled later but never
+/// disabled again.
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct KeyPolicy {
+    /// Defaults to [KeyType::Aes256Gcm96] when creating a key
+    pub key_type: Option<KeyType>,
+    pub derived: Option<bool>,
+    /// Requires `derived`
+    pub convergent_encryption: Option<bool>,
+    pub exportable: bool,
+    pub allow_plaintext_backup: bool,
+    /// A zero period disables automatic rotation
+    pub auto_rotate_period: Option<Duration>,
+    /// The key size in bytes of [KeyType::Hmac] keys
+    pub key_size: Option<u64>,
+    /// The name of the managed key of [KeyType::ManagedKey] keys
+    pub managed_key_name: Option<String>,
+    /// The UUID of the managed key of [KeyType::ManagedKey] keys
+    pub managed_key_id: Option<String>,
+    pub deletion_allowed: Option<bool>,
+    pub min_decryption_version: Option<u64>,
+    pub min_encryption_version: Option<u64>,
+}
+
+impl KeyPolicy {
+    /// Checks that the settings of the policy can be combined
+    pub fn validate(&self) -> Result<(), ClientError> {
+        self.validate_with(
+            self.key_type.unwrap_or_default(),
+            self.derived.unwrap_or(false),
+            self.convergent_encryption.unwrap_or(false),
+        )
+    }
+
+    /// Checks the settings of the policy, using the given values for the
+    /// ones fixed at creation
+    fn validate_with(
+        &self,
+        key_type: KeyType,
+        derived: bool,
+        convergent_encryption: bool,
+    ) -> Result<(), ClientError> {
+        if convergent_encryption && !derived {
+            return Err(policy_error("convergent encryption requires derived keys"));
+        }
+        if derived && !key_type.supports_derivation() {
+            return Err(policy_error(&format!(
+                "{:?} keys don't support derivation",
+                key_type
+            )));
+        }
+        if convergent_encryption && !key_type.supports_convergent_encryption() {
+            return Err(policy_error(&format!(
+                "{:?} keys don't support convergent encryption",
+                key_type
+            )));
+        }
+
+        match (key_type, self.key_size) {
+            (KeyType::Hmac, Some(size)) if !(32..=512).contains(&size) => {
+                return Err(policy_error(
+                    "the key size must be between 32 and 512 bytes",
+                ))
+            }
+            (KeyType::Hmac, _) | (_, None) => {}
+            (_, Some(_)) => return Err(policy_error("the key size can only be set for Hmac keys")),
+        }
+
+        let managed = self.managed_key_name.is_some() || self.managed_key_id.is_some();
+        match key_type {
+            KeyType::ManagedKey if !managed => {
+                return Err(policy_error(
+                    "ManagedKey keys require a managed key name or ID",
+                ))
+            }
+            KeyType::ManagedKey if self.exportable || self.allow_plaintext_backup => {
+                return Err(policy_error("ManagedKey keys can't be exported"))
+            }
+            KeyType::ManagedKey => {}
+            _ if managed => {
+                return Err(policy_error(
+                    "a managed key can only be set for ManagedKey keys",
+                ))
+            }
+            _ => {}
+        }
+
+        if let Some(period) = self.auto_rotate_period {
+            if !period.is_zero() && period < MIN_AUTO_ROTATE_PERIOD {
+                return Err(policy_error(
+                    "the auto rotate period can't be shorter than one hour",
+                ));
+            }
+        }
+        if let (Some(decryption), Some(encryption)) =
+            (self.min_decryption_version, self.min_encryption_version)
+        {
+            if encryption != 0 && encryption < decryption {
+                return Err(policy_error(
+                    "the minimum encryption version must be 0 or at least the minimum decryption version",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the policy can be applied to the existing key
+    ///
+    /// This fails if the policy changes a setting which is fixed at creation
+    /// or disables a setting which can't be disabled.
+    pub fn validate_update(&self, key: &ReadKeyResponse) -> Result<(), ClientError> {
+        let current_convergent = key.convergent_encryption.unwrap_or(false);
+        self.validate_with(
+            self.key_type.unwrap_or(key.key_type),
+            self.derived.unwrap_or(key.derived),
+            self.convergent_encryption.unwrap_or(current_convergent),
+        )?;
+        if let Some(key_type) = self.key_type {
+            if key_type != key.key_type {
+                return Err(policy_error(&format!(
+                    "the key type can't be changed from {:?} to {:?}",
+                    key.key_type, key_type
+                )));
+            }
+        }
+        if let Some(derived) = self.derived {
+            if derived != key.derived {
+                return Err(policy_error("key derivation can't be changed"));
+            }
+        }
+        if let (Some(convergent), Some(current)) =
+            (self.convergent_encryption, key.convergent_encryption)
+        {
+            if convergent != current {
+                return Err(policy_error("convergent encryption can't be changed"));
+            }
+        }
+        if let (Some(size), Some(current)) = (self.key_size, key.key_size) {
+            if size != current {
+                return Err(policy_error("the key size can't be changed"));
+            }
+        }
+        if key.exportable && !self.exportable {
+            return Err(policy_error("exporting can't be disabled"));
+        }
+        if key.allow_plaintext_backup && !self.allow_plaintext_backup {
+            return Err(policy_error("plaintext backups can't be disabled"));
+        }
+        Ok(())
+    }
+
+    fn update_request(&self) -> Option<UpdateKeyConfigurationRequestBuilder> {
+        if self.deletion_allowed.is_none()
+            && self.min_decryption_version.is_none()
+            && self.min_encryption_version.is_none()
+        {
+            return None;
+        }
+        let mut builder = UpdateKeyConfigurationRequest::builder();
+        builder
+            .deletion_allowed(self.deletion_allowed)
+            .min_decryption_version(self.min_decryption_version)
+            .min_encryption_version(self.min_encryption_version);
+        Some(builder)
+    }
+}
+
+/// Validates the policy and creates a key with it
+///
+/// Settings which can't be passed when creating a key, such as
+/// `deletion_allowed` and the minimum versions, are applied with a second
+/// request.
+#[instrument(skip(client), err)]
+pub async fn create(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    policy: &KeyPolicy,
+) -> Result<(), ClientError> {
+    policy.validate()?;
+
+    let mut builder = CreateKeyRequest::builder();
+    builder
+        .key_type(policy.key_type.unwrap_or_default())
+        .derived(policy.derived.unwrap_or(false))
+        .convergent_encryption(policy.convergent_encryption.unwrap_or(false))
+        .exportable(policy.exportable)
+        .allow_plaintext_backup(policy.allow_plaintext_backup);
+    if let Some(period) = policy.auto_rotate_period {
+        builder.auto_rotate_period(format_period(period));
+    }
+    if let Some(size) = policy.key_size {
+        builder.key_size(size);
+    }
+    if let Some(managed_key_name) = &policy.managed_key_name {
+        builder.managed_key_name(managed_key_name);
+    }
+    if let Some(managed_key_id) = &policy.managed_key_id {
+        builder.managed_key_id(managed_key_id);
+    }
+    key::create(client, mount, name, Some(&mut builder)).await?;
+
+    if let Some(mut builder) = policy.update_request() {
+        key::update(client, mount, name, Some(&mut builder)).await?;
+    }
+    Ok(())
+}
+
+/// Validates the policy against the existing key and updates the key's
+/// configuration to match it
+#[instrument(skip(client), err)]
+pub async fn apply(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    policy: &KeyPolicy,
+) -> Result<(), ClientError> {
+    let current = key::read(client, mount, name).await?;
+    policy.validate_update(&current)?;
+
+    let mut builder = policy
+        .update_request()
+        .unwrap_or_else(UpdateKeyConfigurationRequest::builder);
+    if policy.exportable && !current.exportable {
+        builder.exportable(true);
+    }
+    if policy.allow_plaintext_backup && !current.allow_plaintext_backup {
+        builder.allow_plaintext_backup(true);
+    }
+    builder.auto_rotate_period(policy.auto_rotate_period.map(format_period));
+    key::update(client, mount, name, Some(&mut builder)).await
+}
+
+fn format_period(period: Duration) -> String {
+    format!("{}s", period.as_secs())
+}
+
+fn policy_error(reason: &str) -> ClientError {
+    ClientError::KeyPolicyError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,209 @@
+//! Rewraps stored ciphertexts with the latest version of a transit key.
+//!
+//! After rotating a key and raising its `min_decryption_version`, every
+//! ciphertext produced by an older version has to be rewrapped before it
+//! becomes undecryptable. [run] consumes a stream of ciphertexts, skips
+//! those already produced by the latest version and rewraps the rest in
+//! batches:
+//!
+//! ```ignore
+//! use futures::stream;
+//! use vaultrs::transit::rewrap::{self, RewrapOptions, RewrapStatus};
+//!
+//! let ciphertexts = stream::iter(rows.iter().map(|row| row.ciphertext.clone()));
+//! let report = rewrap::run(&client, "transit", "my-key", ciphertexts, &RewrapOptions::default(),
+//!     |progress, outcomes| {
+//!         for outcome in outcomes {
+//!             if let RewrapStatus::Rewrapped(ciphertext) = &outcome.status {
+//!                 rows[outcome.index].ciphertext = ciphertext.to_string();
+//!             }
+//!         }
+//!         println!("{} processed", progress.processed);
+//!     },
+//! ).await?;
+//! ```
+use futures::{Stream, StreamExt};
+
+use crate::{
+    api::transit::{requests::DecryptDataBatchInput, responses::ReadKeyData, Ciphertext},
+    client::Client,
+    error::ClientError,
+    transit::{data, key, DEFAULT_BATCH_SIZE},
+};
+
+/// Options for [run]
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct RewrapOptions {
+    /// The number of ciphertexts sent per request
+    pub batch_size: usize,
+    /// The base64 encoded context used for all ciphertexts of a derived key
+    pub context: Option<String>,
+}
+
+impl Default for RewrapOptions {
+    fn default() -> Self {
+        RewrapOptions {
+            batch_size: DEFAULT_BATCH_SIZE,
+            context: None,
+        }
+    }
+}
+
+/// What happened to a single ciphertext
+#[derive(Debug)]
+pub enum RewrapStatus {
+    /// The ciphertext was already produced by the latest key version
+    Current,
+    /// The ciphertext was rewrapped into the contained ciphertext
+    Rewrapped(Ciphertext),
+    /// The ciphertext couldn't be rewrapped
+    Failed(ClientError),
+}
+
+/// The result of processing a single ciphertext
+#[derive(Debug)]
+pub struct RewrapOutcome {
+    /// The position of the ciphertext in the input stream
+    pub index: usize,
+    /// The original ciphertext
+    pub ciphertext: String,
+    pub status: RewrapStatus,
+}
+
+/// The number of ciphertexts processed so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewrapProgress {
+    pub processed: usize,
+    pub rewrapped: usize,
+    pub current: usize,
+    pub failed: usize,
+}
+
+/// The report returned by [run]
+#[derive(Debug, Default)]
+pub struct RewrapReport {
+    /// The key version all ciphertexts were rewrapped to
+    pub latest_version: u64,
+    pub progress: RewrapProgress,
+    /// Every ciphertext which couldn't be rewrapped
+    pub failures: Vec<RewrapOutcome>,
+}
+
+/// Returns the key version of a `vault:vN:` ciphertext
+///
+/// See [Ciphertext]
+pub fn ciphertext_version(ciphertext: &str) -> Option<u64> {
+    Ciphertext::parse(ciphertext).ok().map(|c| c.version())
+}
+
+/// Rewraps every ciphertext of the stream which wasn't produced by the latest
+/// version of the key
+///
+/// `on_batch` is called after each batch with the progress so far and the
+/// outcome of every ciphertext in the batch, which is where rewrapped
+/// ciphertexts should be persisted. Failures for individual ciphertexts,
+/// including failed requests, are recorded in the returned [RewrapReport]
+/// and don't stop the job. Only failing to read the key is returned as an
+/// `Err`.
+#[instrument(skip(client, ciphertexts, on_batch), err)]
+pub async fn run<S, F>(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    ciphertexts: S,
+    opts: &RewrapOptions,
+    mut on_batch: F,
+) -> Result<RewrapReport, ClientError>
+where
+    S: Stream<Item = String>,
+    F: FnMut(&RewrapProgress, &[RewrapOutcome]),
+{
+    let key = key::read(client, mount, name).await?;
+    let latest_version = match key.latest_version {
+        Some(version) => version,
+        None => match &key.keys {
+            ReadKeyData::Symmetric(keys) => versions(keys.keys()),
+            ReadKeyData::Asymmetric(keys) => versions(keys.keys()),
+        },
+    };
+
+    let mut report = RewrapReport {
+        latest_version,
+        ..Default::default()
+    };
+    let mut chunks = Box::pin(ciphertexts.chunks(opts.batch_size.max(1)));
+    let mut index = 0;
+    while let Some(chunk) = chunks.next().await {
+        let mut outcomes = Vec::with_capacity(chunk.len());
+        let mut pending = Vec::new();
+        for ciphertext in chunk {
+            let status = match ciphertext_version(&ciphertext) {
+                Some(version) if version >= latest_version => RewrapStatus::Current,
+                Some(_) => {
+                    // Replaced with the result of the rewrap request below
+                    pending.push(outcomes.len());
+                    RewrapStatus::Current
+                }
+                None => RewrapStatus::Failed(ClientError::RewrapError {
+                    reason: "not a vault:vN: ciphertext".into(),
+                }),
+            };
+            outcomes.push(RewrapOutcome {
+                index,
+                ciphertext,
+                status,
+            });
+            index += 1;
+        }
+
+        if !pending.is_empty() {
+            let items: Vec<_> = pending
+                .iter()
+                .map(|&i| DecryptDataBatchInput {
+                    ciphertext: outcomes[i].ciphertext.clone(),
+                    context: opts.context.clone(),
+                    ..Default::default()
+                })
+                .collect();
+            match data::rewrap_batch(client, mount, name, &items, items.len(), None).await {
+                Ok(results) => {
+                    for (&i, res) in pending.iter().zip(results) {
+                        outcomes[i].status = match res {
+                            Ok(res) => RewrapStatus::Rewrapped(res.ciphertext),
+                            Err(e) => RewrapStatus::Failed(e),
+                        };
+                    }
+                }
+                Err(e) => {
+                    let reason = format!("the rewrap request failed: {}", e);
+                    for &i in &pending {
+                        outcomes[i].status = RewrapStatus::Failed(ClientError::RewrapError {
+                            reason: reason.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for outcome in &outcomes {
+            match outcome.status {
+                RewrapStatus::Current => report.progress.current += 1,
+                RewrapStatus::Rewrapped(_) => report.progress.rewrapped += 1,
+                RewrapStatus::Failed(_) => report.progress.failed += 1,
+            }
+        }
+        report.progress.processed += outcomes.len();
+        on_batch(&report.progress, &outcomes);
+        report.failures.extend(
+            outcomes
+                .into_iter()
+                .filter(|o| matches!(o.status, RewrapStatus::Failed(_))),
+        );
+    }
+    Ok(report)
+}
+
+fn versions<'a>(keys: impl Iterator<Item = &'a String>) -> u64 {
+    keys.filter_map(|v| v.parse().ok()).max().unwrap_or(1)
+}
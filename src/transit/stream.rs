@@ -0,0 +1,483 @@
+//! Streaming encryption of large files with data keys generated by a transit
+//! engine.
+//!
+//! Each file is encrypted locally with its own data key (DEK) generated with
+//! [data_key][crate::transit::generate::data_key], so the contents are never
+//! sent to Vault and never held in memory as a whole:
+//!
+//! ```ignore
+//! use vaultrs::transit::stream;
+//!
+//! stream::encrypt_file(&client, "transit", "backups", "db.dump", "db.dump.enc", &Default::default()).await?;
+//! stream::decrypt_file(&client, "transit", "db.dump.enc", "db.dump", &Default::default()).await?;
+//! ```
+//!
+//! The plaintext is split into chunks which are encrypted with AES-256-GCM
+//! following the STREAM construction: the nonce of each chunk contains a
+//! random prefix, the chunk counter and a flag marking the final chunk.
+//! Reordering, dropping or appending chunks and truncating the file all fail
+//! authentication when decrypting.
+//!
+//! An encrypted stream is laid out as:
+//!
+//! | Field          | Size                                   |
+//! |----------------|----------------------------------------|
+//! | `VSTR`         | 4 bytes                                |
+//! | format version | 1 byte                                 |
+//! | chunk size     | 4 bytes big-endian                     |
+//! | key name       | 2 byte big-endian length + UTF-8 bytes |
+//! | key version    | 4 bytes big-endian                     |
+//! | wrapped DEK    | 2 byte big-endian length + UTF-8 bytes |
+//! | nonce prefix   | 7 bytes                                |
+//! | chunks         | chunk size + 16 bytes each, the final  |
+//! |                | chunk may be shorter                   |
+//!
+//! The header is authenticated as additional data of every chunk.
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+};
+
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit, Nonce,
+};
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use crate::{
+    client::Client,
+    error::ClientError,
+    transit::dek::{self, HeaderReader},
+};
+
+/// The bytes every encrypted stream starts with
+const MAGIC: &[u8; 4] = b"VSTR";
+/// The version of the stream format
+const FORMAT_VERSION: u8 = 1;
+const PREFIX_SIZE: usize = 7;
+const TAG_SIZE: usize = 16;
+
+/// The default size of plaintext chunks
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1024;
+/// The largest accepted chunk size
+pub const MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Options for encrypting and decrypting streams
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into, strip_option), default)]
+pub struct StreamOptions {
+    /// The size of plaintext chunks, only used when encrypting
+    pub chunk_size: u32,
+    /// The context used for key derivation when the transit key is derived
+    pub context: Option<String>,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            context: None,
+        }
+    }
+}
+
+/// The header of an encrypted stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamHeader {
+    pub chunk_size: u32,
+    /// The name of the transit key which wrapped the DEK
+    pub key: String,
+    /// The version of the transit key which wrapped the DEK
+    pub key_version: u64,
+    /// The DEK as returned by Vault, e.g. `vault:v1:...`
+    pub wrapped_key: String,
+    pub nonce_prefix: [u8; PREFIX_SIZE],
+}
+
+impl StreamHeader {
+    /// Serializes this header
+    ///
+    /// Fails if the key name or the wrapped DEK is longer than 65535 bytes or
+    /// the key version doesn't fit in 32 bits.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ClientError> {
+        let mut out = Vec::with_capacity(32 + self.key.len() + self.wrapped_key.len());
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&self.chunk_size.to_be_bytes());
+        dek::write_key_fields(
+            &mut out,
+            &self.key,
+            self.key_version,
+            &self.wrapped_key,
+            stream_error,
+        )?;
+        out.extend_from_slice(&self.nonce_prefix);
+        Ok(out)
+    }
+
+    /// Reads a serialized header from the start of `reader`
+    pub async fn read<R>(reader: &mut R) -> Result<Self, ClientError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        // The lengths of the variable fields are read before the fields
+        let mut bytes = vec![0u8; 11];
+        read_header_bytes(reader, &mut bytes).await?;
+        if &bytes[..4] != MAGIC {
+            return Err(stream_error("not an encrypted stream"));
+        }
+        if bytes[4] != FORMAT_VERSION {
+            return Err(stream_error(&format!(
+                "unsupported format version {}",
+                bytes[4]
+            )));
+        }
+        let chunk_size = u32::from_be_bytes(bytes[5..9].try_into().unwrap());
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(stream_error("invalid chunk size"));
+        }
+        let key_len = u16::from_be_bytes(bytes[9..11].try_into().unwrap()) as usize;
+        read_more_header_bytes(reader, &mut bytes, key_len + 6).await?;
+        let wrapped_len = u16::from_be_bytes(bytes[bytes.len() - 2..].try_into().unwrap()) as usize;
+        read_more_header_bytes(reader, &mut bytes, wrapped_len + PREFIX_SIZE).await?;
+
+        let mut fields = HeaderReader::new(&bytes[9..], stream_error);
+        let (key, key_version, wrapped_key) = fields.key_fields()?;
+        Ok(StreamHeader {
+            chunk_size,
+            key,
+            key_version,
+            wrapped_key,
+            nonce_prefix: fields.take(PREFIX_SIZE)?.try_into().unwrap(),
+        })
+    }
+}
+
+/// Encrypts everything read from `reader` with a new DEK wrapped by the named
+/// transit key and writes the encrypted stream to `writer`
+///
+/// Returns the number of plaintext bytes encrypted.
+#[instrument(skip(client, reader, writer), err)]
+pub async fn encrypt<R, W>(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    mut reader: R,
+    mut writer: W,
+    opts: &StreamOptions,
+) -> Result<u64, ClientError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    if opts.chunk_size == 0 || opts.chunk_size > MAX_CHUNK_SIZE {
+        return Err(stream_error("invalid chunk size"));
+    }
+
+    let key = dek::generate(client, mount, name, opts.context.as_deref(), stream_error).await?;
+    let header = StreamHeader {
+        chunk_size: opts.chunk_size,
+        key: name.to_string(),
+        key_version: key.version,
+        wrapped_key: key.wrapped,
+        nonce_prefix: rand::random(),
+    };
+    let header_bytes = header.to_bytes()?;
+    write(&mut writer, &header_bytes).await?;
+
+    let cipher = Aes256Gcm::new(&key.key.into());
+    let chunk_size = opts.chunk_size as usize;
+    let mut current = vec![0u8; chunk_size];
+    let mut next = vec![0u8; chunk_size];
+    let mut len = read_full(&mut reader, &mut current).await?;
+    let mut counter = 0u32;
+    let mut total = 0u64;
+    loop {
+        // A full chunk is only the final one if nothing follows it
+        let next_len = match len == chunk_size {
+            true => read_full(&mut reader, &mut next).await?,
+            false => 0,
+        };
+        let last = next_len == 0;
+        let ciphertext = cipher
+            .encrypt(
+                &nonce(&header.nonce_prefix, counter, last),
+                Payload {
+                    msg: &current[..len],
+                    aad: &header_bytes,
+                },
+            )
+            .map_err(|_| stream_error("encryption failed"))?;
+        write(&mut writer, &ciphertext).await?;
+        total += len as u64;
+        if last {
+            break;
+        }
+
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| stream_error("the stream has too many chunks"))?;
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|e| ClientError::StreamError { source: e })?;
+    Ok(total)
+}
+
+/// Decrypts an encrypted stream read from `reader` and writes the plaintext
+/// to `writer`
+///
+/// Plaintext is written as soon as each chunk is authenticated, so when an
+/// error is returned everything written so far must be discarded.
+///
+/// Returns the number of plaintext bytes decrypted.
+#[instrument(skip(client, reader, writer), err)]
+pub async fn decrypt<R, W>(
+    client: &impl Client,
+    mount: &str,
+    mut reader: R,
+    mut writer: W,
+    opts: &StreamOptions,
+) -> Result<u64, ClientError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let header = StreamHeader::read(&mut reader).await?;
+    let header_bytes = header.to_bytes()?;
+
+    let key = dek::unwrap(
+        client,
+        mount,
+        &header.key,
+        &header.wrapped_key,
+        opts.context.as_deref(),
+        stream_error,
+    )
+    .await?;
+
+    let cipher = Aes256Gcm::new(&key.into());
+    let chunk_size = header.chunk_size as usize + TAG_SIZE;
+    let mut current = vec![0u8; chunk_size];
+    let mut next = vec![0u8; chunk_size];
+    let mut len = read_full(&mut reader, &mut current).await?;
+    let mut counter = 0u32;
+    let mut total = 0u64;
+    loop {
+        if len < TAG_SIZE {
+            return Err(stream_error("the stream is truncated"));
+        }
+        let next_len = match len == chunk_size {
+            true => read_full(&mut reader, &mut next).await?,
+            false => 0,
+        };
+        let last = next_len == 0;
+        let plaintext = cipher
+            .decrypt(
+                &nonce(&header.nonce_prefix, counter, last),
+                Payload {
+                    msg: &current[..len],
+                    aad: &header_bytes,
+                },
+            )
+            .map_err(|_| match last {
+                true => stream_error("the stream is truncated or corrupted"),
+                false => stream_error(&format!("chunk {} is corrupted", counter)),
+            })?;
+        write(&mut writer, &plaintext).await?;
+        total += plaintext.len() as u64;
+        if last {
+            break;
+        }
+
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| stream_error("the stream has too many chunks"))?;
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|e| ClientError::StreamError { source: e })?;
+    Ok(total)
+}
+
+/// Encrypts the file at `src` into a new file at `dst`
+///
+/// The encrypted stream is written to a temporary file which is renamed to
+/// `dst` once complete, so `dst` is left untouched if encryption fails.
+///
+/// See [encrypt]
+#[instrument(skip(client, src, dst), err)]
+pub async fn encrypt_file(
+    client: &impl Client,
+    mount: &str,
+    name: &str,
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    opts: &StreamOptions,
+) -> Result<u64, ClientError> {
+    let reader = open(src.as_ref()).await?;
+    let tmp = tmp_path(dst.as_ref());
+    let mut writer = create(&tmp).await?;
+    let res = encrypt(client, mount, name, reader, &mut writer, opts).await;
+    persist(res, writer, &tmp, dst.as_ref()).await
+}
+
+/// Decrypts the file at `src` into a new file at `dst`
+///
+/// The plaintext is written to a temporary file which is renamed to `dst`
+/// once every chunk is authenticated, so `dst` is left untouched if
+/// decryption fails. On Unix, the file is only readable by its owner.
+///
+/// See [decrypt]
+#[instrument(skip(client, src, dst), err)]
+pub async fn decrypt_file(
+    client: &impl Client,
+    mount: &str,
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    opts: &StreamOptions,
+) -> Result<u64, ClientError> {
+    let reader = open(src.as_ref()).await?;
+    let tmp = tmp_path(dst.as_ref());
+    let mut writer = create(&tmp).await?;
+    let res = decrypt(client, mount, reader, &mut writer, opts).await;
+    persist(res, writer, &tmp, dst.as_ref()).await
+}
+
+type FileWriter = Compat<tokio::io::BufWriter<tokio::fs::File>>;
+
+async fn open(path: &Path) -> Result<impl AsyncRead + Unpin, ClientError> {
+    tokio::fs::File::open(path)
+        .await
+        .map(|f| tokio::io::BufReader::new(f).compat())
+        .map_err(|e| ClientError::FileReadError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+        })
+}
+
+/// Creates a new file which, as it may hold plaintext, is only readable by
+/// its owner on Unix
+async fn create(path: &Path) -> Result<FileWriter, ClientError> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+        .open(path)
+        .await
+        .map(|f| tokio::io::BufWriter::new(f).compat_write())
+        .map_err(|e| ClientError::FileWriteError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+        })
+}
+
+/// Returns a temporary path next to `dst`
+fn tmp_path(dst: &Path) -> PathBuf {
+    let name = dst
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    dst.with_file_name(format!(".{}.{:x}.tmp", name, rand::random::<u64>()))
+}
+
+/// Syncs and renames the temporary file to `dst` if `res` is a success and
+/// removes it otherwise
+async fn persist(
+    res: Result<u64, ClientError>,
+    writer: FileWriter,
+    tmp: &Path,
+    dst: &Path,
+) -> Result<u64, ClientError> {
+    let res = match res {
+        Ok(total) => sync_and_rename(writer, tmp, dst)
+            .await
+            .map(|_| total)
+            .map_err(|e| ClientError::FileWriteError {
+                source: e,
+                path: dst.to_string_lossy().to_string(),
+            }),
+        Err(e) => Err(e),
+    };
+    if res.is_err() {
+        let _ = tokio::fs::remove_file(tmp).await;
+    }
+    res
+}
+
+async fn sync_and_rename(writer: FileWriter, tmp: &Path, dst: &Path) -> std::io::Result<()> {
+    writer.into_inner().into_inner().sync_all().await?;
+    tokio::fs::rename(tmp, dst).await
+}
+
+/// Fills `buf` unless the end of the stream is reached first, returning the
+/// number of bytes read
+async fn read_full<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<usize, ClientError> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]).await {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(ClientError::StreamError { source: e }),
+        }
+    }
+    Ok(read)
+}
+
+async fn read_header_bytes<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<(), ClientError> {
+    match read_full(reader, buf).await? == buf.len() {
+        true => Ok(()),
+        false => Err(stream_error("the header is truncated")),
+    }
+}
+
+/// Reads the next `n` bytes of the header and appends them to `bytes`
+async fn read_more_header_bytes<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    bytes: &mut Vec<u8>,
+    n: usize,
+) -> Result<(), ClientError> {
+    let start = bytes.len();
+    bytes.resize(start + n, 0);
+    read_header_bytes(reader, &mut bytes[start..]).await
+}
+
+async fn write<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> Result<(), ClientError> {
+    writer
+        .write_all(buf)
+        .await
+        .map_err(|e| ClientError::StreamError { source: e })
+}
+
+fn nonce(
+    prefix: &[u8; PREFIX_SIZE],
+    counter: u32,
+    last: bool,
+) -> Nonce<aes_gcm::aead::consts::U12> {
+    let mut nonce = [0u8; 12];
+    nonce[..PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[PREFIX_SIZE..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce.into()
+}
+
+fn stream_error(reason: &str) -> ClientError {
+    ClientError::StreamFormatError {
+        reason: reason.to_string(),
+    }
+}
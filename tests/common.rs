@@ -19,7 +19,9 @@ use vaultrs::{
 //use vaultrs_test::VaultServer;
 
 pub const PORT: u32 = 8300;
-pub const VERSION: &str = "1.10.3";
+// The last release published as the `vault` image, use `vault_at_least` to
+// skip tests of newer endpoints
+pub const VERSION: &str = "1.13.3";
 pub const NGINX_PORT: u32 = 8888;
 pub const NGINX_VERSION: &str = "1.21";
 
@@ -138,6 +140,17 @@ impl VaultServerHelper for VaultServer {
     }
 }
 
+// Returns whether the server is at least Vault `major`.`minor`.
+#[allow(dead_code)]
+pub async fn vault_at_least(client: &impl Client, major: u64, minor: u64) -> bool {
+    let health = vaultrs::sys::health(client).await.unwrap();
+    let mut parts = health
+        .version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|p| p.parse::<u64>().unwrap_or(0));
+    (parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (major, minor)
+}
+
 // Sets up a new test.
 #[allow(dead_code)]
 pub fn new_test() -> Test {
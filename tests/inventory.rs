@@ -0,0 +1,188 @@
+#[macro_use]
+extern crate tracing;
+
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::{VaultServer, VaultServerHelper};
+use test_log::test;
+use vaultrs::api::pki::requests::{
+    GenerateCertificateRequest, GenerateRootRequest, SetRoleRequest,
+};
+use vaultrs::api::sys::requests::EnableEngineDataConfigBuilder;
+use vaultrs::client::Client;
+use vaultrs::error::ClientError;
+use vaultrs::pki::cert;
+use vaultrs::pki::inventory::{self, InventoryEntry, InventoryOptions, InventoryOptionsBuilder};
+use vaultrs::pki::x509::Certificate;
+
+#[test]
+fn test() {
+    let test = common::new_test();
+    test.run(|instance| async move {
+        let server: VaultServer = instance.server();
+        let client = server.client();
+        setup(&server, &client).await.unwrap();
+
+        test_collect(&client).await;
+        test_filter(&client).await;
+        test_empty(&server, &client).await;
+    });
+}
+
+#[test]
+fn test_entry() {
+    let entry = entry();
+    assert_eq!(
+        entry.serial_number,
+        "1e:1d:ad:de:a3:f3:da:16:63:9a:64:d0:87:31:fe:56"
+    );
+    assert_eq!(entry.common_name.as_deref(), Some("test.com"));
+    assert!(entry.is_expired_at(SystemTime::now()));
+    assert!(entry.expires_in().is_none());
+    assert!(!entry.is_revoked());
+
+    let opts = InventoryOptions::default();
+    assert!(opts.matches(&entry, SystemTime::now()));
+    let opts = InventoryOptions {
+        include_expired: false,
+        ..Default::default()
+    };
+    assert!(!opts.matches(&entry, SystemTime::now()));
+    let opts = InventoryOptions {
+        expiring_within: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    assert!(opts.matches(&entry, entry.not_after - Duration::from_secs(30)));
+    assert!(!opts.matches(&entry, entry.not_before));
+}
+
+#[test]
+fn test_export() {
+    let mut entry = entry();
+    entry.dns_names = vec!["a.test.com".into(), "b.test.com".into()];
+    entry.role = Some("web, internal".into());
+    entry.revocation_time = Some(entry.not_before);
+
+    let csv = inventory::to_csv(&[entry.clone()]);
+    let mut lines = csv.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("serial_number,common_name,"));
+    let row = lines.next().unwrap();
+    assert!(row.contains(",test.com,CN=test.com,CN=test.com,a.test.com b.test.com,"));
+    assert!(row.contains(",2021-08-16T23:53:22Z,2021-09-17T23:53:22Z,true,"));
+    assert!(row.ends_with(",2021-08-16T23:53:22Z,\"web, internal\""));
+    assert!(lines.next().is_none());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&inventory::to_json(&[entry]).unwrap()).unwrap();
+    assert_eq!(json[0]["common_name"], "test.com");
+    assert_eq!(json[0]["not_after"], "2021-09-17T23:53:22Z");
+    assert_eq!(json[0]["revocation_time"], "2021-08-16T23:53:22Z");
+    assert_eq!(json[0]["dns_names"][1], "b.test.com");
+}
+
+async fn test_collect(client: &impl Client) {
+    let serial = issue(client, "short.test.com", "1h").await;
+    issue(client, "long.test.com", "720h").await;
+    cert::revoke(client, "pki_test", &serial).await.unwrap();
+
+    let entries = inventory::collect(client, "pki_test", &InventoryOptions::default())
+        .await
+        .unwrap();
+    // The root CA is listed along with the issued certificates
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].common_name.as_deref(), Some("short.test.com"));
+    assert_eq!(entries[0].dns_names, vec!["short.test.com"]);
+    assert!(entries[0].is_revoked());
+    assert_eq!(entries[1].common_name.as_deref(), Some("long.test.com"));
+    assert!(entries[2].is_ca);
+}
+
+async fn test_filter(client: &impl Client) {
+    let opts = InventoryOptionsBuilder::default()
+        .expiring_within(Duration::from_secs(24 * 60 * 60))
+        .include_revoked(false)
+        .concurrency(1usize)
+        .build()
+        .unwrap();
+    let entries = inventory::collect(client, "pki_test", &opts).await.unwrap();
+    assert!(entries.is_empty());
+
+    let opts = InventoryOptionsBuilder::default()
+        .expiring_within(Duration::from_secs(24 * 60 * 60))
+        .build()
+        .unwrap();
+    let entries = inventory::collect(client, "pki_test", &opts).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].common_name.as_deref(), Some("short.test.com"));
+}
+
+async fn test_empty(server: &VaultServer, client: &impl Client) {
+    server
+        .mount_secret(client, "pki_empty", "pki")
+        .await
+        .unwrap();
+    let entries = inventory::collect(client, "pki_empty", &InventoryOptions::default())
+        .await
+        .unwrap();
+    assert!(entries.is_empty());
+}
+
+async fn issue(client: &impl Client, common_name: &str, ttl: &str) -> String {
+    cert::generate(
+        client,
+        "pki_test",
+        "test",
+        Some(
+            GenerateCertificateRequest::builder()
+                .common_name(common_name)
+                .ttl(ttl),
+        ),
+    )
+    .await
+    .unwrap()
+    .serial_number
+}
+
+fn entry() -> InventoryEntry {
+    let pem = std::fs::read_to_string("tests/files/root_ca.crt").unwrap();
+    InventoryEntry::new(&Certificate::from_pem(&pem).unwrap(), None)
+}
+
+async fn setup(server: &VaultServer, client: &impl Client) -> Result<(), ClientError> {
+    debug!("setting up PKI engine");
+
+    let config = EnableEngineDataConfigBuilder::default()
+        .max_lease_ttl("87600h")
+        .build()
+        .unwrap();
+    server
+        .mount_secret_with_config(client, "pki_test", "pki", config)
+        .await?;
+    cert::ca::generate(
+        client,
+        "pki_test",
+        "internal",
+        Some(
+            GenerateRootRequest::builder()
+                .common_name("Test")
+                .ttl("87600h"),
+        ),
+    )
+    .await?;
+    vaultrs::pki::role::set(
+        client,
+        "pki_test",
+        "test",
+        Some(
+            SetRoleRequest::builder()
+                .allow_any_name(true)
+                .max_ttl(8760u64 * 60 * 60),
+        ),
+    )
+    .await
+}
@@ -27,6 +27,8 @@ fn test() {
         test_set(&client, &endpoint).await;
         test_set_with_compare_and_swap(&client, &endpoint).await;
         test_set_metadata(&client, &endpoint).await;
+        #[cfg(feature = "streams")]
+        test_watch(&client, &endpoint).await;
 
         // Test delete
         test_delete_latest(&client, &endpoint).await;
@@ -44,6 +46,12 @@ fn test() {
         // Test config
         crate::config::test_set(&client, &endpoint).await;
         crate::config::test_read(&client, &endpoint).await;
+
+        // Test migrate
+        crate::migrate::test_migrate(&server, &client).await;
+
+        // Test maintenance
+        crate::maintenance::test_apply(&client, &endpoint).await;
     });
 }
 
@@ -141,6 +149,46 @@ async fn test_set_metadata(client: &impl Client, endpoint: &SecretEndpoint) {
     assert!(res.is_ok());
 }
 
+#[cfg(feature = "streams")]
+async fn test_watch(client: &impl Client, endpoint: &SecretEndpoint) {
+    use futures::{pin_mut, StreamExt};
+    use std::time::Duration;
+    use vaultrs::kv2::SecretEvent;
+
+    let stream = kv2::watch::<TestSecret>(
+        client,
+        endpoint.path.as_str(),
+        endpoint.name.as_str(),
+        Duration::from_millis(100),
+    );
+    pin_mut!(stream);
+
+    let res = stream.next().await.unwrap();
+    assert!(res.is_ok());
+    let version = match res.unwrap() {
+        SecretEvent::Changed { version, data, .. } => {
+            assert_eq!(data.key, endpoint.secret.key);
+            version
+        }
+        e => panic!("Expected a changed event, got {:?}", e),
+    };
+
+    create(client, endpoint).await.unwrap();
+    let res = stream.next().await.unwrap();
+    assert!(res.is_ok());
+    match res.unwrap() {
+        SecretEvent::Changed { version: v, .. } => assert_eq!(v, version + 1),
+        e => panic!("Expected a changed event, got {:?}", e),
+    }
+
+    kv2::delete_latest(client, endpoint.path.as_str(), endpoint.name.as_str())
+        .await
+        .unwrap();
+    let res = stream.next().await.unwrap();
+    assert!(matches!(res, Ok(SecretEvent::Removed { .. })));
+    create(client, endpoint).await.unwrap();
+}
+
 async fn test_undelete_versions(client: &impl Client, endpoint: &SecretEndpoint) {
     let res = kv2::undelete_versions(
         client,
@@ -179,6 +227,209 @@ mod config {
     }
 }
 
+mod maintenance {
+    use crate::{Client, SecretEndpoint};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use time::{macros::datetime, OffsetDateTime};
+    use vaultrs::api::kv2::responses::{ReadSecretMetadataResponse, SecretMetadata};
+    use vaultrs::kv2::{
+        self,
+        maintenance::{self, RetentionAction, RetentionPolicy},
+    };
+
+    pub async fn test_apply(client: &impl Client, endpoint: &SecretEndpoint) {
+        let mount = endpoint.path.as_str();
+        for _ in 0..4 {
+            kv2::set(client, mount, "retention/a", &endpoint.secret)
+                .await
+                .unwrap();
+        }
+        kv2::set(client, mount, "retention/nested/b", &endpoint.secret)
+            .await
+            .unwrap();
+
+        let mut policy = RetentionPolicy {
+            keep_last: Some(2),
+            dry_run: true,
+            ..Default::default()
+        };
+        let res = maintenance::apply(client, mount, "retention", &policy).await;
+        assert!(res.is_ok());
+        let report = res.unwrap();
+        assert_eq!(report.secrets.len(), 1);
+        assert_eq!(report.secrets[0].path, "retention/a");
+        assert_eq!(report.secrets[0].versions, vec![1, 2]);
+        let meta = kv2::read_metadata(client, mount, "retention/a")
+            .await
+            .unwrap();
+        assert!(!meta.versions["1"].destroyed);
+
+        policy.dry_run = false;
+        let res = maintenance::apply(client, mount, "retention", &policy).await;
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().total_versions(), 2);
+        let meta = kv2::read_metadata(client, mount, "retention/a")
+            .await
+            .unwrap();
+        assert!(meta.versions["1"].destroyed);
+        assert!(meta.versions["2"].destroyed);
+        assert!(!meta.versions["3"].destroyed);
+
+        let res = maintenance::apply(client, mount, "retention", &policy).await;
+        assert!(res.is_ok());
+        assert!(res.unwrap().secrets.is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let version = |created: &str, destroyed: bool, deleted: bool| SecretMetadata {
+            created_time: created.to_string(),
+            deletion_time: match deleted {
+                true => "2023-01-10T00:00:00Z".to_string(),
+                false => String::new(),
+            },
+            destroyed,
+        };
+        let meta = ReadSecretMetadataResponse {
+            cas_required: false,
+            created_time: "2023-01-01T00:00:00Z".to_string(),
+            current_version: 5,
+            delete_version_after: "0s".to_string(),
+            max_versions: 0,
+            oldest_version: 0,
+            updated_time: "2023-01-05T00:00:00Z".to_string(),
+            versions: HashMap::from([
+                (
+                    "1".to_string(),
+                    version("2023-01-01T00:00:00Z", true, false),
+                ),
+                (
+                    "2".to_string(),
+                    version("2023-01-02T00:00:00Z", false, true),
+                ),
+                (
+                    "3".to_string(),
+                    version("2023-01-03T00:00:00Z", false, false),
+                ),
+                (
+                    "4".to_string(),
+                    version("2023-01-04T00:00:00Z", false, false),
+                ),
+                (
+                    "5".to_string(),
+                    version("2023-01-05T00:00:00Z", false, false),
+                ),
+            ]),
+        };
+        let now: OffsetDateTime = datetime!(2023-01-06 0:00 UTC);
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let policy = RetentionPolicy {
+            keep_last: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(policy.select(&meta, now), vec![2]);
+
+        let policy = RetentionPolicy {
+            keep_last: Some(3),
+            action: RetentionAction::Delete,
+            ..Default::default()
+        };
+        assert!(policy.select(&meta, now).is_empty());
+
+        let policy = RetentionPolicy {
+            max_age: Some(day * 2),
+            ..Default::default()
+        };
+        assert_eq!(policy.select(&meta, now), vec![2, 3]);
+
+        // Recent versions are kept even when they're too old
+        let policy = RetentionPolicy {
+            keep_last: Some(3),
+            max_age: Some(day * 2),
+            ..Default::default()
+        };
+        assert_eq!(policy.select(&meta, now), vec![2]);
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        assert_eq!(policy.select(&meta, now), vec![2, 3, 4]);
+    }
+}
+
+mod migrate {
+    use crate::{Client, VaultServer, VaultServerHelper};
+    use std::collections::HashMap;
+    use vaultrs::kv2::migrate::{self, MigrateOptions, MigrationStatus};
+    use vaultrs::{kv1, kv2};
+
+    pub async fn test_migrate(server: &VaultServer, client: &impl Client) {
+        let from = "kv1_migrate";
+        let to = "kv2_migrate";
+        server.mount_secret(client, from, "kv").await.unwrap();
+        server.mount_secret(client, to, "kv-v2").await.unwrap();
+        for path in ["app/db", "app/nested/api", "other"] {
+            let data = HashMap::from([("path", path)]);
+            kv1::set(client, from, path, &data).await.unwrap();
+        }
+
+        // Dry run
+        let opts = MigrateOptions {
+            dry_run: true,
+            max_versions: Some(5),
+            ..Default::default()
+        };
+        let res = migrate::migrate(client, from, to, "app", opts).await;
+        assert!(res.is_ok());
+        let report = res.unwrap();
+        let paths: Vec<&str> = report.secrets.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["app/db", "app/nested/api"]);
+        assert_eq!(report.config.unwrap().max_versions, Some(5));
+        assert!(report
+            .secrets
+            .iter()
+            .all(|s| matches!(s.status, MigrationStatus::Planned)));
+        assert!(kv2::list(client, to, "").await.is_err());
+
+        // A path which is a secret rather than a folder
+        let opts = MigrateOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = migrate::migrate(client, from, to, "app/db", opts)
+            .await
+            .unwrap();
+        assert_eq!(report.secrets.len(), 1);
+        assert_eq!(report.secrets[0].path, "app/db");
+        assert!(report.config.is_none());
+
+        // Migrate everything
+        let opts = MigrateOptions {
+            verify: true,
+            max_versions: Some(5),
+            ..Default::default()
+        };
+        let res = migrate::migrate(client, from, to, "", opts).await;
+        assert!(res.is_ok());
+        let report = res.unwrap();
+        assert!(report.is_success());
+        assert_eq!(report.secrets.len(), 3);
+        assert!(report
+            .secrets
+            .iter()
+            .all(|s| matches!(s.status, MigrationStatus::Verified { version: 1 })));
+
+        let secret: HashMap<String, String> =
+            kv2::read(client, to, "app/nested/api").await.unwrap();
+        assert_eq!(secret["path"], "app/nested/api");
+        let config = kv2::config::read(client, to).await.unwrap();
+        assert_eq!(config.max_versions, 5);
+    }
+}
+
 #[derive(Debug)]
 pub struct SecretEndpoint {
     pub path: String,
@@ -0,0 +1,243 @@
+#[macro_use]
+extern crate tracing;
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{VaultServer, VaultServerHelper};
+use rcgen::{
+    date_time_ymd, CertificateParams, CertificateRevocationList, CertificateRevocationListParams,
+    IsCa, KeyIdMethod, KeyUsagePurpose, RevokedCertParams, SerialNumber, PKCS_ECDSA_P256_SHA256,
+};
+use test_log::test;
+use vaultrs::api::pki::requests::{
+    GenerateCertificateRequest, GenerateRootRequest, SetRoleRequest,
+};
+use vaultrs::api::sys::requests::EnableEngineDataConfigBuilder;
+use vaultrs::client::{Client, VaultClient};
+use vaultrs::error::ClientError;
+use vaultrs::pki::cert;
+use vaultrs::pki::revocation::{Crl, RevocationChecker, RevocationOptions, RevocationStatus};
+use vaultrs::pki::x509::Certificate;
+
+#[test]
+fn test() {
+    let test = common::new_test();
+    test.run(|instance| async move {
+        let server: VaultServer = instance.server();
+        let client = server.client();
+        setup(&server, &client).await.unwrap();
+
+        let checker = RevocationChecker::start(
+            Arc::new(server.client()),
+            "pki_test",
+            RevocationOptions::default(),
+        )
+        .await
+        .unwrap();
+        test_check(&client, &checker).await;
+        test_check_ocsp(&client, &checker).await;
+        #[cfg(feature = "cert-manager")]
+        test_verifier(&client, &checker).await;
+    });
+}
+
+#[test]
+fn test_crl() {
+    let ca = ca("Test CA");
+    let issuer = Certificate::from_der(&ca.serialize_der().unwrap()).unwrap();
+    let der = crl(&ca, &[0x1e, 0x1d, 0xad], date_time_ymd(2030, 1, 1))
+        .serialize_der_with_signer(&ca)
+        .unwrap();
+
+    let crl = Crl::from_der(&der, &issuer).unwrap();
+    assert_eq!(crl.len(), 1);
+    assert_eq!(crl.this_update, timestamp(1577836800));
+    assert_eq!(crl.revocation_time("1e:1d:ad"), Some(timestamp(1577836800)));
+    assert_eq!(
+        crl.revocation_time("00-1e-1d-ad"),
+        Some(timestamp(1577836800))
+    );
+    assert!(crl.revocation_time("1e:1d:ae").is_none());
+    assert!(crl.revocation_time("not a serial").is_none());
+    assert!(crl.is_current_at(timestamp(1577836800)));
+    assert!(!crl.is_current_at(timestamp(1893456000)));
+
+    let other = Certificate::from_der(&self::ca("Other CA").serialize_der().unwrap()).unwrap();
+    assert!(matches!(
+        Crl::from_der(&der, &other),
+        Err(ClientError::CertificateError { .. })
+    ));
+}
+
+#[test]
+fn test_crl_pem_and_merge() {
+    let ca = ca("Test CA");
+    let issuer = Certificate::from_der(&ca.serialize_der().unwrap()).unwrap();
+    let pem = crl(&ca, &[0x01], date_time_ymd(2030, 1, 1))
+        .serialize_pem_with_signer(&ca)
+        .unwrap();
+    let delta = crl(&ca, &[0x02], date_time_ymd(2025, 1, 1))
+        .serialize_der_with_signer(&ca)
+        .unwrap();
+
+    let mut crl = Crl::from_pem(&pem, &issuer).unwrap();
+    assert!(crl.revocation_time("02").is_none());
+
+    crl.merge(Crl::from_der(&delta, &issuer).unwrap());
+    assert_eq!(crl.len(), 2);
+    assert!(crl.revocation_time("01").is_some());
+    assert!(crl.revocation_time("02").is_some());
+    assert_eq!(crl.next_update, Some(timestamp(1735689600)));
+}
+
+async fn test_check(client: &impl Client, checker: &RevocationChecker<VaultClient>) {
+    let serial = issue(client).await;
+    assert!(checker.crl().is_some());
+    assert_eq!(
+        checker.check(&serial).await.unwrap(),
+        RevocationStatus::Good
+    );
+
+    cert::revoke(client, "pki_test", &serial).await.unwrap();
+    assert_eq!(checker.check_cached(&serial), Some(RevocationStatus::Good));
+    checker.refresh().await.unwrap();
+    assert!(matches!(
+        checker.check(&serial).await.unwrap(),
+        RevocationStatus::Revoked { .. }
+    ));
+}
+
+async fn test_check_ocsp(client: &impl Client, checker: &RevocationChecker<VaultClient>) {
+    let serial = issue(client).await;
+    assert_eq!(
+        checker.check_ocsp(&serial).await.unwrap(),
+        RevocationStatus::Good
+    );
+
+    cert::revoke(client, "pki_test", &serial).await.unwrap();
+    assert!(matches!(
+        checker.check_ocsp(&serial).await.unwrap(),
+        RevocationStatus::Revoked { .. }
+    ));
+}
+
+#[cfg(feature = "cert-manager")]
+async fn test_verifier(client: &impl Client, checker: &RevocationChecker<VaultClient>) {
+    use rustls::server::{AllowAnyAuthenticatedClient, ClientCertVerifier};
+    use vaultrs::pki::revocation::RevocationVerifier;
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots
+        .add(&rustls::Certificate(checker.issuer().der.clone()))
+        .unwrap();
+    let verifier =
+        RevocationVerifier::new(AllowAnyAuthenticatedClient::new(roots), checker.clone());
+
+    let resp = cert::generate(
+        client,
+        "pki_test",
+        "test",
+        Some(GenerateCertificateRequest::builder().common_name("test.com")),
+    )
+    .await
+    .unwrap();
+    let cert = rustls::Certificate(Certificate::from_pem(&resp.certificate).unwrap().der);
+    checker.refresh().await.unwrap();
+    assert!(verifier
+        .verify_client_cert(&cert, &[], SystemTime::now())
+        .is_ok());
+
+    cert::revoke(client, "pki_test", &resp.serial_number)
+        .await
+        .unwrap();
+    checker.refresh().await.unwrap();
+    assert!(matches!(
+        verifier.verify_client_cert(&cert, &[], SystemTime::now()),
+        Err(rustls::Error::InvalidCertificate(
+            rustls::CertificateError::Revoked
+        ))
+    ));
+}
+
+async fn issue(client: &impl Client) -> String {
+    cert::generate(
+        client,
+        "pki_test",
+        "test",
+        Some(GenerateCertificateRequest::builder().common_name("test.com")),
+    )
+    .await
+    .unwrap()
+    .serial_number
+}
+
+fn ca(name: &str) -> rcgen::Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.alg = &PKCS_ECDSA_P256_SHA256;
+    params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, name);
+    rcgen::Certificate::from_params(params).unwrap()
+}
+
+fn crl(
+    ca: &rcgen::Certificate,
+    serial: &[u8],
+    next_update: time::OffsetDateTime,
+) -> CertificateRevocationList {
+    CertificateRevocationList::from_params(CertificateRevocationListParams {
+        this_update: date_time_ymd(2020, 1, 1),
+        next_update,
+        crl_number: SerialNumber::from_slice(&[1]),
+        issuing_distribution_point: None,
+        revoked_certs: vec![RevokedCertParams {
+            serial_number: SerialNumber::from_slice(serial),
+            revocation_time: date_time_ymd(2020, 1, 1),
+            reason_code: None,
+            invalidity_date: None,
+        }],
+        alg: ca.get_params().alg,
+        key_identifier_method: KeyIdMethod::Sha256,
+    })
+    .unwrap()
+}
+
+fn timestamp(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+async fn setup(server: &VaultServer, client: &impl Client) -> Result<(), ClientError> {
+    debug!("setting up PKI engine");
+
+    let config = EnableEngineDataConfigBuilder::default()
+        .max_lease_ttl("87600h")
+        .build()
+        .unwrap();
+    server
+        .mount_secret_with_config(client, "pki_test", "pki", config)
+        .await?;
+    cert::ca::generate(
+        client,
+        "pki_test",
+        "internal",
+        Some(
+            GenerateRootRequest::builder()
+                .common_name("Test")
+                .ttl("87600h"),
+        ),
+    )
+    .await?;
+    vaultrs::pki::role::set(
+        client,
+        "pki_test",
+        "test",
+        Some(SetRoleRequest::builder().allow_any_name(true)),
+    )
+    .await
+}
@@ -0,0 +1,222 @@
+#[macro_use]
+extern crate tracing;
+
+mod common;
+
+use common::{VaultServer, VaultServerHelper};
+use test_log::test;
+use vaultrs::api::pki::requests::GenerateRootRequest;
+use vaultrs::api::sys::requests::EnableEngineDataConfigBuilder;
+use vaultrs::client::Client;
+use vaultrs::error::ClientError;
+use vaultrs::pki::rotation::{
+    self, RotationKind, RotationOptions, RotationOptionsBuilder, RotationState, RotationStep,
+};
+use vaultrs::pki::{cert, issuer};
+
+#[test]
+fn test() {
+    let test = common::new_test();
+    test.run(|instance| async move {
+        let server: VaultServer = instance.server();
+        let client = server.client();
+        setup(&server, &client).await.unwrap();
+
+        test_rotate_root(&client).await;
+        test_rotate_intermediate(&client).await;
+        test_resume(&client).await;
+    });
+}
+
+#[test]
+fn test_steps() {
+    let state = RotationState {
+        old_issuer: Some("old".into()),
+        ..Default::default()
+    };
+    let opts = RotationOptions::default();
+    assert_eq!(
+        opts.steps(&state),
+        vec![
+            RotationStep::Generate,
+            RotationStep::CrossSign,
+            RotationStep::ImportCrossSigned,
+            RotationStep::SetDefault,
+            RotationStep::Done,
+        ]
+    );
+    assert_eq!(
+        opts.steps(&RotationState::default()),
+        vec![
+            RotationStep::Generate,
+            RotationStep::SetDefault,
+            RotationStep::Done
+        ]
+    );
+
+    let opts = RotationOptions {
+        kind: RotationKind::Intermediate {
+            parent_mount: "pki".into(),
+            parent_issuer: "default".into(),
+        },
+        cross_sign: false,
+        set_default: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        opts.steps(&state),
+        vec![
+            RotationStep::Generate,
+            RotationStep::SignWithParent,
+            RotationStep::Import,
+            RotationStep::Done,
+        ]
+    );
+
+    let state = RotationState {
+        step: RotationStep::ImportCrossSigned,
+        steps: RotationOptions::default().steps(&state),
+        ..state
+    };
+    let json = serde_json::to_value(&state).unwrap();
+    assert_eq!(json["step"], "import_cross_signed");
+    assert_eq!(
+        serde_json::from_value::<RotationState>(json).unwrap(),
+        state
+    );
+}
+
+async fn test_rotate_root(client: &impl Client) {
+    let mut state = rotation::prepare(client, "pki_root").await.unwrap();
+    let old_issuer = state.old_issuer.clone().unwrap();
+
+    let opts = RotationOptionsBuilder::default()
+        .common_name("Root B")
+        .issuer_name("root-b")
+        .ttl("8760h")
+        .build()
+        .unwrap();
+    rotation::run(client, &mut state, &opts).await.unwrap();
+    assert!(state.is_done());
+
+    let new_issuer = state.new_issuer.clone().unwrap();
+    assert_ne!(new_issuer, old_issuer);
+    let default = issuer::read_default(client, "pki_root").await.unwrap();
+    assert_eq!(default.default, new_issuer);
+
+    // The cross-signed certificate shares the key of the new root
+    let cross_signed = issuer::read(
+        client,
+        "pki_root",
+        state.cross_signed_issuer.as_deref().unwrap(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Some(cross_signed.key_id), state.new_key);
+
+    // The cross-signed certificate uses the TTL of the new root as well
+    #[cfg(feature = "x509")]
+    {
+        use std::time::Duration;
+        use vaultrs::pki::x509::Certificate;
+
+        let certificate = Certificate::from_pem(&cross_signed.certificate).unwrap();
+        let expires_in = certificate.expires_in().unwrap();
+        let ttl = Duration::from_secs(8760 * 3600);
+        assert!(expires_in > ttl - Duration::from_secs(3600));
+        assert!(expires_in <= ttl);
+    }
+}
+
+async fn test_rotate_intermediate(client: &impl Client) {
+    let opts = RotationOptionsBuilder::default()
+        .kind(RotationKind::Intermediate {
+            parent_mount: "pki_root".into(),
+            parent_issuer: "default".into(),
+        })
+        .common_name("Intermediate")
+        .build()
+        .unwrap();
+
+    // A mount without an issuer gets its first intermediate
+    let mut state = rotation::prepare(client, "pki_int").await.unwrap();
+    assert!(state.old_issuer.is_none());
+    rotation::run(client, &mut state, &opts).await.unwrap();
+    assert!(state.cross_signed_issuer.is_none());
+    let first = state.new_issuer.clone().unwrap();
+
+    let mut state = rotation::prepare(client, "pki_int").await.unwrap();
+    assert_eq!(state.old_issuer.as_deref(), Some(first.as_str()));
+    rotation::run(client, &mut state, &opts).await.unwrap();
+    assert!(state.cross_signed_issuer.is_some());
+    let default = issuer::read_default(client, "pki_int").await.unwrap();
+    assert_eq!(Some(default.default), state.new_issuer);
+}
+
+async fn test_resume(client: &impl Client) {
+    let opts = RotationOptionsBuilder::default()
+        .common_name("Root C")
+        .build()
+        .unwrap();
+
+    // The state can be persisted between steps
+    let mut state = rotation::prepare(client, "pki_root").await.unwrap();
+    let mut steps = vec![rotation::step(client, &mut state, &opts).await.unwrap()];
+
+    // Resuming with options planning other steps fails instead of skipping
+    let other = RotationOptions {
+        cross_sign: false,
+        ..opts.clone()
+    };
+    let resumed = state.clone();
+    assert!(matches!(
+        rotation::step(client, &mut state, &other).await,
+        Err(ClientError::RotationError { .. })
+    ));
+    assert_eq!(state, resumed);
+
+    while !state.is_done() {
+        steps.push(rotation::step(client, &mut state, &opts).await.unwrap());
+        state = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+    }
+    assert_eq!(
+        steps,
+        vec![
+            RotationStep::CrossSign,
+            RotationStep::ImportCrossSigned,
+            RotationStep::SetDefault,
+            RotationStep::Done,
+        ]
+    );
+
+    // Running a finished rotation does nothing
+    let finished = state.clone();
+    rotation::run(client, &mut state, &opts).await.unwrap();
+    assert_eq!(state, finished);
+}
+
+async fn setup(server: &VaultServer, client: &impl Client) -> Result<(), ClientError> {
+    debug!("setting up PKI engines");
+
+    let config = EnableEngineDataConfigBuilder::default()
+        .max_lease_ttl("87600h")
+        .build()
+        .unwrap();
+    server
+        .mount_secret_with_config(client, "pki_root", "pki", config)
+        .await?;
+    server.mount_secret(client, "pki_int", "pki").await?;
+    cert::ca::generate(
+        client,
+        "pki_root",
+        "internal",
+        Some(
+            GenerateRootRequest::builder()
+                .common_name("Root A")
+                .issuer_name("root-a")
+                .ttl("87600h"),
+        ),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#[macro_use]
+extern crate tracing;
+
+mod common;
+
+use common::{VaultServer, VaultServerHelper};
+use serde_json::json;
+use std::collections::HashMap;
+use test_log::test;
+use vaultrs::client::Client;
+use vaultrs::error::ClientError;
+use vaultrs::secret::VaultSecret;
+use vaultrs::{kv1, kv2};
+
+#[test]
+fn test() {
+    let test = common::new_test();
+    test.run(|instance| async move {
+        let server: VaultServer = instance.server();
+        let client = server.client();
+        setup(&server, &client).await.unwrap();
+
+        test_load(&client).await;
+        test_load_missing_key(&client).await;
+        test_load_missing_secret(&client).await;
+        test_load_invalid_key(&client).await;
+    });
+}
+
+#[derive(Debug, VaultSecret)]
+#[vault(mount = "kv2_test", path = "app/db")]
+struct Config {
+    user: String,
+    #[vault(key = "password")]
+    pass: String,
+    port: u16,
+    #[vault(path = "app/features")]
+    beta: Option<bool>,
+    #[vault(path = "app/features")]
+    missing: Option<String>,
+    #[vault(path = "app/absent")]
+    absent: Option<String>,
+    #[vault(mount = "kv1_test", path = "legacy", key = "api_key", version = 1)]
+    api_key: String,
+}
+
+#[derive(Debug, VaultSecret)]
+#[vault(mount = "kv2_test", path = "app/db")]
+struct MissingKey {
+    #[allow(dead_code)]
+    token: String,
+}
+
+#[derive(Debug, VaultSecret)]
+#[vault(mount = "kv2_test", path = "app/absent")]
+struct MissingSecret {
+    #[allow(dead_code)]
+    token: String,
+    #[allow(dead_code)]
+    other: Option<String>,
+}
+
+#[derive(Debug, VaultSecret)]
+#[vault(mount = "kv2_test", path = "app/db")]
+struct InvalidKey {
+    #[allow(dead_code)]
+    user: u64,
+}
+
+async fn test_load(client: &impl Client) {
+    let res = Config::load(client).await;
+    assert!(res.is_ok());
+
+    let config = res.unwrap();
+    assert_eq!(config.user, "admin");
+    assert_eq!(config.pass, "supersecret");
+    assert_eq!(config.port, 5432);
+    assert_eq!(config.beta, Some(true));
+    assert_eq!(config.missing, None);
+    assert_eq!(config.absent, None);
+    assert_eq!(config.api_key, "abc123");
+}
+
+async fn test_load_missing_key(client: &impl Client) {
+    let res = MissingKey::load(client).await;
+    assert!(matches!(
+        res.unwrap_err(),
+        ClientError::SecretKeyMissingError { key, .. } if key == "token"
+    ));
+}
+
+async fn test_load_missing_secret(client: &impl Client) {
+    // The secret is also read by a required field so it must exist
+    let res = MissingSecret::load(client).await;
+    assert!(matches!(
+        res.unwrap_err(),
+        ClientError::APIError { code: 404, .. }
+    ));
+}
+
+async fn test_load_invalid_key(client: &impl Client) {
+    let res = InvalidKey::load(client).await;
+    assert!(matches!(
+        res.unwrap_err(),
+        ClientError::SecretKeyParseError { key, .. } if key == "user"
+    ));
+}
+
+async fn setup(server: &VaultServer, client: &impl Client) -> Result<(), ClientError> {
+    debug!("setting up kv engines");
+    server.mount_secret(client, "kv2_test", "kv-v2").await?;
+    server.mount_secret(client, "kv1_test", "kv").await?;
+
+    kv2::set(
+        client,
+        "kv2_test",
+        "app/db",
+        &json!({"user": "admin", "password": "supersecret", "port": 5432}),
+    )
+    .await?;
+    kv2::set(client, "kv2_test", "app/features", &json!({"beta": true})).await?;
+    kv1::set(
+        client,
+        "kv1_test",
+        "legacy",
+        &HashMap::from([("api_key", "abc123")]),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,246 @@
+#[macro_use]
+extern crate tracing;
+
+mod common;
+
+use common::{VaultServer, VaultServerHelper};
+use serde_json::json;
+use std::collections::HashMap;
+use test_log::test;
+use vaultrs::client::Client;
+use vaultrs::error::ClientError;
+use vaultrs::secret::{KvVersion, SecretLocation};
+use vaultrs::template::{self, ExportFormat, Template};
+use vaultrs::{kv1, kv2};
+
+#[test]
+fn test() {
+    let test = common::new_test();
+    test.run(|instance| async move {
+        let server: VaultServer = instance.server();
+        let client = server.client();
+        setup(&server, &client).await.unwrap();
+
+        test_render(&client).await;
+        test_render_missing_key(&client).await;
+        #[cfg(feature = "fs")]
+        test_render_file(&client).await;
+        test_export(&client).await;
+        #[cfg(feature = "fs")]
+        test_export_file(&client).await;
+    });
+}
+
+#[test]
+fn test_parse() {
+    assert!(Template::parse("no tags").is_ok());
+    assert!(Template::parse(r#"{{ kv2 "kv/app" "key" }}"#).is_ok());
+    assert!(Template::parse(r#"{{kv1 "kv/data/app" "k\"ey"}}"#).is_ok());
+
+    let res = Template::parse("line\n{{ kv2 \"kv/app\" \"key\"");
+    assert!(matches!(
+        res,
+        Err(ClientError::TemplateError { line: 2, .. })
+    ));
+    let res = Template::parse(r#"{{ env "HOME" }}"#);
+    assert!(matches!(
+        res,
+        Err(ClientError::TemplateError { line: 1, .. })
+    ));
+    let res = Template::parse(r#"{{ kv2 "kv/app" }}"#);
+    assert!(matches!(res, Err(ClientError::TemplateError { .. })));
+    let res = Template::parse(r#"{{ kv1 "app" "key" }}"#);
+    assert!(matches!(res, Err(ClientError::TemplateError { .. })));
+    let res = Template::parse(r#"{{ kv2 "" "app" "key" }}"#);
+    assert!(matches!(res, Err(ClientError::TemplateError { .. })));
+}
+
+#[test]
+fn test_parse_locations() {
+    let source = r#"{{ secret "kv/data/app" "a" }}
+{{ secret "team/kv/data/app/db" "b" }}
+{{ secret "legacy/app" "c" }}
+{{ kv2 "kv/app" "d" }}
+{{ kv1 "legacy/data/app" "e" }}
+{{ secret "team/kv" "data/app" "f" }}
+{{ secret "team/legacy" "app" "g" }}
+{{ kv2 "team/kv" "app/db" "h" }}"#;
+    let template = Template::parse(source).unwrap();
+    assert_eq!(
+        template.locations().cloned().collect::<Vec<_>>(),
+        vec![
+            SecretLocation::new(KvVersion::V2, "kv", "app"),
+            SecretLocation::new(KvVersion::V2, "team/kv", "app/db"),
+            SecretLocation::new(KvVersion::V1, "legacy", "app"),
+            SecretLocation::new(KvVersion::V2, "kv", "app"),
+            SecretLocation::new(KvVersion::V1, "legacy", "data/app"),
+            SecretLocation::new(KvVersion::V2, "team/kv", "app"),
+            SecretLocation::new(KvVersion::V1, "team/legacy", "app"),
+            SecretLocation::new(KvVersion::V2, "team/kv", "app/db"),
+        ]
+    );
+}
+
+#[test]
+fn test_format_secret() {
+    let data = json!({"A": "it's", "B": 1}).as_object().unwrap().clone();
+    assert_eq!(
+        template::format_secret(&data, ExportFormat::Dotenv).unwrap(),
+        "A=\"it's\"\nB=\"1\"\n"
+    );
+    assert_eq!(
+        template::format_secret(&data, ExportFormat::Shell).unwrap(),
+        "export A='it'\\''s'\nexport B='1'\n"
+    );
+    assert_eq!(
+        template::format_secret(&data, ExportFormat::Json).unwrap(),
+        "{\n  \"A\": \"it's\",\n  \"B\": 1\n}\n"
+    );
+
+    let data = json!({"not-valid": 1}).as_object().unwrap().clone();
+    assert!(matches!(
+        template::format_secret(&data, ExportFormat::Shell),
+        Err(ClientError::InvalidEnvironmentKeyError { .. })
+    ));
+    assert!(template::format_secret(&data, ExportFormat::Json).is_ok());
+}
+
+async fn test_render(client: &impl Client) {
+    let source = r#"user={{ kv2 "kv2_tpl/app/db" "user" }}
+port={{ secret "kv2_tpl/data/app/db" "port" }}
+key={{ secret "kv1_tpl/legacy" "api_key" }}
+token={{ secret "team/kv2_tpl/data/app" "token" }}
+token={{ kv2 "team/kv2_tpl" "app" "token" }}
+"#;
+    let res = template::render(client, source).await;
+    assert!(res.is_ok());
+    assert_eq!(
+        res.unwrap(),
+        "user=admin\nport=5432\nkey=abc123\ntoken=t0k3n\ntoken=t0k3n\n"
+    );
+}
+
+async fn test_render_missing_key(client: &impl Client) {
+    let res = template::render(client, r#"{{ kv2 "kv2_tpl/app/db" "nope" }}"#).await;
+    assert!(matches!(
+        res,
+        Err(ClientError::SecretKeyMissingError { .. })
+    ));
+}
+
+#[cfg(feature = "fs")]
+async fn test_render_file(client: &impl Client) {
+    use std::fs;
+    use vaultrs::template::WriteOptions;
+
+    let dir = std::env::temp_dir();
+    let source = dir.join("vaultrs_template.tpl");
+    let dest = dir.join("vaultrs_template.out");
+    fs::write(&source, r#"pass={{ kv2 "kv2_tpl/app/db" "password" }}"#).unwrap();
+
+    let res = template::render_file(
+        client,
+        source.to_str().unwrap(),
+        dest.to_str().unwrap(),
+        &WriteOptions::default(),
+    )
+    .await;
+    assert!(res.is_ok());
+    assert_eq!(fs::read_to_string(&dest).unwrap(), "pass=it's \"$ecret\"");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&dest).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+async fn test_export(client: &impl Client) {
+    let location = SecretLocation::new(KvVersion::V2, "kv2_tpl", "app/db");
+
+    let res = template::export(client, &location, ExportFormat::Dotenv).await;
+    assert!(res.is_ok());
+    assert_eq!(
+        res.unwrap(),
+        "password=\"it's \\\"\\$ecret\\\"\"\nport=\"5432\"\nuser=\"admin\"\n"
+    );
+
+    let res = template::export(client, &location, ExportFormat::Shell).await;
+    assert!(res.is_ok());
+    assert_eq!(
+        res.unwrap(),
+        "export password='it'\\''s \"$ecret\"'\nexport port='5432'\nexport user='admin'\n"
+    );
+
+    let res = template::export(client, &location, ExportFormat::Json).await;
+    assert!(res.is_ok());
+    let value: serde_json::Value = serde_json::from_str(&res.unwrap()).unwrap();
+    assert_eq!(value["port"], 5432);
+
+    let location = SecretLocation::new(KvVersion::V2, "kv2_tpl", "app/invalid");
+    let res = template::export(client, &location, ExportFormat::Dotenv).await;
+    assert!(matches!(
+        res,
+        Err(ClientError::InvalidEnvironmentKeyError { .. })
+    ));
+}
+
+#[cfg(feature = "fs")]
+async fn test_export_file(client: &impl Client) {
+    use std::fs;
+    use vaultrs::template::WriteOptions;
+
+    let location = SecretLocation::new(KvVersion::V1, "kv1_tpl", "legacy");
+    let dest = std::env::temp_dir().join("vaultrs_template.env");
+    let opts = WriteOptions { mode: 0o640 };
+
+    let res = template::export_file(
+        client,
+        &location,
+        ExportFormat::Dotenv,
+        dest.to_str().unwrap(),
+        &opts,
+    )
+    .await;
+    assert!(res.is_ok());
+    assert_eq!(fs::read_to_string(&dest).unwrap(), "api_key=\"abc123\"\n");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&dest).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+}
+
+async fn setup(server: &VaultServer, client: &impl Client) -> Result<(), ClientError> {
+    debug!("setting up kv engines");
+    server.mount_secret(client, "kv2_tpl", "kv-v2").await?;
+    server.mount_secret(client, "kv1_tpl", "kv").await?;
+    server.mount_secret(client, "team/kv2_tpl", "kv-v2").await?;
+
+    kv2::set(
+        client,
+        "kv2_tpl",
+        "app/db",
+        &json!({"user": "admin", "password": "it's \"$ecret\"", "port": 5432}),
+    )
+    .await?;
+    kv2::set(
+        client,
+        "kv2_tpl",
+        "app/invalid",
+        &json!({"not-valid": true}),
+    )
+    .await?;
+    kv1::set(
+        client,
+        "kv1_tpl",
+        "legacy",
+        &HashMap::from([("api_key", "abc123")]),
+    )
+    .await?;
+    kv2::set(client, "team/kv2_tpl", "app", &json!({"token": "t0k3n"})).await?;
+    Ok(())
+}
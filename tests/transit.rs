@@ -26,19 +26,204 @@ fn test() {
         key::test_export(&endpoint).await;
         key::test_backup_and_restore(&endpoint).await;
         key::test_trim(&endpoint).await;
+        #[cfg(feature = "transit-crypto")]
+        key::test_import(&endpoint).await;
 
         data::test_encrypt_and_rewrap_and_decrypt(&endpoint).await;
         data::test_sign_and_verify(&endpoint).await;
+        data::test_typed_values(&endpoint).await;
+        data::test_batch_encrypt_and_rewrap_and_decrypt(&endpoint).await;
+        data::test_batch_sign_and_verify(&endpoint).await;
 
         generate::test_data_key(&endpoint).await;
         generate::test_random_bytes(&endpoint).await;
         generate::test_hash(&endpoint).await;
         generate::test_hmac(&endpoint).await;
+        generate::test_verify_hmac(&endpoint).await;
+        generate::test_hmac_batch(&endpoint).await;
+
+        #[cfg(feature = "transit-crypto")]
+        {
+            offline::test_verify(&endpoint).await;
+            offline::test_refresh(&endpoint).await;
+        }
+
+        #[cfg(feature = "transit-crypto")]
+        {
+            jwt::test_sign(&endpoint).await;
+            jwt::test_jwks(&endpoint).await;
+        }
+
+        #[cfg(feature = "transit-crypto")]
+        {
+            envelope::test_encrypt_and_decrypt(&endpoint).await;
+            envelope::test_reader(&endpoint).await;
+        }
+
+        #[cfg(all(feature = "transit-crypto", feature = "fs"))]
+        {
+            stream::test_encrypt_and_decrypt(&endpoint).await;
+            stream::test_tampering(&endpoint).await;
+            stream::test_files(&endpoint).await;
+        }
+
+        encrypted::test_seal_and_unseal(&endpoint).await;
+        encrypted::test_derived(&endpoint).await;
+
+        #[cfg(feature = "streams")]
+        rewrap::test_run(&endpoint).await;
+
+        policy::test_create(&endpoint).await;
+        policy::test_apply(&endpoint).await;
+        policy::test_lifecycle(&endpoint).await;
 
         cache::test_configure_and_read(&endpoint).await
     });
 }
 
+#[cfg(feature = "transit-crypto")]
+#[test]
+fn test_key_material() {
+    use vaultrs::api::transit::KeyType;
+    use vaultrs::transit::wrapping::KeyMaterial;
+
+    let key = KeyMaterial::Raw(vec![1; 32]);
+    assert_eq!(
+        key.to_import_bytes(KeyType::Aes256Gcm96).unwrap(),
+        vec![1; 32]
+    );
+    assert!(matches!(
+        key.to_import_bytes(KeyType::Aes128Gcm96),
+        Err(ClientError::KeyImportError { .. })
+    ));
+    assert!(matches!(
+        key.to_import_bytes(KeyType::Rsa2048),
+        Err(ClientError::KeyImportError { .. })
+    ));
+
+    // Raw Ed25519 seeds are converted to PKCS#8
+    let der = key.to_import_bytes(KeyType::Ed25519).unwrap();
+    assert_eq!(der.len(), 48);
+    let key = KeyMaterial::Pkcs8Der(der);
+    assert!(key.to_import_bytes(KeyType::Ed25519).is_ok());
+    assert!(matches!(
+        key.to_import_bytes(KeyType::EcdsaP256),
+        Err(ClientError::KeyImportError { .. })
+    ));
+    assert!(matches!(
+        key.to_import_bytes(KeyType::Aes256Gcm96),
+        Err(ClientError::KeyImportError { .. })
+    ));
+
+    // EC keys must be on the curve of the key type
+    use p256::pkcs8::EncodePrivateKey;
+    let der = p256::SecretKey::random(&mut rand::thread_rng())
+        .to_pkcs8_der()
+        .unwrap();
+    let key = KeyMaterial::Pkcs8Der(der.as_bytes().to_vec());
+    assert!(key.to_import_bytes(KeyType::EcdsaP256).is_ok());
+    assert!(matches!(
+        key.to_import_bytes(KeyType::EcdsaP384),
+        Err(ClientError::KeyImportError { .. })
+    ));
+}
+
+#[cfg(feature = "transit-crypto")]
+#[test]
+fn test_wrap() {
+    use aes_kw::KekAes256;
+    use rsa::{pkcs8::EncodePublicKey, Oaep, RsaPrivateKey};
+    use std::convert::TryFrom;
+    use vaultrs::transit::wrapping;
+
+    let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+    let public_key = private_key
+        .to_public_key()
+        .to_public_key_pem(Default::default())
+        .unwrap();
+    let key = [9u8; 32];
+
+    let ciphertext = general_purpose::STANDARD
+        .decode(wrapping::wrap(&public_key, &key).unwrap())
+        .unwrap();
+    let (encrypted, wrapped) = ciphertext.split_at(256);
+    let ephemeral = private_key
+        .decrypt(Oaep::new::<Sha256>(), encrypted)
+        .unwrap();
+    let kek = KekAes256::try_from(ephemeral.as_slice()).unwrap();
+    assert_eq!(kek.unwrap_with_padding_vec(wrapped).unwrap(), key);
+
+    assert!(matches!(
+        wrapping::wrap("not a key", &key),
+        Err(ClientError::KeyImportError { .. })
+    ));
+}
+
+#[test]
+fn test_versioned_values() {
+    use vaultrs::api::transit::{Ciphertext, Hmac, Signature};
+
+    let ciphertext: Ciphertext = "vault:v12:abc:def".parse().unwrap();
+    assert_eq!(ciphertext.version(), 12);
+    assert_eq!(ciphertext.payload(), "abc:def");
+    assert_eq!(ciphertext.to_string(), "vault:v12:abc:def");
+    assert_eq!(
+        serde_json::to_string(&ciphertext).unwrap(),
+        "\"vault:v12:abc:def\""
+    );
+
+    let signature: Signature = serde_json::from_str("\"vault:v1:c2ln\"").unwrap();
+    assert_eq!(signature.version(), 1);
+    assert!(serde_json::from_str::<Signature>("\"c2ln\"").is_err());
+
+    for invalid in [
+        "",
+        "vault:v1",
+        "vault:v1:",
+        "vault:1:abc",
+        "vault:v0:abc",
+        "other:v1:abc",
+    ] {
+        assert!(
+            matches!(
+                Hmac::parse(invalid),
+                Err(ClientError::ParseVaultValueError { .. })
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[cfg(feature = "transit-crypto")]
+#[test]
+fn test_envelope_format() {
+    use vaultrs::transit::envelope::Envelope;
+
+    let envelope = Envelope {
+        key: "my-key".into(),
+        key_version: 3,
+        wrapped_key: "vault:v3:abcd".into(),
+        nonce: [7; 12],
+        ciphertext: vec![1, 2, 3],
+    };
+    let bytes = envelope.to_bytes().unwrap();
+    assert!(bytes.starts_with(b"VENV"));
+    assert_eq!(Envelope::from_bytes(&bytes).unwrap(), envelope);
+
+    let oversized = Envelope {
+        key: "k".repeat(usize::from(u16::MAX) + 1),
+        ..envelope.clone()
+    };
+    let res = oversized.to_bytes();
+    assert!(matches!(res, Err(ClientError::EnvelopeError { .. })));
+
+    let res = Envelope::from_bytes(&bytes[..20]);
+    assert!(matches!(res, Err(ClientError::EnvelopeError { .. })));
+    let res = Envelope::from_bytes(b"not an envelope");
+    assert!(matches!(res, Err(ClientError::EnvelopeError { .. })));
+}
+
 mod key {
     use super::TransitEndpoint;
     use vaultrs::api::transit::requests::{
@@ -47,6 +232,8 @@ mod key {
     };
     use vaultrs::api::transit::KeyType;
     use vaultrs::transit::key;
+    #[cfg(feature = "transit-crypto")]
+    use vaultrs::transit::wrapping::KeyMaterial;
 
     pub async fn test_create(endpoint: &TransitEndpoint) {
         let resp = key::create(&endpoint.client, &endpoint.path, &endpoint.keys.basic, None).await;
@@ -237,16 +424,65 @@ mod key {
         let resp = key::trim(&endpoint.client, &endpoint.path, &endpoint.keys.export, 2).await;
         assert!(resp.is_ok());
     }
+
+    #[cfg(feature = "transit-crypto")]
+    pub async fn test_import(endpoint: &TransitEndpoint) {
+        use vaultrs::api::transit::requests::ImportKeyRequest;
+        use vaultrs::api::transit::responses::ReadKeyData;
+
+        let resp = key::read_wrapping_key(&endpoint.client, &endpoint.path).await;
+        assert!(resp.unwrap().public_key.contains("PUBLIC KEY"));
+
+        let resp = key::import(
+            &endpoint.client,
+            &endpoint.path,
+            "imported-aes",
+            KeyType::Aes256Gcm96,
+            &KeyMaterial::Raw(vec![7; 32]),
+            Some(ImportKeyRequest::builder().allow_rotation(true)),
+        )
+        .await;
+        assert!(resp.is_ok());
+
+        let resp = key::import_version(
+            &endpoint.client,
+            &endpoint.path,
+            "imported-aes",
+            &KeyMaterial::Raw(vec![8; 32]),
+            None,
+        )
+        .await;
+        assert!(resp.is_ok());
+
+        let resp = key::read(&endpoint.client, &endpoint.path, "imported-aes")
+            .await
+            .unwrap();
+        assert_eq!(resp.imported, Some(true));
+        assert!(matches!(resp.keys, ReadKeyData::Symmetric(keys) if keys.len() == 2));
+
+        let resp = key::import(
+            &endpoint.client,
+            &endpoint.path,
+            "imported-ed25519",
+            KeyType::Ed25519,
+            &KeyMaterial::Raw(vec![3; 32]),
+            None,
+        )
+        .await;
+        assert!(resp.is_ok());
+    }
 }
 
 mod data {
     use super::TransitEndpoint;
     use vaultrs::api::transit::requests::{
-        DecryptDataRequest, EncryptDataRequest, RewrapDataRequest, SignDataRequest,
+        DecryptDataBatchInput, DecryptDataRequest, EncryptDataBatchInput, EncryptDataRequest,
+        RewrapDataRequest, SignDataBatchInput, SignDataRequest, VerifySignedDataBatchInput,
         VerifySignedDataRequest,
     };
-    use vaultrs::api::transit::SignatureAlgorithm;
-    use vaultrs::transit::{data, key};
+    use vaultrs::api::transit::{Ciphertext, SignatureAlgorithm};
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::{data, key, DEFAULT_BATCH_SIZE};
 
     pub async fn test_encrypt_and_rewrap_and_decrypt(endpoint: &TransitEndpoint) {
         let encrypted = data::encrypt(
@@ -320,7 +556,7 @@ mod data {
             Some(
                 VerifySignedDataRequest::builder()
                     .context(&endpoint.data.context)
-                    .signature(&signed.signature)
+                    .signature(signed.signature.as_str())
                     .signature_algorithm(SignatureAlgorithm::Pkcs1v15),
             ),
         )
@@ -328,16 +564,201 @@ mod data {
         .unwrap();
         assert!(verified.valid);
     }
+
+    pub async fn test_typed_values(endpoint: &TransitEndpoint) {
+        let encrypted = data::encrypt(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &endpoint.data.secret,
+            Some(EncryptDataRequest::builder().context(&endpoint.data.context)),
+        )
+        .await
+        .unwrap();
+        let ciphertext = encrypted.ciphertext;
+        assert!(ciphertext.version() >= 1);
+        let decrypted = data::decrypt(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &ciphertext,
+            Some(DecryptDataRequest::builder().context(&endpoint.data.context)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(decrypted.plaintext, endpoint.data.secret);
+
+        let res = Ciphertext::parse("not a ciphertext");
+        assert!(matches!(res, Err(ClientError::ParseVaultValueError { .. })));
+
+        let signed = data::sign(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.signing,
+            &endpoint.data.secret,
+            Some(SignDataRequest::builder().context(&endpoint.data.context)),
+        )
+        .await
+        .unwrap();
+        let signature = signed.signature;
+        for (input, expected) in [
+            (&endpoint.data.secret, true),
+            (&endpoint.data.context, false),
+        ] {
+            let valid = data::verify_signature(
+                &endpoint.client,
+                &endpoint.path,
+                &endpoint.keys.signing,
+                input,
+                &signature,
+                Some(VerifySignedDataRequest::builder().context(&endpoint.data.context)),
+            )
+            .await
+            .unwrap();
+            assert_eq!(valid, expected);
+        }
+    }
+
+    pub async fn test_batch_encrypt_and_rewrap_and_decrypt(endpoint: &TransitEndpoint) {
+        let items: Vec<EncryptDataBatchInput> = (0..5)
+            .map(|i| EncryptDataBatchInput {
+                plaintext: endpoint.data.secret.clone(),
+                context: Some(endpoint.data.context.clone()),
+                reference: Some(format!("item-{}", i)),
+                ..Default::default()
+            })
+            .collect();
+        let encrypted = data::encrypt_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &items,
+            2,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(encrypted.len(), 5);
+
+        let mut items: Vec<DecryptDataBatchInput> = encrypted
+            .into_iter()
+            .map(|res| DecryptDataBatchInput {
+                ciphertext: res.unwrap().ciphertext.into(),
+                context: Some(endpoint.data.context.clone()),
+                ..Default::default()
+            })
+            .collect();
+        items.push(DecryptDataBatchInput {
+            ciphertext: "vault:v1:invalid".into(),
+            context: Some(endpoint.data.context.clone()),
+            reference: Some("invalid".into()),
+            ..Default::default()
+        });
+
+        let rewrapped = data::rewrap_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &items[..5],
+            DEFAULT_BATCH_SIZE,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(rewrapped.iter().all(|res| res.is_ok()));
+
+        let decrypted = data::decrypt_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &items,
+            4,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(decrypted.len(), 6);
+        for res in &decrypted[..5] {
+            assert_eq!(&res.as_ref().unwrap().plaintext, &endpoint.data.secret);
+        }
+        assert!(matches!(
+            &decrypted[5],
+            Err(ClientError::BatchItemError { index: 5, reference: Some(r), .. }) if r == "invalid"
+        ));
+
+        // Every item of the last chunk fails
+        let decrypted = data::decrypt_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.export,
+            &items,
+            5,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(decrypted[..5].iter().all(|res| res.is_ok()));
+        assert!(matches!(
+            &decrypted[5],
+            Err(ClientError::BatchItemError { index: 5, .. })
+        ));
+    }
+
+    pub async fn test_batch_sign_and_verify(endpoint: &TransitEndpoint) {
+        let items: Vec<SignDataBatchInput> = (0..3)
+            .map(|i| SignDataBatchInput {
+                input: endpoint.data.secret.clone(),
+                context: Some(endpoint.data.context.clone()),
+                reference: Some(i.to_string()),
+            })
+            .collect();
+        let signed = data::sign_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.signing,
+            &items,
+            2,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(signed.len(), 3);
+
+        let mut items: Vec<VerifySignedDataBatchInput> = signed
+            .into_iter()
+            .map(|res| VerifySignedDataBatchInput {
+                input: endpoint.data.secret.clone(),
+                signature: Some(res.unwrap().signature.into()),
+                context: Some(endpoint.data.context.clone()),
+                ..Default::default()
+            })
+            .collect();
+        items[2].input = endpoint.data.context.clone();
+
+        let verified = data::verify_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.signing,
+            &items,
+            2,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(verified[0].as_ref().unwrap().valid);
+        assert!(verified[1].as_ref().unwrap().valid);
+        assert!(!verified[2].as_ref().unwrap().valid);
+    }
 }
 
 mod generate {
     use super::TransitEndpoint;
     use vaultrs::api::transit::requests::{
         DataKeyType, GenerateDataKeyRequest, GenerateRandomBytesRequest, HashDataRequest,
-        RandomBytesSource,
+        RandomBytesSource, SignDataBatchInput,
     };
     use vaultrs::api::transit::{HashAlgorithm, OutputFormat};
-    use vaultrs::transit::generate;
+    use vaultrs::transit::{data, generate};
 
     pub async fn test_data_key(endpoint: &TransitEndpoint) {
         let resp = generate::data_key(
@@ -392,6 +813,1100 @@ mod generate {
         .await;
         assert!(resp.is_ok());
     }
+
+    pub async fn test_verify_hmac(endpoint: &TransitEndpoint) {
+        let resp = generate::hmac(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            &endpoint.data.secret,
+            None,
+        )
+        .await
+        .unwrap();
+        let hmac = resp.hmac;
+        assert!(hmac.version() >= 1);
+
+        for (input, expected) in [
+            (&endpoint.data.secret, true),
+            (&endpoint.data.context, false),
+        ] {
+            let valid = data::verify_hmac(
+                &endpoint.client,
+                &endpoint.path,
+                &endpoint.keys.basic,
+                input,
+                &hmac,
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(valid, expected);
+        }
+    }
+
+    pub async fn test_hmac_batch(endpoint: &TransitEndpoint) {
+        let items: Vec<SignDataBatchInput> = [&endpoint.data.context, &endpoint.data.secret]
+            .iter()
+            .map(|input| SignDataBatchInput {
+                input: input.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let resp = generate::hmac_batch(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            &items,
+            1,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.len(), 2);
+
+        let single = generate::hmac(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            &endpoint.data.context,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp[0].as_ref().unwrap().hmac, single.hmac);
+    }
+}
+
+#[cfg(feature = "transit-crypto")]
+#[test]
+fn test_jwks_from_key() {
+    use p256::{elliptic_curve::sec1::ToEncodedPoint, pkcs8::EncodePublicKey};
+    use vaultrs::api::transit::responses::ReadKeyResponse;
+    use vaultrs::transit::jwt::{Jwks, JwsAlgorithm};
+
+    let public_key = p256::SecretKey::random(&mut rand::thread_rng()).public_key();
+    let pem = public_key.to_public_key_pem(Default::default()).unwrap();
+    let entry = serde_json::json!({
+        "creation_time": "2023-01-01T00:00:00Z",
+        "name": "P-256",
+        "public_key": pem,
+    });
+    let key: ReadKeyResponse = serde_json::from_value(serde_json::json!({
+        "type": "ecdsa-p256",
+        "deletion_allowed": false,
+        "derived": false,
+        "exportable": false,
+        "allow_plaintext_backup": false,
+        "keys": { "1": entry, "2": entry, "3": entry },
+        "latest_version": 3,
+        "min_decryption_version": 2,
+        "min_encryption_version": 0,
+        "name": "jwt",
+        "supports_encryption": false,
+        "supports_decryption": false,
+        "supports_derivation": false,
+        "supports_signing": true,
+    }))
+    .unwrap();
+
+    let jwks = Jwks::from_key(&key, None).unwrap();
+    let kids: Vec<_> = jwks.keys.iter().map(|k| k.kid.as_str()).collect();
+    assert_eq!(kids, vec!["jwt:v2", "jwt:v3"]);
+
+    let jwk = &jwks.keys[0];
+    let point = public_key.to_encoded_point(false);
+    assert_eq!(jwk.kty, "EC");
+    assert_eq!(jwk.alg, Some(JwsAlgorithm::ES256));
+    assert_eq!(jwk.crv.as_deref(), Some("P-256"));
+    assert_eq!(
+        jwk.x.as_deref(),
+        Some(
+            general_purpose::URL_SAFE_NO_PAD
+                .encode(point.x().unwrap())
+                .as_str()
+        )
+    );
+    assert_eq!(
+        jwk.y.as_deref(),
+        Some(
+            general_purpose::URL_SAFE_NO_PAD
+                .encode(point.y().unwrap())
+                .as_str()
+        )
+    );
+    let json = serde_json::to_value(jwk).unwrap();
+    assert_eq!(json["use"], "sig");
+    assert!(json.get("n").is_none());
+
+    let res = Jwks::from_key(&key, Some(JwsAlgorithm::RS256));
+    assert!(matches!(res, Err(ClientError::JwsError { .. })));
+}
+
+#[cfg(feature = "transit-crypto")]
+#[test]
+fn test_jwt_audience() {
+    use vaultrs::transit::jwt::{Audience, JwtClaims};
+
+    let claims: JwtClaims = serde_json::from_str(r#"{"aud": ["a", "b"], "foo": 1}"#).unwrap();
+    let aud = claims.aud.unwrap();
+    assert!(aud.contains("b"));
+    assert!(!aud.contains("c"));
+    assert_eq!(claims.extra["foo"], 1);
+
+    let claims = JwtClaims {
+        aud: Some("a".into()),
+        ..Default::default()
+    };
+    assert_eq!(serde_json::to_string(&claims).unwrap(), r#"{"aud":"a"}"#);
+    assert_eq!(claims.aud, Some(Audience::Single("a".into())));
+}
+
+#[test]
+fn test_encrypted_serde() {
+    use vaultrs::transit::encrypted::{Encrypted, TransitKey};
+
+    struct Key;
+    impl TransitKey for Key {
+        const MOUNT: &'static str = "transit";
+        const NAME: &'static str = "key";
+    }
+
+    let field: Encrypted<u64, Key> = Encrypted::new(42);
+    assert!(serde_json::to_string(&field).is_err());
+
+    let field: Encrypted<u64, Key> = serde_json::from_str("\"vault:v1:abcd\"").unwrap();
+    assert_eq!(field.ciphertext(), Some("vault:v1:abcd"));
+    assert!(field.get().is_none());
+    assert_eq!(serde_json::to_string(&field).unwrap(), "\"vault:v1:abcd\"");
+
+    let mut field: Encrypted<u64, Key> = Encrypted::new(1);
+    field.set(2);
+    assert_eq!(field.get(), Some(&2));
+    assert!(field.ciphertext().is_none());
+}
+
+#[cfg(feature = "streams")]
+#[test]
+fn test_ciphertext_version() {
+    use vaultrs::transit::rewrap::ciphertext_version;
+
+    assert_eq!(ciphertext_version("vault:v1:abcd"), Some(1));
+    assert_eq!(ciphertext_version("vault:v12:ab:cd"), Some(12));
+    assert_eq!(ciphertext_version("vault:1:abcd"), None);
+    assert_eq!(ciphertext_version("vault:v1"), None);
+    assert_eq!(ciphertext_version("other:v1:abcd"), None);
+}
+
+#[test]
+fn test_key_policy_validate() {
+    use std::time::Duration;
+    use vaultrs::api::transit::KeyType;
+    use vaultrs::transit::policy::KeyPolicy;
+
+    let invalid = [
+        KeyPolicy {
+            convergent_encryption: Some(true),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::EcdsaP256),
+            derived: Some(true),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::Ed25519),
+            derived: Some(true),
+            convergent_encryption: Some(true),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::Hmac),
+            key_size: Some(16),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_size: Some(64),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::ManagedKey),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::ManagedKey),
+            managed_key_name: Some("hsm".into()),
+            exportable: true,
+            ..Default::default()
+        },
+        KeyPolicy {
+            managed_key_id: Some("id".into()),
+            ..Default::default()
+        },
+        KeyPolicy {
+            auto_rotate_period: Some(Duration::from_secs(60)),
+            ..Default::default()
+        },
+        KeyPolicy {
+            min_decryption_version: Some(3),
+            min_encryption_version: Some(2),
+            ..Default::default()
+        },
+    ];
+    for policy in invalid.iter() {
+        assert!(
+            matches!(policy.validate(), Err(ClientError::KeyPolicyError { .. })),
+            "{:?}",
+            policy
+        );
+    }
+
+    let valid = [
+        KeyPolicy::default(),
+        KeyPolicy {
+            key_type: Some(KeyType::Chacha20Poly1305),
+            derived: Some(true),
+            convergent_encryption: Some(true),
+            auto_rotate_period: Some(Duration::ZERO),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::Hmac),
+            key_size: Some(64),
+            auto_rotate_period: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        },
+        KeyPolicy {
+            key_type: Some(KeyType::ManagedKey),
+            managed_key_name: Some("hsm".into()),
+            min_decryption_version: Some(3),
+            min_encryption_version: Some(0),
+            ..Default::default()
+        },
+    ];
+    for policy in valid.iter() {
+        assert!(policy.validate().is_ok(), "{:?}", policy);
+    }
+}
+
+#[cfg(all(feature = "transit-crypto", feature = "fs"))]
+#[test]
+fn test_stream_header() {
+    use futures::io::Cursor;
+    use vaultrs::transit::stream::StreamHeader;
+
+    let header = StreamHeader {
+        chunk_size: 1024,
+        key: "my-key".into(),
+        key_version: 2,
+        wrapped_key: "vault:v2:abcd".into(),
+        nonce_prefix: [3; 7],
+    };
+    let bytes = header.to_bytes().unwrap();
+    assert!(bytes.starts_with(b"VSTR"));
+    let res = futures::executor::block_on(StreamHeader::read(&mut Cursor::new(&bytes)));
+    assert_eq!(res.unwrap(), header);
+
+    let oversized = StreamHeader {
+        key_version: u64::from(u32::MAX) + 1,
+        ..header.clone()
+    };
+    let res = oversized.to_bytes();
+    assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+
+    let res = futures::executor::block_on(StreamHeader::read(&mut Cursor::new(&bytes[..20])));
+    assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+    let res = futures::executor::block_on(StreamHeader::read(&mut Cursor::new(b"VENV")));
+    assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+}
+
+#[cfg(all(feature = "transit-crypto", feature = "fs"))]
+mod stream {
+    use super::TransitEndpoint;
+    use futures::io::Cursor;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::key;
+    use vaultrs::transit::stream::{self, StreamHeader, StreamOptions};
+
+    const KEY: &str = "stream-key";
+    const CHUNK_SIZE: u32 = 1024;
+
+    fn plaintext() -> Vec<u8> {
+        (0..10 * CHUNK_SIZE + 100).map(|i| i as u8).collect()
+    }
+
+    fn opts() -> StreamOptions {
+        StreamOptions {
+            chunk_size: CHUNK_SIZE,
+            ..Default::default()
+        }
+    }
+
+    async fn encrypt(endpoint: &TransitEndpoint, plaintext: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let res = stream::encrypt(
+            &endpoint.client,
+            &endpoint.path,
+            KEY,
+            Cursor::new(plaintext),
+            &mut out,
+            &opts(),
+        )
+        .await;
+        assert_eq!(res.unwrap(), plaintext.len() as u64);
+        out
+    }
+
+    async fn decrypt(
+        endpoint: &TransitEndpoint,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, ClientError> {
+        let mut out = Vec::new();
+        stream::decrypt(
+            &endpoint.client,
+            &endpoint.path,
+            Cursor::new(ciphertext),
+            &mut out,
+            &Default::default(),
+        )
+        .await?;
+        Ok(out)
+    }
+
+    pub async fn test_encrypt_and_decrypt(endpoint: &TransitEndpoint) {
+        key::create(&endpoint.client, &endpoint.path, KEY, None)
+            .await
+            .unwrap();
+
+        for len in [0, 10, CHUNK_SIZE as usize, plaintext().len()] {
+            let plaintext = &plaintext()[..len];
+            let ciphertext = encrypt(endpoint, plaintext).await;
+            assert_eq!(decrypt(endpoint, &ciphertext).await.unwrap(), plaintext);
+        }
+
+        let ciphertext = encrypt(endpoint, &plaintext()).await;
+        let header = StreamHeader::read(&mut Cursor::new(&ciphertext))
+            .await
+            .unwrap();
+        assert_eq!(header.key, KEY);
+        assert_eq!(header.key_version, 1);
+        assert_eq!(header.chunk_size, CHUNK_SIZE);
+    }
+
+    pub async fn test_tampering(endpoint: &TransitEndpoint) {
+        let ciphertext = encrypt(endpoint, &plaintext()).await;
+        let header_len = StreamHeader::read(&mut Cursor::new(&ciphertext))
+            .await
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+            .len();
+        let chunk = CHUNK_SIZE as usize + 16;
+
+        // Truncated at a chunk boundary
+        let truncated = &ciphertext[..header_len + 2 * chunk];
+        let res = decrypt(endpoint, truncated).await;
+        assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+
+        // Truncated within a chunk
+        let res = decrypt(endpoint, &ciphertext[..ciphertext.len() - 10]).await;
+        assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+
+        // Reordered chunks
+        let mut reordered = ciphertext.clone();
+        let (first, second) = reordered[header_len..].split_at_mut(chunk);
+        first.swap_with_slice(&mut second[..chunk]);
+        let res = decrypt(endpoint, &reordered).await;
+        assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+
+        // Modified header
+        let mut modified = ciphertext.clone();
+        modified[6] ^= 1;
+        assert!(decrypt(endpoint, &modified).await.is_err());
+
+        // Appended data
+        let mut appended = ciphertext;
+        appended.extend_from_slice(&[0; 32]);
+        let res = decrypt(endpoint, &appended).await;
+        assert!(matches!(res, Err(ClientError::StreamFormatError { .. })));
+    }
+
+    pub async fn test_files(endpoint: &TransitEndpoint) {
+        let dir = std::env::temp_dir().join(format!("vaultrs-stream-{}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("plain");
+        let enc = dir.join("plain.enc");
+        let dst = dir.join("plain.out");
+        std::fs::write(&src, plaintext()).unwrap();
+        let opts = opts();
+
+        let res = stream::encrypt_file(&endpoint.client, &endpoint.path, KEY, &src, &enc, &opts);
+        assert_eq!(res.await.unwrap(), plaintext().len() as u64);
+        let res = stream::decrypt_file(&endpoint.client, &endpoint.path, &enc, &dst, &opts);
+        assert_eq!(res.await.unwrap(), plaintext().len() as u64);
+        assert_eq!(std::fs::read(&dst).unwrap(), plaintext());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&dst).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // The output is removed when decryption fails
+        let ciphertext = std::fs::read(&enc).unwrap();
+        std::fs::write(&enc, &ciphertext[..ciphertext.len() - 1]).unwrap();
+        std::fs::remove_file(&dst).unwrap();
+        let res = stream::decrypt_file(&endpoint.client, &endpoint.path, &enc, &dst, &opts);
+        assert!(matches!(
+            res.await,
+            Err(ClientError::StreamFormatError { .. })
+        ));
+        assert!(!dst.exists());
+
+        let res = stream::encrypt_file(
+            &endpoint.client,
+            &endpoint.path,
+            KEY,
+            dir.join("missing"),
+            &enc,
+            &opts,
+        );
+        assert!(matches!(res.await, Err(ClientError::FileReadError { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+mod policy {
+    use super::TransitEndpoint;
+    use std::time::Duration;
+    use vaultrs::api::transit::KeyType;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::key;
+    use vaultrs::transit::policy::{self, KeyPolicy};
+
+    fn key_policy() -> KeyPolicy {
+        KeyPolicy {
+            key_type: Some(KeyType::Aes128Gcm96),
+            derived: Some(true),
+            convergent_encryption: Some(true),
+            auto_rotate_period: Some(Duration::from_secs(2 * 60 * 60)),
+            deletion_allowed: Some(true),
+            ..Default::default()
+        }
+    }
+
+    pub async fn test_create(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        policy::create(client, mount, "policy-key", &key_policy())
+            .await
+            .unwrap();
+
+        let key = key::read(client, mount, "policy-key").await.unwrap();
+        assert_eq!(key.key_type, KeyType::Aes128Gcm96);
+        assert!(key.derived);
+        assert!(key.deletion_allowed);
+        assert_eq!(key.auto_rotate_period, Some(2 * 60 * 60));
+
+        let policy = KeyPolicy {
+            derived: Some(false),
+            ..key_policy()
+        };
+        let res = policy::create(client, mount, "policy-invalid", &policy).await;
+        assert!(matches!(res, Err(ClientError::KeyPolicyError { .. })));
+        assert!(key::read(client, mount, "policy-invalid").await.is_err());
+    }
+
+    pub async fn test_apply(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        let policy = KeyPolicy {
+            exportable: true,
+            auto_rotate_period: Some(Duration::ZERO),
+            ..key_policy()
+        };
+        policy::apply(client, mount, "policy-key", &policy)
+            .await
+            .unwrap();
+
+        let key = key::read(client, mount, "policy-key").await.unwrap();
+        assert!(key.exportable);
+        assert_eq!(key.auto_rotate_period, Some(0));
+
+        // Settings fixed at creation don't have to be repeated
+        let policy = KeyPolicy {
+            exportable: true,
+            allow_plaintext_backup: true,
+            ..Default::default()
+        };
+        policy::apply(client, mount, "policy-key", &policy)
+            .await
+            .unwrap();
+        let key = key::read(client, mount, "policy-key").await.unwrap();
+        assert!(key.allow_plaintext_backup);
+
+        // Fixed or irreversible settings can't be changed
+        let res = policy::apply(client, mount, "policy-key", &key_policy()).await;
+        assert!(matches!(res, Err(ClientError::KeyPolicyError { .. })));
+        let policy = KeyPolicy {
+            key_type: Some(KeyType::Aes256Gcm96),
+            exportable: true,
+            ..key_policy()
+        };
+        let res = policy::apply(client, mount, "policy-key", &policy).await;
+        assert!(matches!(res, Err(ClientError::KeyPolicyError { .. })));
+    }
+
+    pub async fn test_lifecycle(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        key::create(client, mount, "lifecycle-key", None)
+            .await
+            .unwrap();
+
+        key::allow_export(client, mount, "lifecycle-key")
+            .await
+            .unwrap();
+        key::allow_plaintext_backup(client, mount, "lifecycle-key")
+            .await
+            .unwrap();
+        key::set_auto_rotate_period(client, mount, "lifecycle-key", Duration::from_secs(3600))
+            .await
+            .unwrap();
+
+        let key = key::read(client, mount, "lifecycle-key").await.unwrap();
+        assert!(key.exportable);
+        assert!(key.allow_plaintext_backup);
+        assert_eq!(key.auto_rotate_period, Some(3600));
+        assert!(key::backup(client, mount, "lifecycle-key").await.is_ok());
+    }
+}
+
+#[cfg(feature = "streams")]
+mod rewrap {
+    use super::TransitEndpoint;
+    use base64::{engine::general_purpose, Engine as _};
+    use futures::stream;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::rewrap::{self, RewrapOptions, RewrapProgress, RewrapStatus};
+    use vaultrs::transit::{data, key};
+
+    pub async fn test_run(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        let name = "rewrap-key";
+        key::create(client, mount, name, None).await.unwrap();
+
+        let plaintext = general_purpose::STANDARD.encode("data");
+        let mut ciphertexts = Vec::new();
+        for _ in 0..3 {
+            let res = data::encrypt(client, mount, name, &plaintext, None).await;
+            ciphertexts.push(res.unwrap().ciphertext.into());
+        }
+        key::rotate(client, mount, name).await.unwrap();
+        let res = data::encrypt(client, mount, name, &plaintext, None).await;
+        ciphertexts.push(res.unwrap().ciphertext.into());
+        ciphertexts.push("not a ciphertext".into());
+
+        let mut rewrapped = ciphertexts.clone();
+        let mut batches = 0;
+        let opts = RewrapOptions {
+            batch_size: 2,
+            ..Default::default()
+        };
+        let report = rewrap::run(
+            client,
+            mount,
+            name,
+            stream::iter(ciphertexts.clone()),
+            &opts,
+            |_, outcomes| {
+                batches += 1;
+                for outcome in outcomes {
+                    if let RewrapStatus::Rewrapped(ciphertext) = &outcome.status {
+                        rewrapped[outcome.index] = ciphertext.to_string();
+                    }
+                }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(batches, 3);
+        assert_eq!(report.latest_version, 2);
+        assert_eq!(
+            report.progress,
+            RewrapProgress {
+                processed: 5,
+                rewrapped: 3,
+                current: 1,
+                failed: 1,
+            }
+        );
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].index, 4);
+        assert!(matches!(
+            report.failures[0].status,
+            RewrapStatus::Failed(ClientError::RewrapError { .. })
+        ));
+        assert_eq!(rewrapped[3], ciphertexts[3]);
+        for ciphertext in &rewrapped[..4] {
+            assert!(ciphertext.starts_with("vault:v2:"));
+            let ciphertext = ciphertext.parse().unwrap();
+            let res = data::decrypt(client, mount, name, &ciphertext, None).await;
+            assert_eq!(res.unwrap().plaintext, plaintext);
+        }
+    }
+}
+
+mod encrypted {
+    use super::TransitEndpoint;
+    use serde::{Deserialize, Serialize};
+    use vaultrs::api::transit::requests::CreateKeyRequest;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::encrypted::{self, Encrypted, EncryptedField, Sealable, TransitKey};
+    use vaultrs::transit::key;
+
+    struct RecordKey;
+    impl TransitKey for RecordKey {
+        const MOUNT: &'static str = "transit-test";
+        const NAME: &'static str = "encrypted-key";
+    }
+
+    struct DerivedKey;
+    impl TransitKey for DerivedKey {
+        const MOUNT: &'static str = "transit-test";
+        const NAME: &'static str = "encrypted-derived";
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Record {
+        id: u64,
+        ssn: Encrypted<String, RecordKey>,
+        tokens: Vec<Encrypted<Vec<String>, RecordKey>>,
+        pin: Option<Encrypted<u32, DerivedKey>>,
+    }
+
+    impl Sealable for Record {
+        fn encrypted_fields(&mut self) -> Vec<&mut dyn EncryptedField> {
+            let mut fields = self.ssn.encrypted_fields();
+            fields.extend(self.tokens.encrypted_fields());
+            fields.extend(self.pin.encrypted_fields());
+            fields
+        }
+    }
+
+    pub async fn test_seal_and_unseal(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        key::create(client, RecordKey::MOUNT, RecordKey::NAME, None)
+            .await
+            .unwrap();
+
+        let mut records = vec![
+            Record {
+                id: 1,
+                ssn: Encrypted::new("123-45-6789".into()),
+                tokens: vec![Encrypted::new(vec!["a".into(), "b".into()])],
+                pin: None,
+            },
+            Record {
+                id: 2,
+                ssn: Encrypted::new("987-65-4321".into()),
+                tokens: vec![],
+                pin: None,
+            },
+        ];
+        encrypted::seal(client, &mut records).await.unwrap();
+        let json = serde_json::to_string(&records).unwrap();
+        assert!(!json.contains("123-45-6789"));
+        assert!(records[0]
+            .ssn
+            .ciphertext()
+            .unwrap()
+            .starts_with("vault:v1:"));
+
+        let mut records: Vec<Record> = serde_json::from_str(&json).unwrap();
+        assert!(records[0].ssn.get().is_none());
+        encrypted::unseal(client, &mut records).await.unwrap();
+        assert_eq!(records[0].ssn.get().unwrap(), "123-45-6789");
+        assert_eq!(records[0].tokens[0].get().unwrap(), &vec!["a", "b"]);
+        assert_eq!(records[1].ssn.get().unwrap(), "987-65-4321");
+
+        // Only modified fields are sealed again
+        let ciphertext = records[1].ssn.ciphertext().unwrap().to_string();
+        records[0].ssn.set("000-00-0000".into());
+        encrypted::seal(client, &mut records).await.unwrap();
+        assert_eq!(records[1].ssn.ciphertext().unwrap(), ciphertext);
+        assert!(records[0].ssn.ciphertext().is_some());
+    }
+
+    pub async fn test_derived(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        key::create(
+            client,
+            DerivedKey::MOUNT,
+            DerivedKey::NAME,
+            Some(CreateKeyRequest::builder().derived(true)),
+        )
+        .await
+        .unwrap();
+
+        let mut record = Record {
+            id: 3,
+            ssn: Encrypted::new("111-22-3333".into()),
+            tokens: vec![],
+            pin: Some(Encrypted::new(1234).with_context("record-3")),
+        };
+        encrypted::seal(client, &mut record).await.unwrap();
+        let json = serde_json::to_string(&record).unwrap();
+
+        let mut record: Record = serde_json::from_str(&json).unwrap();
+        let res = encrypted::unseal(client, &mut record).await;
+        assert!(matches!(res, Err(ClientError::BatchItemError { .. })));
+
+        let mut record: Record = serde_json::from_str(&json).unwrap();
+        record.pin.as_mut().unwrap().set_context("record-3");
+        encrypted::unseal(client, &mut record).await.unwrap();
+        assert_eq!(record.pin.unwrap().get(), Some(&1234));
+    }
+}
+
+#[cfg(feature = "transit-crypto")]
+mod jwt {
+    use super::TransitEndpoint;
+    use base64::{engine::general_purpose, Engine as _};
+    use vaultrs::api::transit::requests::CreateKeyRequest;
+    use vaultrs::api::transit::{
+        HashAlgorithm, KeyType, MarshalingAlgorithm, Signature, SignatureAlgorithm,
+    };
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::jwt::{self, JwsAlgorithm, JwsOptions, JwtClaims};
+    use vaultrs::transit::key;
+    use vaultrs::transit::offline::{OfflineVerifier, VerifyOptions};
+
+    pub async fn test_sign(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        let claims = JwtClaims {
+            sub: Some("user".into()),
+            exp: Some(4102444800),
+            ..Default::default()
+        };
+        for (name, key_type, alg, hash, signature_algorithm) in [
+            (
+                "jwt-ed25519",
+                KeyType::Ed25519,
+                JwsAlgorithm::EdDSA,
+                HashAlgorithm::Sha2_256,
+                None,
+            ),
+            (
+                "jwt-p384",
+                KeyType::EcdsaP384,
+                JwsAlgorithm::ES384,
+                HashAlgorithm::Sha2_384,
+                None,
+            ),
+            (
+                "jwt-rsa",
+                KeyType::Rsa2048,
+                JwsAlgorithm::RS256,
+                HashAlgorithm::Sha2_256,
+                Some(SignatureAlgorithm::Pkcs1v15),
+            ),
+            (
+                "jwt-rsa-pss",
+                KeyType::Rsa2048,
+                JwsAlgorithm::PS512,
+                HashAlgorithm::Sha2_512,
+                Some(SignatureAlgorithm::Pss),
+            ),
+        ] {
+            key::create(
+                client,
+                mount,
+                name,
+                Some(CreateKeyRequest::builder().key_type(key_type)),
+            )
+            .await
+            .unwrap();
+            let opts = JwsOptions {
+                algorithm: Some(alg),
+                ..Default::default()
+            };
+            let token = jwt::sign(client, mount, name, &claims, &opts)
+                .await
+                .unwrap();
+
+            let parts: Vec<_> = token.split('.').collect();
+            assert_eq!(parts.len(), 3);
+            let header: serde_json::Value =
+                serde_json::from_slice(&general_purpose::URL_SAFE_NO_PAD.decode(parts[0]).unwrap())
+                    .unwrap();
+            assert_eq!(header["alg"], alg.to_string());
+            assert_eq!(header["typ"], "JWT");
+            assert_eq!(header["kid"], jwt::kid(name, 1));
+            let payload: serde_json::Value =
+                serde_json::from_slice(&general_purpose::URL_SAFE_NO_PAD.decode(parts[1]).unwrap())
+                    .unwrap();
+            assert_eq!(payload["sub"], "user");
+
+            let verifier = OfflineVerifier::new(client, mount, name).await.unwrap();
+            let opts = VerifyOptions {
+                hash_algorithm: Some(hash),
+                signature_algorithm,
+                marshaling_algorithm: Some(MarshalingAlgorithm::Jws),
+                ..Default::default()
+            };
+            let signing_input = format!("{}.{}", parts[0], parts[1]);
+            let signature = Signature::parse(format!("vault:v1:{}", parts[2])).unwrap();
+            let res = verifier.verify(signing_input.as_bytes(), &signature, &opts);
+            assert!(res.await.unwrap());
+        }
+
+        let opts = JwsOptions {
+            algorithm: Some(JwsAlgorithm::ES256),
+            ..Default::default()
+        };
+        let res = jwt::sign(client, mount, "jwt-rsa", &claims, &opts).await;
+        assert!(matches!(res, Err(ClientError::JwsError { .. })));
+    }
+
+    pub async fn test_jwks(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        key::rotate(client, mount, "jwt-ed25519").await.unwrap();
+
+        let jwks = jwt::jwks(client, mount, "jwt-ed25519", None).await.unwrap();
+        assert_eq!(jwks.keys.len(), 2);
+        assert!(jwks.keys.iter().all(|k| k.kty == "OKP"));
+        assert_eq!(jwks.keys[1].kid, jwt::kid("jwt-ed25519", 2));
+
+        // RSA keys only advertise an algorithm when given one
+        let jwks = jwt::jwks(client, mount, "jwt-rsa", None).await.unwrap();
+        assert_eq!(jwks.keys[0].kty, "RSA");
+        assert!(jwks.keys[0].n.is_some());
+        assert!(jwks.keys[0].alg.is_none());
+        let jwks = jwt::jwks(client, mount, "jwt-rsa-pss", Some(JwsAlgorithm::PS512))
+            .await
+            .unwrap();
+        assert_eq!(jwks.keys[0].alg, Some(JwsAlgorithm::PS512));
+
+        let res = jwt::jwks(client, mount, &endpoint.keys.basic, None).await;
+        assert!(matches!(res, Err(ClientError::JwsError { .. })));
+    }
+}
+
+#[cfg(feature = "transit-crypto")]
+mod offline {
+    use super::TransitEndpoint;
+    use base64::{engine::general_purpose, Engine as _};
+    use vaultrs::api::transit::requests::{CreateKeyRequest, SignDataRequest};
+    use vaultrs::api::transit::{
+        HashAlgorithm, KeyType, MarshalingAlgorithm, Signature, SignatureAlgorithm,
+    };
+    use vaultrs::client::VaultClient;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::offline::{OfflineVerifier, VerifyOptions};
+    use vaultrs::transit::{data, key};
+
+    async fn sign(
+        client: &VaultClient,
+        mount: &str,
+        name: &str,
+        input: &[u8],
+        opts: &mut vaultrs::api::transit::requests::SignDataRequestBuilder,
+    ) -> Signature {
+        data::sign(
+            client,
+            mount,
+            name,
+            &general_purpose::STANDARD.encode(input),
+            Some(opts),
+        )
+        .await
+        .unwrap()
+        .signature
+    }
+
+    pub async fn test_verify(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        for (name, key_type) in [
+            ("offline-ed25519", KeyType::Ed25519),
+            ("offline-p256", KeyType::EcdsaP256),
+            ("offline-p384", KeyType::EcdsaP384),
+            ("offline-p521", KeyType::EcdsaP521),
+            ("offline-rsa", KeyType::Rsa2048),
+        ] {
+            key::create(
+                client,
+                mount,
+                name,
+                Some(CreateKeyRequest::builder().key_type(key_type)),
+            )
+            .await
+            .unwrap();
+            let verifier = OfflineVerifier::new(client, mount, name).await.unwrap();
+
+            let signature = sign(
+                client,
+                mount,
+                name,
+                b"data",
+                &mut SignDataRequest::builder(),
+            )
+            .await;
+            let opts = VerifyOptions::default();
+            assert!(verifier.verify(b"data", &signature, &opts).await.unwrap());
+            assert!(!verifier.verify(b"other", &signature, &opts).await.unwrap());
+
+            let signature = sign(
+                client,
+                mount,
+                name,
+                b"data",
+                SignDataRequest::builder()
+                    .hash_algorithm(HashAlgorithm::Sha2_512)
+                    .marshaling_algorithm(MarshalingAlgorithm::Jws)
+                    .signature_algorithm(SignatureAlgorithm::Pkcs1v15),
+            )
+            .await;
+            let opts = VerifyOptions {
+                hash_algorithm: Some(HashAlgorithm::Sha2_512),
+                marshaling_algorithm: Some(MarshalingAlgorithm::Jws),
+                signature_algorithm: Some(SignatureAlgorithm::Pkcs1v15),
+                ..Default::default()
+            };
+            assert!(verifier.verify(b"data", &signature, &opts).await.unwrap());
+        }
+
+        let verifier = OfflineVerifier::new(client, mount, "offline-p256")
+            .await
+            .unwrap();
+        let signature = Signature::parse("vault:v1:not-base64!").unwrap();
+        let res = verifier
+            .verify(b"data", &signature, &VerifyOptions::default())
+            .await;
+        assert!(matches!(
+            res,
+            Err(ClientError::OfflineVerificationError { .. })
+        ));
+
+        let res = OfflineVerifier::new(client, mount, &endpoint.keys.basic).await;
+        assert!(matches!(
+            res,
+            Err(ClientError::OfflineVerificationError { .. })
+        ));
+    }
+
+    pub async fn test_refresh(endpoint: &TransitEndpoint) {
+        let client = &endpoint.client;
+        let mount = &endpoint.path;
+        let verifier = OfflineVerifier::new(client, mount, "offline-rsa")
+            .await
+            .unwrap();
+        assert_eq!(verifier.latest_version(), 1);
+
+        key::rotate(client, mount, "offline-rsa").await.unwrap();
+        let signature = sign(
+            client,
+            mount,
+            "offline-rsa",
+            b"data",
+            &mut SignDataRequest::builder(),
+        )
+        .await;
+        assert_eq!(signature.version(), 2);
+
+        let res = verifier
+            .verify(b"data", &signature, &VerifyOptions::default())
+            .await;
+        assert!(res.unwrap());
+        assert_eq!(verifier.latest_version(), 2);
+    }
+}
+
+#[cfg(feature = "transit-crypto")]
+mod envelope {
+    use super::TransitEndpoint;
+    use std::time::Duration;
+    use vaultrs::error::ClientError;
+    use vaultrs::transit::envelope::{Envelope, EnvelopeCipher, EnvelopeOptions};
+
+    pub async fn test_encrypt_and_decrypt(endpoint: &TransitEndpoint) {
+        let cipher = EnvelopeCipher::new(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            EnvelopeOptions::default(),
+        );
+        let first = cipher.encrypt(b"first").await.unwrap();
+        let second = cipher.encrypt(b"second").await.unwrap();
+        assert_eq!(first.key, endpoint.keys.basic);
+        assert!(first.wrapped_key.starts_with("vault:v"));
+        // The cached data key is reused
+        assert_eq!(first.wrapped_key, second.wrapped_key);
+        assert_ne!(first.nonce, second.nonce);
+
+        // A cipher without a cache unwraps the data key through Vault
+        let uncached = EnvelopeCipher::new(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            EnvelopeOptions {
+                ttl: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+        let decrypted = uncached
+            .decrypt(&Envelope::from_bytes(&first.to_bytes().unwrap()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(decrypted, b"first");
+        let third = uncached.encrypt(b"third").await.unwrap();
+        assert_ne!(first.wrapped_key, third.wrapped_key);
+        assert_eq!(cipher.decrypt(&third).await.unwrap(), b"third");
+
+        // A new data key is generated once the cached one was used enough
+        let limited = EnvelopeCipher::new(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            EnvelopeOptions {
+                max_uses: 2,
+                ..Default::default()
+            },
+        );
+        let keys = [
+            limited.encrypt(b"1").await.unwrap().wrapped_key,
+            limited.encrypt(b"2").await.unwrap().wrapped_key,
+            limited.encrypt(b"3").await.unwrap().wrapped_key,
+        ];
+        assert_eq!(keys[0], keys[1]);
+        assert_ne!(keys[1], keys[2]);
+
+        let mut tampered = second.clone();
+        tampered.key_version += 1;
+        let res = cipher.decrypt(&tampered).await;
+        assert!(matches!(res, Err(ClientError::EnvelopeError { .. })));
+    }
+
+    pub async fn test_reader(endpoint: &TransitEndpoint) {
+        let cipher = EnvelopeCipher::new(
+            &endpoint.client,
+            &endpoint.path,
+            &endpoint.keys.basic,
+            EnvelopeOptions::default(),
+        );
+        let data = vec![42u8; 100_000];
+        let envelope = cipher.encrypt_reader(&data[..]).await.unwrap();
+        let bytes = envelope.to_bytes().unwrap();
+
+        cipher.clear_cache();
+        let decrypted = cipher.decrypt_reader(&bytes[..]).await.unwrap();
+        assert_eq!(decrypted, data);
+    }
 }
 
 mod cache {
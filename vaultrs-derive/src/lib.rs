@@ -0,0 +1,184 @@
+//! # vaultrs-derive
+//!
+//! > Derive macros for loading typed secrets with [vaultrs][1].
+//!
+//! This crate is re-exported by `vaultrs` when its `derive` feature is
+//! enabled and should not be depended on directly. See
+//! `vaultrs::secret::VaultSecret` for usage.
+//!
+//! [1]: https://docs.rs/vaultrs
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, LitInt,
+    LitStr, PathArguments, Type,
+};
+
+/// Derives `vaultrs::secret::VaultSecret` for a struct with named fields.
+///
+/// Every field is read from a key of a secret stored in a KV engine. The
+/// location is configured with `#[vault(...)]` attributes which may be placed
+/// on the struct to provide defaults for all of its fields:
+///
+/// * `mount` - The mount path of the KV engine
+/// * `path` - The path of the secret within the engine
+/// * `key` - The key within the secret (fields only, defaults to the field name)
+/// * `version` - The version of the KV engine, either `1` or `2` (default)
+///
+/// Fields of type `Option<T>` are set to `None` when their key or the whole
+/// secret is missing.
+#[proc_macro_derive(VaultSecret, attributes(vault))]
+pub fn derive_vault_secret(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The location options collected from a `#[vault(...)]` attribute
+#[derive(Clone, Default)]
+struct Location {
+    mount: Option<LitStr>,
+    path: Option<LitStr>,
+    key: Option<LitStr>,
+    version: Option<LitInt>,
+}
+
+impl Location {
+    /// Parses all `#[vault(...)]` attributes in the given list
+    fn parse(attrs: &[Attribute], allow_key: bool) -> syn::Result<Location> {
+        let mut loc = Location::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("vault")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("mount") {
+                    loc.mount = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("path") {
+                    loc.path = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("key") && allow_key {
+                    loc.key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("version") {
+                    let version: LitInt = meta.value()?.parse()?;
+                    match version.base10_parse::<u8>()? {
+                        1 | 2 => loc.version = Some(version),
+                        _ => return Err(Error::new(version.span(), "version must be 1 or 2")),
+                    }
+                } else {
+                    return Err(meta.error("unsupported vault attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(loc)
+    }
+
+    /// Fills any unset options from the given defaults
+    fn or(self, defaults: &Location) -> Location {
+        Location {
+            mount: self.mount.or_else(|| defaults.mount.clone()),
+            path: self.path.or_else(|| defaults.path.clone()),
+            key: self.key.or_else(|| defaults.key.clone()),
+            version: self.version.or_else(|| defaults.version.clone()),
+        }
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "VaultSecret can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "VaultSecret can only be derived for structs",
+            ))
+        }
+    };
+    let defaults = Location::parse(&input.attrs, false)?;
+
+    let mut locations = Vec::new();
+    let mut optional = Vec::new();
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let loc = Location::parse(&field.attrs, true)?.or(&defaults);
+        let mount = loc.mount.ok_or_else(|| {
+            Error::new(
+                field.span(),
+                "missing `mount`, set it on the field or struct",
+            )
+        })?;
+        let path = loc.path.ok_or_else(|| {
+            Error::new(
+                field.span(),
+                "missing `path`, set it on the field or struct",
+            )
+        })?;
+        let key = loc
+            .key
+            .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        let version = match loc.version.map(|v| v.base10_parse::<u8>()).transpose()? {
+            Some(1) => quote!(::vaultrs::secret::KvVersion::V1),
+            _ => quote!(::vaultrs::secret::KvVersion::V2),
+        };
+
+        let location = quote! {
+            ::vaultrs::secret::SecretLocation::new(#version, #mount, #path)
+        };
+        let getter = match is_option(&field.ty) {
+            true => {
+                optional.push(location.clone());
+                quote!(get_optional)
+            }
+            false => {
+                locations.push(location.clone());
+                quote!(get)
+            }
+        };
+        inits.push(quote! {
+            #ident: secrets.#getter(&#location, #key)?
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::vaultrs::secret::VaultSecret for #name #ty_generics #where_clause {
+            fn load<'a, C: ::vaultrs::client::Client>(
+                client: &'a C,
+            ) -> ::vaultrs::secret::LoadFuture<'a, Self> {
+                ::std::boxed::Box::pin(async move {
+                    let secrets = ::vaultrs::secret::Secrets::fetch(
+                        client,
+                        ::std::vec![#(#locations),*],
+                        ::std::vec![#(#optional),*],
+                    )
+                    .await?;
+                    ::std::result::Result::Ok(Self {
+                        #(#inits),*
+                    })
+                })
+            }
+        }
+    })
+}
+
+/// Returns whether the given type is an `Option<T>`
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => p
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == "Option" && matches!(s.arguments, PathArguments::AngleBracketed(_)))
+            .unwrap_or(false),
+        _ => false,
+    }
+}